@@ -0,0 +1,562 @@
+//! Attack lookups.
+//!
+//! Knights, kings and pawns index tables built at compile time, as do [`Square::between`] and
+//! [`Square::ray`]. Sliding pieces use magic bitboards: the relevant blockers of a square are
+//! multiplied by a magic number so that the top bits index a table of precomputed attacks. These
+//! tables are filled the first time a lookup is made, and are read-only after that, so lookups
+//! from any number of threads are fine.
+//!
+//! An [`AttackMap`] looks up every piece of a board once, for the parts of the engine that ask
+//! about many squares at a time.
+
+use std::sync::OnceLock;
+
+use crate::{BitBoard, Board, Color, Piece, PieceKind, Square};
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+/// Rook magics per square, found with a sparse random search.
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x1880_08A2_8040_0050, 0x0140_00C0_2000_9001, 0x1100_1008_4020_0100, 0x0200_0804_1200_2040,
+    0x0100_1100_0402_0800, 0x8200_0102_0004_9008, 0x2100_4410_8100_0A00, 0x2080_00A3_D080_0100,
+    0x0002_0020_4082_0100, 0x0900_C010_0544_2000, 0x2002_0022_0010_4080, 0xA504_8008_0080_1000,
+    0x0413_0010_0500_0800, 0x0000_8080_0200_0400, 0x0200_8080_0100_0200, 0x1202_0004_0120_4082,
+    0x8308_2880_0040_0080, 0x0120_00C0_0050_0022, 0x4000_1100_4104_2002, 0x0010_0300_10A1_0008,
+    0x0032_1100_0800_0500, 0x8000_8080_0200_0400, 0x84A0_0101_0004_0200, 0x1060_0200_0040_8104,
+    0x0040_4010_8000_2080, 0x0A40_2002_8040_0080, 0x0400_4011_0020_0108, 0x0000_1000_8080_0800,
+    0x0848_0011_0008_0500, 0xC002_0080_8002_0400, 0x0001_210C_0002_1008, 0x6002_801B_8008_4100,
+    0x1200_4000_2080_1080, 0x0020_2000_8080_4004, 0x0010_4202_2200_1280, 0x0208_0200_1010_0100,
+    0x1010_0800_8080_0400, 0x0200_8002_0080_0400, 0x80C6_9022_2C00_1809, 0x2000_1100_8200_0054,
+    0x8250_AA40_0484_8000, 0x00C0_4020_100A_4000, 0x0600_1000_2000_8080, 0x0008_0080_1000_8008,
+    0x0028_0100_0409_0010, 0x1046_0400_0200_8080, 0xC008_0802_1004_0001, 0x0104_0408_AC42_0003,
+    0x0400_4000_8000_2080, 0xA800_2000_4010_0C40, 0x00C3_0010_2000_4500, 0x00A9_2052_00C0_4A00,
+    0x4000_0800_8004_0080, 0x0808_8004_0002_0080, 0x0008_0802_4110_0400, 0x0050_8000_4100_1880,
+    0x8000_C080_0410_2301, 0x2200_2101_1200_4682, 0x00C0_2010_8040_0A02, 0x0010_0408_2010_0101,
+    0x0C02_0104_1020_0802, 0x4011_0002_0400_0801, 0x100A_3008_0240_8104, 0x0086_0104_0062_4492,
+];
+
+/// Bishop magics per square, found with a sparse random search.
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0x8004_0110_0428_8480, 0x8220_2C01_0053_0308, 0x1242_108B_1101_2000, 0x005A_2082_0020_0050,
+    0x200C_0C60_0504_0004, 0x0002_1110_2801_0094, 0x0008_4128_2110_0010, 0x0802_0284_0101_5000,
+    0x6080_1020_2224_0044, 0x0001_8902_1802_0021, 0x1000_0488_2081_0010, 0x0A00_4410_6204_0884,
+    0x2028_4424_2004_0200, 0x0800_1201_04A2_0008, 0x0004_0048_2A30_1008, 0x0005_0104_0201_8400,
+    0x0021_0004_0810_4100, 0x8008_0202_5004_0080, 0x0020_8019_0104_0080, 0x4400_9008_0200_4000,
+    0x9502_0210_1210_0C01, 0x8F09_0000_8041_4000, 0x01C6_0900_4802_6802, 0x0000_8814_2088_0880,
+    0x0060_0820_0688_0800, 0x9008_0810_0450_40A2, 0x8008_0900_5202_0603, 0x0002_0080_2800_8002,
+    0x0041_0010_0100_4010, 0x4050_0108_4080_9000, 0x0004_0082_0402_0110, 0x1084_0048_0022_1201,
+    0x2001_4440_0010_1101, 0x0001_0150_0068_3045, 0x0020_4448_0010_0420, 0x0004_0404_0008_0210,
+    0x1D02_0384_0002_0020, 0x0010_1000_2000_4404, 0x0808_0204_0010_4110, 0x0408_3104_2040_4201,
+    0x2028_2444_0410_2119, 0x40C1_8A10_1100_0210, 0x0004_1201_1000_4900, 0x8000_0140_1040_0200,
+    0x9000_0809_0440_1C01, 0x0084_0098_0840_0200, 0x0020_2204_4100_2040, 0x8050_2106_0220_0880,
+    0x0104_00C4_2420_0084, 0x0001_0082_5002_1000, 0x1086_0050_4410_1200, 0x0010_0000_4202_0100,
+    0x0006_1009_1024_0048, 0x2000_043C_2832_0180, 0x0006_202C_0404_8040, 0x1008_1828_0188_A900,
+    0x1001_0421_8C20_0804, 0xC010_9884_0401_0400, 0x0000_4C00_5208_0400, 0x8084_0080_0020_8800,
+    0xA008_2880_1002_0220, 0x128A_6004_0408_8201, 0x0200_0942_9012_0200, 0x0A10_2008_0100_2020,
+];
+
+const KNIGHT_ATTACKS: [BitBoard; 64] = leaper_table(Leaper::Knight);
+const KING_ATTACKS: [BitBoard; 64] = leaper_table(Leaper::King);
+const PAWN_ATTACKS: [[BitBoard; 64]; 2] = [
+    leaper_table(Leaper::WhitePawn),
+    leaper_table(Leaper::BlackPawn),
+];
+
+/// Squares strictly between two aligned squares, indexed by both squares.
+pub(crate) static BETWEEN: [[BitBoard; 64]; 64] = line_tables().0;
+/// From the first square through the second to the edge of the board, without the first.
+pub(crate) static RAY: [[BitBoard; 64]; 64] = line_tables().1;
+
+/// Knight attacks from `square`.
+#[inline]
+pub const fn knight(square: Square) -> BitBoard {
+    KNIGHT_ATTACKS[square.raw_index() as usize]
+}
+
+/// King attacks from `square`.
+#[inline]
+pub const fn king(square: Square) -> BitBoard {
+    KING_ATTACKS[square.raw_index() as usize]
+}
+
+/// Squares a pawn of `color` on `square` attacks.
+#[inline]
+pub const fn pawn(square: Square, color: Color) -> BitBoard {
+    PAWN_ATTACKS[color as usize][square.raw_index() as usize]
+}
+
+/// Rook attacks from `square` with the given occupancy.
+#[inline]
+pub fn rook(square: Square, occupancy: BitBoard) -> BitBoard {
+    let tables = tables();
+    tables.lookup(&tables.rook[square.raw_index() as usize], occupancy)
+}
+
+/// Bishop attacks from `square` with the given occupancy.
+#[inline]
+pub fn bishop(square: Square, occupancy: BitBoard) -> BitBoard {
+    let tables = tables();
+    tables.lookup(&tables.bishop[square.raw_index() as usize], occupancy)
+}
+
+/// Queen attacks from `square` with the given occupancy.
+#[inline]
+pub fn queen(square: Square, occupancy: BitBoard) -> BitBoard {
+    rook(square, occupancy) | bishop(square, occupancy)
+}
+
+/// Attacks of `piece` from `square` with the given occupancy.
+pub fn piece(piece: Piece, square: Square, occupancy: BitBoard) -> BitBoard {
+    match piece.kind {
+        PieceKind::Pawn => pawn(square, piece.color),
+        PieceKind::Knight => knight(square),
+        PieceKind::Bishop => bishop(square, occupancy),
+        PieceKind::Rook => rook(square, occupancy),
+        PieceKind::Queen => queen(square, occupancy),
+        PieceKind::King => king(square),
+    }
+}
+
+/// Build the lookup tables up front instead of on the first lookup.
+#[inline]
+pub fn init() {
+    tables();
+}
+
+/// The squares every piece of a board attacks.
+///
+/// Only direct attacks count, pieces lined up behind another slider (x-rays) are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackMap {
+    /// Attacks of the piece on each square, empty for empty squares.
+    by_square: [BitBoard; 64],
+    /// Pieces by color.
+    pieces: [BitBoard; 2],
+    /// Squares attacked by at least one piece of a color.
+    once: [BitBoard; 2],
+    /// Squares attacked by at least two pieces of a color.
+    twice: [BitBoard; 2],
+}
+
+impl AttackMap {
+    pub fn new(board: &Board) -> Self {
+        let mut map = Self {
+            by_square: [BitBoard::ZERO; 64],
+            pieces: [Color::White, Color::Black].map(|color| board.get_color_bb(color)),
+            once: [BitBoard::ZERO; 2],
+            twice: [BitBoard::ZERO; 2],
+        };
+
+        let occupancy = board.get_full_bb();
+        for square in occupancy.set_iter() {
+            let Some(piece) = board.get(square) else {
+                continue;
+            };
+
+            let attacks = self::piece(piece, square, occupancy);
+            let color = piece.color as usize;
+            map.by_square[square.raw_index() as usize] = attacks;
+            map.twice[color] |= map.once[color] & attacks;
+            map.once[color] |= attacks;
+        }
+
+        map
+    }
+
+    /// Squares attacked by the piece on `square`.
+    #[inline]
+    pub const fn from(&self, square: Square) -> BitBoard {
+        self.by_square[square.raw_index() as usize]
+    }
+
+    /// Squares attacked by any piece of `color`.
+    #[inline]
+    pub const fn attacked_by(&self, color: Color) -> BitBoard {
+        self.once[color as usize]
+    }
+
+    /// Squares attacked by more than one piece of `color`.
+    #[inline]
+    pub const fn attacked_twice_by(&self, color: Color) -> BitBoard {
+        self.twice[color as usize]
+    }
+
+    /// Pieces of `color` attacking any of the `targets`.
+    pub fn attackers(&self, targets: BitBoard, color: Color) -> BitBoard {
+        let mut attackers = BitBoard::ZERO;
+        for square in self.pieces[color as usize].set_iter() {
+            if self.from(square).intersects(targets) {
+                attackers.set(square, true);
+            }
+        }
+
+        attackers
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    /// Squares whose occupancy matters, without the edges.
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    /// Start of this square's entries in the shared attack table.
+    offset: usize,
+}
+
+#[derive(Debug)]
+struct Tables {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    attacks: Vec<BitBoard>,
+}
+
+impl Tables {
+    #[inline]
+    fn lookup(&self, magic: &Magic, occupancy: BitBoard) -> BitBoard {
+        let index = ((occupancy.0 & magic.mask).wrapping_mul(magic.magic) >> magic.shift) as usize;
+        self.attacks[magic.offset + index]
+    }
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut tables = Tables {
+            rook: [Magic::default(); 64],
+            bishop: [Magic::default(); 64],
+            attacks: Vec::with_capacity(0x1_9000 + 0x1480),
+        };
+
+        for square in 0..64 {
+            tables.rook[square as usize] = fill(
+                square,
+                ROOK_MAGICS[square as usize],
+                &ROOK_DIRECTIONS,
+                &mut tables.attacks,
+            );
+            tables.bishop[square as usize] = fill(
+                square,
+                BISHOP_MAGICS[square as usize],
+                &BISHOP_DIRECTIONS,
+                &mut tables.attacks,
+            );
+        }
+
+        tables
+    })
+}
+
+/// Append the attack table for `square` to `attacks`.
+fn fill(square: u8, magic: u64, directions: &[(i8, i8)], attacks: &mut Vec<BitBoard>) -> Magic {
+    let mask = relevant_mask(square, directions);
+    let bits = mask.count_ones();
+    let magic = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: attacks.len(),
+    };
+
+    attacks.resize(magic.offset + (1 << bits), BitBoard::ZERO);
+
+    // every subset of the mask, enumerated with the carry-rippler trick
+    let mut subset = 0u64;
+    loop {
+        let index = (subset.wrapping_mul(magic.magic) >> magic.shift) as usize;
+        let attack = BitBoard(slide(square, subset, directions));
+
+        // constructive collisions are fine
+        debug_assert!(
+            attacks[magic.offset + index] == BitBoard::ZERO
+                || attacks[magic.offset + index] == attack
+        );
+        attacks[magic.offset + index] = attack;
+
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+
+    magic
+}
+
+/// Squares along `directions` that can block, i.e. without the last square of every ray.
+fn relevant_mask(square: u8, directions: &[(i8, i8)]) -> u64 {
+    let (rank, file) = ((square / 8) as i8, (square % 8) as i8);
+    let mut mask = 0;
+
+    for &(dr, df) in directions {
+        let (mut r, mut f) = (rank + dr, file + df);
+
+        while (0..8).contains(&(r + dr)) && (0..8).contains(&(f + df)) {
+            mask |= 1 << (r * 8 + f);
+            r += dr;
+            f += df;
+        }
+    }
+
+    mask
+}
+
+/// Pieces that don't slide, each with its own table.
+#[derive(Clone, Copy)]
+enum Leaper {
+    Knight,
+    King,
+    WhitePawn,
+    BlackPawn,
+}
+
+const fn leaper_table(leaper: Leaper) -> [BitBoard; 64] {
+    let mut table = [BitBoard::ZERO; 64];
+
+    let mut i = 0;
+    while i < 64 {
+        let bb = BitBoard(1 << i);
+
+        table[i] = match leaper {
+            Leaper::Knight => knight_step(bb),
+            Leaper::King => king_step(bb),
+            Leaper::WhitePawn => BitBoard(bb.shift_north_east().0 | bb.shift_north_west().0),
+            Leaper::BlackPawn => BitBoard(bb.shift_south_east().0 | bb.shift_south_west().0),
+        };
+        i += 1;
+    }
+
+    table
+}
+
+const fn knight_step(bb: BitBoard) -> BitBoard {
+    let (north, south) = (bb.shift_north(), bb.shift_south());
+    let (east, west) = (bb.shift_east(), bb.shift_west());
+
+    BitBoard(
+        north.shift_north_east().0
+            | north.shift_north_west().0
+            | south.shift_south_east().0
+            | south.shift_south_west().0
+            | east.shift_north_east().0
+            | east.shift_south_east().0
+            | west.shift_north_west().0
+            | west.shift_south_west().0,
+    )
+}
+
+const fn king_step(bb: BitBoard) -> BitBoard {
+    // spread sideways along the rank first, then up and down
+    let rank = BitBoard(bb.0 | bb.shift_east().0 | bb.shift_west().0);
+
+    BitBoard((rank.0 | rank.shift_north().0 | rank.shift_south().0) & !bb.0)
+}
+
+/// The [`BETWEEN`] and [`RAY`] tables, empty for squares that aren't on a common line.
+const fn line_tables() -> ([[BitBoard; 64]; 64], [[BitBoard; 64]; 64]) {
+    let mut between = [[BitBoard::ZERO; 64]; 64];
+    let mut ray = [[BitBoard::ZERO; 64]; 64];
+
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            let (dr, df) = if d < 4 {
+                ROOK_DIRECTIONS[d]
+            } else {
+                BISHOP_DIRECTIONS[d - 4]
+            };
+
+            // walk to the edge once for the whole ray, then again to fill in every square on it
+            let mut full = 0;
+            let mut to = step_towards(from, dr, df);
+            while let Some(square) = to {
+                full |= 1 << square;
+                to = step_towards(square, dr, df);
+            }
+
+            let mut passed = 0;
+            let mut to = step_towards(from, dr, df);
+            while let Some(square) = to {
+                between[from][square] = BitBoard(passed);
+                ray[from][square] = BitBoard(full);
+                passed |= 1 << square;
+                to = step_towards(square, dr, df);
+            }
+
+            d += 1;
+        }
+
+        from += 1;
+    }
+
+    (between, ray)
+}
+
+/// The square one step away in a direction, if it's still on the board.
+const fn step_towards(square: usize, dr: i8, df: i8) -> Option<usize> {
+    let (rank, file) = ((square / 8) as i8 + dr, (square % 8) as i8 + df);
+
+    if rank >= 0 && rank < 8 && file >= 0 && file < 8 {
+        Some((rank * 8 + file) as usize)
+    } else {
+        None
+    }
+}
+
+/// Slow ray scan used to fill the tables.
+fn slide(square: u8, occupancy: u64, directions: &[(i8, i8)]) -> u64 {
+    let (rank, file) = ((square / 8) as i8, (square % 8) as i8);
+    let mut attacks = 0;
+
+    for &(dr, df) in directions {
+        let (mut r, mut f) = (rank + dr, file + df);
+
+        while (0..8).contains(&r) && (0..8).contains(&f) {
+            let bit = 1 << (r * 8 + f);
+            attacks |= bit;
+
+            if occupancy & bit != 0 {
+                break;
+            }
+
+            r += dr;
+            f += df;
+        }
+    }
+
+    attacks
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    #[test]
+    fn matches_ray_scan() {
+        let mut rng = Rng(0xDEAD_BEEF);
+
+        for index in 0..64 {
+            let square = Square::from_index_unchecked(index);
+
+            for _ in 0..200 {
+                let occupancy = rng.next() & rng.next();
+
+                assert_eq!(
+                    rook(square, BitBoard(occupancy)).0,
+                    slide(index, occupancy, &ROOK_DIRECTIONS),
+                    "rook on {square}"
+                );
+                assert_eq!(
+                    bishop(square, BitBoard(occupancy)).0,
+                    slide(index, occupancy, &BISHOP_DIRECTIONS),
+                    "bishop on {square}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn attack_map() {
+        let board = Board::from_fen_placement("4k3/8/8/3q4/8/2N5/8/R3K3").unwrap();
+        let map = AttackMap::new(&board);
+        let square = |name: &str| name.parse::<Square>().unwrap();
+
+        for square in board.get_full_bb().set_iter() {
+            let piece = board.get(square).unwrap();
+            assert_eq!(
+                map.from(square),
+                self::piece(piece, square, board.get_full_bb())
+            );
+        }
+        assert!(map.from(square("e4")).is_empty());
+
+        // b1 is covered by the knight and the rook, a8 by the rook only, nothing covers a1
+        assert!(!map.attacked_by(Color::White).get(square("a1")));
+        assert!(map.attacked_twice_by(Color::White).get(square("b1")));
+        assert!(!map.attacked_twice_by(Color::White).get(square("a8")));
+        assert!(map.attacked_by(Color::Black).get(square("a8")));
+
+        let targets = BitBoard::from_square(square("d1")) | BitBoard::from_square(square("b5"));
+        let white = map.attackers(targets, Color::White);
+        assert_eq!(squares(white), ["a1", "e1", "c3"]);
+        assert_eq!(squares(map.attackers(targets, Color::Black)), ["d5"]);
+    }
+
+    fn squares(bb: BitBoard) -> Vec<String> {
+        bb.set_iter().map(|square| square.to_string()).collect()
+    }
+
+    #[test]
+    fn open_board() {
+        let d4 = Square::at(3, 3).unwrap();
+
+        assert_eq!(rook(d4, BitBoard::ZERO).count(), 14);
+        assert_eq!(bishop(d4, BitBoard::ZERO).count(), 13);
+        assert_eq!(queen(d4, BitBoard::ZERO).count(), 27);
+    }
+
+    #[test]
+    fn non_sliders() {
+        const KNIGHT_JUMPS: [(i8, i8); 8] = [
+            (2, 1),
+            (2, -1),
+            (-2, 1),
+            (-2, -1),
+            (1, 2),
+            (1, -2),
+            (-1, 2),
+            (-1, -2),
+        ];
+
+        // a scan with every square occupied stops after one step
+        let step = |index, directions: &[(i8, i8)]| BitBoard(slide(index, u64::MAX, directions));
+        let king_directions = [ROOK_DIRECTIONS, BISHOP_DIRECTIONS].concat();
+
+        for index in 0..64 {
+            let square = Square::from_index_unchecked(index);
+
+            assert_eq!(
+                knight(square),
+                step(index, &KNIGHT_JUMPS),
+                "knight on {square}"
+            );
+            assert_eq!(
+                king(square),
+                step(index, &king_directions),
+                "king on {square}"
+            );
+            assert_eq!(
+                pawn(square, Color::White),
+                step(index, &[(1, 1), (1, -1)]),
+                "white pawn on {square}"
+            );
+            assert_eq!(
+                pawn(square, Color::Black),
+                step(index, &[(-1, 1), (-1, -1)]),
+                "black pawn on {square}"
+            );
+        }
+
+        let d4 = Square::at(3, 3).unwrap();
+        let white_pawn = Piece {
+            color: Color::White,
+            kind: PieceKind::Pawn,
+        };
+        assert_eq!(piece(white_pawn, d4, BitBoard::ZERO).count(), 2);
+        assert_eq!(knight(d4).count(), 8);
+    }
+}
@@ -0,0 +1,172 @@
+//! Batch operations over slices of bitboards.
+//!
+//! With the `simd` feature these are computed eight boards at a time using portable SIMD,
+//! otherwise they fall back to plain scalar loops. Both paths produce identical results.
+
+use crate::{BitBoard, Square};
+
+/// Clear a square on every board.
+#[inline]
+pub fn clear_square(boards: &mut [BitBoard], square: Square) {
+    #[cfg(feature = "simd")]
+    simd::clear_square(boards, square);
+    #[cfg(not(feature = "simd"))]
+    scalar::clear_square(boards, square);
+}
+
+/// Fill every board towards the 8th rank (each set square also sets all squares above it).
+#[inline]
+pub fn north_fill(boards: &mut [BitBoard]) {
+    #[cfg(feature = "simd")]
+    simd::north_fill(boards);
+    #[cfg(not(feature = "simd"))]
+    scalar::north_fill(boards);
+}
+
+/// Fill every board towards the 1st rank (each set square also sets all squares below it).
+#[inline]
+pub fn south_fill(boards: &mut [BitBoard]) {
+    #[cfg(feature = "simd")]
+    simd::south_fill(boards);
+    #[cfg(not(feature = "simd"))]
+    scalar::south_fill(boards);
+}
+
+#[cfg_attr(feature = "simd", allow(dead_code))]
+mod scalar {
+    use super::*;
+
+    #[inline]
+    pub fn clear_square(boards: &mut [BitBoard], square: Square) {
+        for bb in boards {
+            bb.set(square, false);
+        }
+    }
+
+    #[inline]
+    pub fn north_fill(boards: &mut [BitBoard]) {
+        for bb in boards {
+            bb.0 |= bb.0 << 8;
+            bb.0 |= bb.0 << 16;
+            bb.0 |= bb.0 << 32;
+        }
+    }
+
+    #[inline]
+    pub fn south_fill(boards: &mut [BitBoard]) {
+        for bb in boards {
+            bb.0 |= bb.0 >> 8;
+            bb.0 |= bb.0 >> 16;
+            bb.0 |= bb.0 >> 32;
+        }
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd {
+    use std::simd::u64x8;
+
+    use super::*;
+
+    /// Apply `f` to the boards in chunks of eight lanes, padding the last chunk with empty boards.
+    #[inline]
+    fn lanes(boards: &mut [BitBoard], f: impl Fn(u64x8) -> u64x8) {
+        for chunk in boards.chunks_mut(8) {
+            let mut lanes = [0; 8];
+            for (lane, bb) in lanes.iter_mut().zip(chunk.iter()) {
+                *lane = bb.0;
+            }
+
+            let lanes = f(u64x8::from_array(lanes)).to_array();
+            for (bb, lane) in chunk.iter_mut().zip(lanes) {
+                bb.0 = lane;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn clear_square(boards: &mut [BitBoard], square: Square) {
+        let mask = u64x8::splat(!(1 << square.raw_index()));
+        lanes(boards, |bb| bb & mask);
+    }
+
+    #[inline]
+    pub fn north_fill(boards: &mut [BitBoard]) {
+        lanes(boards, |mut bb| {
+            bb |= bb << u64x8::splat(8);
+            bb |= bb << u64x8::splat(16);
+            bb |= bb << u64x8::splat(32);
+            bb
+        });
+    }
+
+    #[inline]
+    pub fn south_fill(boards: &mut [BitBoard]) {
+        lanes(boards, |mut bb| {
+            bb |= bb >> u64x8::splat(8);
+            bb |= bb >> u64x8::splat(16);
+            bb |= bb >> u64x8::splat(32);
+            bb
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BOARDS: [BitBoard; 11] = [
+        BitBoard(0),
+        BitBoard(u64::MAX),
+        BitBoard(0x00_FF_00_00_00_00_FF_00),
+        BitBoard(0x42_00_00_00_00_00_00_42),
+        BitBoard(0x24_00_00_00_00_00_00_24),
+        BitBoard(0x81_00_00_00_00_00_00_81),
+        BitBoard(0x08_00_00_00_00_00_00_08),
+        BitBoard(0x10_00_00_00_00_00_00_10),
+        BitBoard(0x00_00_10_00_00_20_00_00),
+        BitBoard(0x8000_0000_0000_0001),
+        BitBoard(0x0123_4567_89AB_CDEF),
+    ];
+
+    #[test]
+    fn clear_square_matches_scalar() {
+        for index in 0..64 {
+            let square = Square::from_index_unchecked(index);
+
+            let mut batch = BOARDS;
+            let mut expected = BOARDS;
+            clear_square(&mut batch, square);
+            scalar::clear_square(&mut expected, square);
+
+            assert_eq!(batch, expected);
+            assert!(batch.iter().all(|bb| !bb.get(square)));
+        }
+    }
+
+    #[test]
+    fn fills_match_scalar() {
+        let mut batch = BOARDS;
+        let mut expected = BOARDS;
+        north_fill(&mut batch);
+        scalar::north_fill(&mut expected);
+        assert_eq!(batch, expected);
+
+        let mut batch = BOARDS;
+        let mut expected = BOARDS;
+        south_fill(&mut batch);
+        scalar::south_fill(&mut expected);
+        assert_eq!(batch, expected);
+    }
+
+    #[test]
+    fn fills() {
+        let mut boards = [BitBoard(0x00_00_00_00_10_00_00_00)]; // e4
+        north_fill(&mut boards);
+        assert_eq!(boards[0], BitBoard(0x10_10_10_10_10_00_00_00));
+
+        let mut boards = [BitBoard(0x00_00_00_00_10_00_00_00)];
+        south_fill(&mut boards);
+        assert_eq!(boards[0], BitBoard(0x00_00_00_00_10_10_10_10));
+    }
+}
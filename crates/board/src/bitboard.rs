@@ -57,6 +57,137 @@ impl BitBoard {
         self.0 == 0
     }
 
+    /// Check if every set square is also set in `other`.
+    #[inline]
+    pub const fn is_subset_of(&self, other: BitBoard) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Check if any square is set in both boards.
+    #[inline]
+    pub const fn intersects(&self, other: BitBoard) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Check if both boards have no set squares in common.
+    #[inline]
+    pub const fn is_disjoint(&self, other: BitBoard) -> bool {
+        !self.intersects(other)
+    }
+
+    /// Check if exactly one square is set.
+    #[inline]
+    pub const fn exactly_one(&self) -> bool {
+        self.0 != 0 && !self.more_than_one()
+    }
+
+    /// Check if at least two squares are set.
+    #[inline]
+    pub const fn more_than_one(&self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    /// Occupancy of a rank as an 8-bit index, bit `i` being the square on file `i`.
+    #[inline]
+    pub const fn rank_occupancy(&self, rank: u8) -> u8 {
+        (self.0 >> (rank * 8)) as u8
+    }
+
+    /// Inverse of [`BitBoard::rank_occupancy`].
+    #[inline]
+    pub const fn from_rank_occupancy(rank: u8, occupancy: u8) -> Self {
+        Self((occupancy as u64) << (rank * 8))
+    }
+
+    /// Occupancy of a file as an 8-bit index, bit `i` being the square on rank `i`.
+    #[inline]
+    pub const fn file_occupancy(&self, file: u8) -> u8 {
+        // every a-file bit lands on a distinct bit of the top byte
+        (((self.0 >> file) & constants::A_FILE.0).wrapping_mul(constants::ANTI_DIAGONAL.0) >> 56)
+            as u8
+    }
+
+    /// Inverse of [`BitBoard::file_occupancy`].
+    #[inline]
+    pub const fn from_file_occupancy(file: u8, occupancy: u8) -> Self {
+        // spreads onto the h-file in reverse rank order
+        let h_file = (occupancy as u64).wrapping_mul(constants::DIAGONAL.0) & constants::H_FILE.0;
+        Self(h_file.swap_bytes() >> (7 - file))
+    }
+
+    /// Occupancy of the a1-h8 direction diagonal through `square` as an 8-bit index, bit `i`
+    /// being the square on file `i`.
+    #[inline]
+    pub const fn diagonal_occupancy(&self, square: Square) -> u8 {
+        let mask = constants::diagonal_mask(square);
+        ((self.0 & mask.0).wrapping_mul(constants::A_FILE.0) >> 56) as u8
+    }
+
+    /// Inverse of [`BitBoard::diagonal_occupancy`].
+    #[inline]
+    pub const fn from_diagonal_occupancy(square: Square, occupancy: u8) -> Self {
+        let mask = constants::diagonal_mask(square);
+        Self((occupancy as u64).wrapping_mul(constants::A_FILE.0) & mask.0)
+    }
+
+    /// Occupancy of the h1-a8 direction diagonal through `square` as an 8-bit index, bit `i`
+    /// being the square on file `i`.
+    #[inline]
+    pub const fn anti_diagonal_occupancy(&self, square: Square) -> u8 {
+        let mask = constants::anti_diagonal_mask(square);
+        ((self.0 & mask.0).wrapping_mul(constants::A_FILE.0) >> 56) as u8
+    }
+
+    /// Inverse of [`BitBoard::anti_diagonal_occupancy`].
+    #[inline]
+    pub const fn from_anti_diagonal_occupancy(square: Square, occupancy: u8) -> Self {
+        let mask = constants::anti_diagonal_mask(square);
+        Self((occupancy as u64).wrapping_mul(constants::A_FILE.0) & mask.0)
+    }
+
+    /// Mirror the board across the horizontal axis (rank 1 <-> rank 8).
+    #[inline]
+    pub const fn flip_vertical(&self) -> Self {
+        Self(self.0.swap_bytes())
+    }
+
+    /// Mirror the board across the vertical axis (a-file <-> h-file).
+    #[inline]
+    pub const fn flip_horizontal(&self) -> Self {
+        const K1: u64 = 0x55_55_55_55_55_55_55_55;
+        const K2: u64 = 0x33_33_33_33_33_33_33_33;
+        const K4: u64 = 0x0F_0F_0F_0F_0F_0F_0F_0F;
+
+        let mut x = self.0;
+        x = ((x >> 1) & K1) | ((x & K1) << 1);
+        x = ((x >> 2) & K2) | ((x & K2) << 2);
+        x = ((x >> 4) & K4) | ((x & K4) << 4);
+        Self(x)
+    }
+
+    /// Mirror the board across the a1-h8 diagonal (a8 <-> h1).
+    #[inline]
+    pub const fn flip_diag_a1h8(&self) -> Self {
+        const K1: u64 = 0x55_00_55_00_55_00_55_00;
+        const K2: u64 = 0x33_33_00_00_33_33_00_00;
+        const K4: u64 = 0x0F_0F_0F_0F_00_00_00_00;
+
+        let mut x = self.0;
+        let t = K4 & (x ^ (x << 28));
+        x ^= t ^ (t >> 28);
+        let t = K2 & (x ^ (x << 14));
+        x ^= t ^ (t >> 14);
+        let t = K1 & (x ^ (x << 7));
+        x ^= t ^ (t >> 7);
+        Self(x)
+    }
+
+    /// Rotate the board by 180 degrees (a1 <-> h8).
+    #[inline]
+    pub const fn rotate_180(&self) -> Self {
+        Self(self.0.reverse_bits())
+    }
+
     /// A bitboard with one square set to 1.
     #[inline]
     pub const fn from_square(square: Square) -> Self {
@@ -69,11 +200,135 @@ impl BitBoard {
         Square::from_index_unchecked(self.0.trailing_zeros() as u8)
     }
 
+    /// Move every square one rank up, squares on the 8th rank fall off.
+    #[inline]
+    pub const fn shift_north(&self) -> Self {
+        Self(self.0 << 8)
+    }
+
+    /// Move every square one rank down, squares on the 1st rank fall off.
+    #[inline]
+    pub const fn shift_south(&self) -> Self {
+        Self(self.0 >> 8)
+    }
+
+    /// Move every square one file towards the h-file, without wrapping around.
+    #[inline]
+    pub const fn shift_east(&self) -> Self {
+        Self((self.0 & !constants::H_FILE.0) << 1)
+    }
+
+    /// Move every square one file towards the a-file, without wrapping around.
+    #[inline]
+    pub const fn shift_west(&self) -> Self {
+        Self((self.0 & !constants::A_FILE.0) >> 1)
+    }
+
+    /// Move every square one step diagonally towards h8, without wrapping around.
+    #[inline]
+    pub const fn shift_north_east(&self) -> Self {
+        Self((self.0 & !constants::H_FILE.0) << 9)
+    }
+
+    /// Move every square one step diagonally towards a8, without wrapping around.
+    #[inline]
+    pub const fn shift_north_west(&self) -> Self {
+        Self((self.0 & !constants::A_FILE.0) << 7)
+    }
+
+    /// Move every square one step diagonally towards h1, without wrapping around.
+    #[inline]
+    pub const fn shift_south_east(&self) -> Self {
+        Self((self.0 & !constants::H_FILE.0) >> 7)
+    }
+
+    /// Move every square one step diagonally towards a1, without wrapping around.
+    #[inline]
+    pub const fn shift_south_west(&self) -> Self {
+        Self((self.0 & !constants::A_FILE.0) >> 9)
+    }
+
+    /// Every square on or above a set square, on its file.
+    #[inline]
+    pub const fn fill_north(&self) -> Self {
+        let mut bb = self.0;
+        bb |= bb << 8;
+        bb |= bb << 16;
+        bb |= bb << 32;
+        Self(bb)
+    }
+
+    /// Every square on or below a set square, on its file.
+    #[inline]
+    pub const fn fill_south(&self) -> Self {
+        let mut bb = self.0;
+        bb |= bb >> 8;
+        bb |= bb >> 16;
+        bb |= bb >> 32;
+        Self(bb)
+    }
+
+    /// Every file with a set square.
+    #[inline]
+    pub const fn fill_files(&self) -> Self {
+        Self(self.fill_north().0 | self.fill_south().0)
+    }
+
+    /// Number of set squares.
+    #[inline]
+    pub const fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The set square with the lowest index, if any.
+    #[inline]
+    pub const fn lsb(&self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.to_square_unchecked())
+        }
+    }
+
+    /// Clear the set square with the lowest index and return it.
+    #[inline]
+    pub const fn pop_lsb(&mut self) -> Option<Square> {
+        let lsb = self.lsb();
+        self.0 &= self.0.wrapping_sub(1);
+        lsb
+    }
+
     /// An iterator over all `set` squares on the board.
     #[inline]
     pub const fn set_iter(&self) -> SetIter {
         SetIter { inner: *self }
     }
+
+    /// Same as [`BitBoard::set_iter`].
+    #[inline]
+    pub const fn iter(&self) -> SetIter {
+        self.set_iter()
+    }
+}
+
+impl IntoIterator for BitBoard {
+    type Item = Square;
+    type IntoIter = SetIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.set_iter()
+    }
+}
+
+impl IntoIterator for &BitBoard {
+    type Item = Square;
+    type IntoIter = SetIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.set_iter()
+    }
 }
 
 impl PartialEq<u64> for BitBoard {
@@ -111,17 +366,9 @@ pub struct SetIter {
 impl Iterator for SetIter {
     type Item = Square;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let trailing = self.inner.0.trailing_zeros() as u8;
-
-        if trailing < 64 {
-            let sq = Square::from_index_unchecked(trailing);
-            let sq_bb = BitBoard::from_square(sq);
-            self.inner &= !sq_bb;
-            return Some(sq);
-        }
-
-        None
+        self.inner.pop_lsb()
     }
 
     #[inline]
@@ -134,7 +381,7 @@ impl Iterator for SetIter {
 impl ExactSizeIterator for SetIter {
     #[inline]
     fn len(&self) -> usize {
-        self.inner.0.count_ones() as usize
+        self.inner.count() as usize
     }
 }
 
@@ -143,4 +390,211 @@ pub mod constants {
 
     pub const A_FILE: BitBoard = BitBoard(0x01_01_01_01_01_01_01_01);
     pub const H_FILE: BitBoard = BitBoard(0x80_80_80_80_80_80_80_80);
+
+    /// Squares of a1's color.
+    pub const DARK_SQUARES: BitBoard = BitBoard(0xAA_55_AA_55_AA_55_AA_55);
+    pub const LIGHT_SQUARES: BitBoard = BitBoard(0x55_AA_55_AA_55_AA_55_AA);
+
+    /// The a1-h8 diagonal.
+    pub const DIAGONAL: BitBoard = BitBoard(0x80_40_20_10_08_04_02_01);
+    /// The h1-a8 diagonal.
+    pub const ANTI_DIAGONAL: BitBoard = BitBoard(0x01_02_04_08_10_20_40_80);
+
+    /// The a1-h8 direction diagonal passing through a square.
+    #[inline]
+    pub const fn diagonal_mask(square: Square) -> BitBoard {
+        let shift = square.file() as i8 - square.rank() as i8;
+
+        if shift >= 0 {
+            BitBoard(DIAGONAL.0 >> (shift * 8))
+        } else {
+            BitBoard(DIAGONAL.0 << (-shift * 8))
+        }
+    }
+
+    /// The h1-a8 direction diagonal passing through a square.
+    #[inline]
+    pub const fn anti_diagonal_mask(square: Square) -> BitBoard {
+        let shift = square.file() as i8 + square.rank() as i8 - 7;
+
+        if shift >= 0 {
+            BitBoard(ANTI_DIAGONAL.0 << (shift * 8))
+        } else {
+            BitBoard(ANTI_DIAGONAL.0 >> (-shift * 8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_algebra() {
+        let a = BitBoard(0b0110);
+        let b = BitBoard(0b1110);
+        let c = BitBoard(0b1_0000);
+
+        assert!(a.is_subset_of(b));
+        assert!(!b.is_subset_of(a));
+        assert!(BitBoard::ZERO.is_subset_of(a));
+        assert!(a.intersects(b));
+        assert!(!a.intersects(c));
+        assert!(a.is_disjoint(c));
+        assert!(BitBoard::ZERO.is_empty());
+    }
+
+    #[test]
+    fn line_occupancy() {
+        use constants::*;
+
+        let e4 = Square::at(3, 4).unwrap();
+        // e-file: e1, e4, e8 / 4th rank: a4, e4 / diagonal: b1, e4 / anti-diagonal: h1, e4, a8
+        let bb = BitBoard(0x11_00_00_00_11_00_00_12 | 0x01_00_00_00_00_00_00_80);
+
+        assert_eq!(bb.rank_occupancy(3), 0b0001_0001);
+        assert_eq!(bb.file_occupancy(4), 0b1000_1001);
+        assert_eq!(bb.diagonal_occupancy(e4), 0b0001_0010);
+        assert_eq!(bb.anti_diagonal_occupancy(e4), 0b1001_0001);
+
+        assert_eq!(diagonal_mask(e4), BitBoard(0x00_80_40_20_10_08_04_02));
+        assert_eq!(anti_diagonal_mask(e4), BitBoard(0x01_02_04_08_10_20_40_80));
+    }
+
+    #[test]
+    fn line_occupancy_round_trip() {
+        use constants::*;
+
+        let boards = [0, u64::MAX, 0x0123_4567_89AB_CDEF, 0xF0F0_0F0F_AA55_55AA];
+
+        for bb in boards.map(BitBoard) {
+            for index in 0..64 {
+                let square = Square::from_index_unchecked(index);
+                let (rank, file) = (square.rank(), square.file());
+                let rank_mask = BitBoard(0xFF << (rank * 8));
+                let file_mask = BitBoard(A_FILE.0 << file);
+
+                assert_eq!(
+                    BitBoard::from_rank_occupancy(rank, bb.rank_occupancy(rank)),
+                    bb & rank_mask
+                );
+                assert_eq!(
+                    BitBoard::from_file_occupancy(file, bb.file_occupancy(file)),
+                    bb & file_mask
+                );
+                assert_eq!(
+                    BitBoard::from_diagonal_occupancy(square, bb.diagonal_occupancy(square)),
+                    bb & diagonal_mask(square)
+                );
+                assert_eq!(
+                    BitBoard::from_anti_diagonal_occupancy(
+                        square,
+                        bb.anti_diagonal_occupancy(square)
+                    ),
+                    bb & anti_diagonal_mask(square)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mirroring() {
+        for index in 0..64 {
+            let square = Square::from_index_unchecked(index);
+            let (rank, file) = (square.rank(), square.file());
+            let bb = BitBoard::from_square(square);
+            let at = |rank, file| BitBoard::from_square(Square::at(rank, file).unwrap());
+
+            assert_eq!(bb.flip_vertical(), at(7 - rank, file));
+            assert_eq!(bb.flip_horizontal(), at(rank, 7 - file));
+            assert_eq!(bb.flip_diag_a1h8(), at(file, rank));
+            assert_eq!(bb.rotate_180(), at(7 - rank, 7 - file));
+        }
+
+        let bb = BitBoard(0x0123_4567_89AB_CDEF);
+        assert_eq!(bb.flip_vertical().flip_vertical(), bb);
+        assert_eq!(bb.flip_horizontal().flip_horizontal(), bb);
+        assert_eq!(bb.flip_diag_a1h8().flip_diag_a1h8(), bb);
+        assert_eq!(bb.flip_vertical().flip_horizontal(), bb.rotate_180());
+    }
+
+    #[test]
+    fn counts() {
+        assert!(!BitBoard::ZERO.exactly_one());
+        assert!(!BitBoard::ZERO.more_than_one());
+        assert!(BitBoard(1 << 63).exactly_one());
+        assert!(!BitBoard(1 << 63).more_than_one());
+        assert!(BitBoard(0b101).more_than_one());
+        assert!(!BitBoard(0b101).exactly_one());
+        assert!(BitBoard(u64::MAX).more_than_one());
+
+        assert_eq!(BitBoard::ZERO.count(), 0);
+        assert_eq!(BitBoard(0b1011).count(), 3);
+        assert_eq!(BitBoard(u64::MAX).count(), 64);
+    }
+
+    #[test]
+    fn fills() {
+        let at = |rank, file| BitBoard::from_square(Square::at(rank, file).unwrap());
+        let d4 = at(3, 3);
+        let d_file = BitBoard::from_file_occupancy(3, 0xff);
+
+        assert_eq!(d4.fill_north(), d_file & !BitBoard(0xff_ffff));
+        assert_eq!(d4.fill_south(), d_file & BitBoard(0xffff_ffff));
+        assert_eq!(d4.fill_files(), d_file);
+        assert_eq!((d4 | at(6, 0)).fill_files().count(), 16);
+        assert_eq!(BitBoard::ZERO.fill_files(), BitBoard::ZERO);
+    }
+
+    #[test]
+    fn shifts() {
+        let at = |rank, file| BitBoard::from_square(Square::at(rank, file).unwrap());
+        let d4 = at(3, 3);
+
+        assert_eq!(d4.shift_north(), at(4, 3));
+        assert_eq!(d4.shift_south(), at(2, 3));
+        assert_eq!(d4.shift_east(), at(3, 4));
+        assert_eq!(d4.shift_west(), at(3, 2));
+        assert_eq!(d4.shift_north_east(), at(4, 4));
+        assert_eq!(d4.shift_north_west(), at(4, 2));
+        assert_eq!(d4.shift_south_east(), at(2, 4));
+        assert_eq!(d4.shift_south_west(), at(2, 2));
+
+        // nothing wraps around to the other side of the board
+        let a_file = constants::A_FILE;
+        let h_file = constants::H_FILE;
+        assert_eq!(h_file.shift_east(), BitBoard::ZERO);
+        assert_eq!(a_file.shift_west(), BitBoard::ZERO);
+        assert_eq!(h_file.shift_north_east(), BitBoard::ZERO);
+        assert_eq!(h_file.shift_south_east(), BitBoard::ZERO);
+        assert_eq!(a_file.shift_north_west(), BitBoard::ZERO);
+        assert_eq!(a_file.shift_south_west(), BitBoard::ZERO);
+        assert_eq!(at(7, 0).shift_north(), BitBoard::ZERO);
+        assert_eq!(at(0, 7).shift_south(), BitBoard::ZERO);
+        assert_eq!(a_file.shift_east(), BitBoard(a_file.0 << 1));
+    }
+
+    #[test]
+    fn bit_scan() {
+        let a1 = Square::from_index_unchecked(0);
+        let c1 = Square::from_index_unchecked(2);
+        let h8 = Square::from_index_unchecked(63);
+
+        let mut bb = BitBoard(0b101 | 1 << 63);
+        assert_eq!(bb.lsb(), Some(a1));
+        assert_eq!(bb.pop_lsb(), Some(a1));
+        assert_eq!(bb.pop_lsb(), Some(c1));
+        assert_eq!(bb.lsb(), Some(h8));
+        assert_eq!(bb.pop_lsb(), Some(h8));
+        assert_eq!(bb, BitBoard::ZERO);
+        assert_eq!(bb.lsb(), None);
+        assert_eq!(bb.pop_lsb(), None);
+
+        let bb = BitBoard(0b101 | 1 << 63);
+        let squares: Vec<_> = bb.into_iter().collect();
+        assert_eq!(squares, [a1, c1, h8]);
+        assert_eq!(bb.iter().len(), 3);
+        assert_eq!((&bb).into_iter().last(), Some(h8));
+        assert_eq!(BitBoard::ZERO.iter().next(), None);
+    }
 }
@@ -0,0 +1,246 @@
+//! SVG diagrams of a board, for annotations, blog posts and bug reports.
+//!
+//! Pieces are drawn as Unicode chess glyphs, so the picture depends a little on the fonts of
+//! whatever displays it. There is no PNG output, any SVG converter can make one.
+
+use std::fmt::Write;
+
+use crate::{Board, Color, Move, PieceKind, Square};
+
+/// How to draw a diagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// Side length of a square in pixels.
+    pub square_size: u32,
+    pub light: String,
+    pub dark: String,
+    /// Drawn over the origin and destination of [`SvgOptions::last_move`].
+    pub highlight: String,
+    pub arrow: String,
+    /// Draw from black's side, a1 in the top right corner.
+    pub flipped: bool,
+    /// File letters and rank numbers along the edges.
+    pub coordinates: bool,
+    pub last_move: Option<Move>,
+    /// Arrows from one square to another, e.g. for a plan or a threat.
+    pub arrows: Vec<(Square, Square)>,
+    /// Squares drawn in a color of their own, over the highlight, e.g. for a heatmap.
+    pub fills: Vec<(Square, String)>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 45,
+            light: "#f0d9b5".to_owned(),
+            dark: "#b58863".to_owned(),
+            highlight: "#cdd26a".to_owned(),
+            arrow: "#15781b".to_owned(),
+            flipped: false,
+            coordinates: true,
+            last_move: None,
+            arrows: vec![],
+            fills: vec![],
+        }
+    }
+}
+
+/// Render a board as a standalone SVG document.
+pub fn svg(board: &Board, options: &SvgOptions) -> String {
+    let size = options.square_size;
+    let side = size * 8;
+    let mut out = String::new();
+
+    // writing to a string can't fail
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{side}\" height=\"{side}\" \
+viewBox=\"0 0 {side} {side}\">"
+    );
+    let _ = writeln!(
+        out,
+        "<defs><marker id=\"arrowhead\" markerWidth=\"4\" markerHeight=\"4\" refX=\"2\" refY=\"2\" \
+orient=\"auto\"><path d=\"M0,0 L4,2 L0,4 z\" fill=\"{}\"/></marker></defs>",
+        options.arrow
+    );
+
+    let highlighted = options
+        .last_move
+        .map_or(vec![], |p_move| vec![p_move.from, p_move.to]);
+
+    for index in 0..64 {
+        let square = Square::from_index_unchecked(index);
+        let (x, y) = corner(square, options);
+        let light = (square.rank() + square.file()) % 2 == 1;
+        let filled = options.fills.iter().find(|(filled, _)| *filled == square);
+        let fill = if let Some((_, fill)) = filled {
+            fill
+        } else if highlighted.contains(&square) {
+            &options.highlight
+        } else if light {
+            &options.light
+        } else {
+            &options.dark
+        };
+
+        let _ = writeln!(
+            out,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" fill=\"{fill}\"/>"
+        );
+    }
+
+    if options.coordinates {
+        let font = size / 5;
+        let (left_file, bottom_rank) = if options.flipped { (7, 7) } else { (0, 0) };
+
+        for i in 0..8 {
+            let file = Square::at(bottom_rank, i).unwrap();
+            let (x, y) = corner(file, options);
+            let _ = writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{font}\" font-family=\"sans-serif\" \
+fill=\"{}\">{}</text>",
+                x + size - font,
+                y + size - font / 3,
+                contrast(file, options),
+                (b'a' + i) as char
+            );
+
+            let rank = Square::at(i, left_file).unwrap();
+            let (x, y) = corner(rank, options);
+            let _ = writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{font}\" font-family=\"sans-serif\" \
+fill=\"{}\">{}</text>",
+                x + font / 3,
+                y + font,
+                contrast(rank, options),
+                i + 1
+            );
+        }
+    }
+
+    for square in board.get_full_bb() {
+        let piece = board.get(square).unwrap();
+        let (x, y) = corner(square, options);
+        let fill = match piece.color {
+            Color::White => "#ffffff",
+            Color::Black => "#000000",
+        };
+
+        let _ = writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"serif\" text-anchor=\"middle\" \
+dominant-baseline=\"central\" fill=\"{fill}\" stroke=\"#000000\">{}</text>",
+            x + size / 2,
+            y + size / 2,
+            size * 4 / 5,
+            glyph(piece.kind)
+        );
+    }
+
+    for &(from, to) in &options.arrows {
+        let (x1, y1) = corner(from, options);
+        let (x2, y2) = corner(to, options);
+        let half = size / 2;
+
+        let _ = writeln!(
+            out,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" \
+stroke-opacity=\"0.8\" marker-end=\"url(#arrowhead)\"/>",
+            x1 + half,
+            y1 + half,
+            x2 + half,
+            y2 + half,
+            options.arrow,
+            size / 6
+        );
+    }
+
+    out += "</svg>\n";
+    out
+}
+
+/// Top left corner of a square in the picture.
+fn corner(square: Square, options: &SvgOptions) -> (u32, u32) {
+    let (column, row) = match options.flipped {
+        false => (square.file(), 7 - square.rank()),
+        true => (7 - square.file(), square.rank()),
+    };
+
+    (
+        column as u32 * options.square_size,
+        row as u32 * options.square_size,
+    )
+}
+
+/// Coordinates are written in the other square color, so they stay readable.
+fn contrast(square: Square, options: &SvgOptions) -> &str {
+    match (square.rank() + square.file()) % 2 {
+        1 => &options.dark,
+        _ => &options.light,
+    }
+}
+
+/// The filled glyphs, colored by the fill so both sides look alike.
+const fn glyph(kind: PieceKind) -> char {
+    match kind {
+        PieceKind::Pawn => '♟',
+        PieceKind::Knight => '♞',
+        PieceKind::Bishop => '♝',
+        PieceKind::Rook => '♜',
+        PieceKind::Queen => '♛',
+        PieceKind::King => '♚',
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let board = Board::starting_position();
+        let options = SvgOptions {
+            last_move: Some("e2e4".parse().unwrap()),
+            arrows: vec![("g1".parse().unwrap(), "f3".parse().unwrap())],
+            ..SvgOptions::default()
+        };
+
+        let svg = svg(&board, &options);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect ").count(), 64);
+        assert_eq!(svg.matches("fill=\"#cdd26a\"").count(), 2);
+        assert_eq!(svg.matches('♟').count(), 16);
+        assert_eq!(svg.matches("<line ").count(), 1);
+        // g1 to f3 is up and to the left
+        assert!(svg.contains("x1=\"292\" y1=\"337\" x2=\"247\" y2=\"247\""));
+
+        let filled = SvgOptions {
+            fills: vec![("e2".parse().unwrap(), "#123456".to_owned())],
+            ..options
+        };
+        let svg = super::svg(&board, &filled);
+        assert_eq!(svg.matches("fill=\"#cdd26a\"").count(), 1);
+        assert!(
+            svg.contains("<rect x=\"180\" y=\"270\" width=\"45\" height=\"45\" fill=\"#123456\"/>")
+        );
+    }
+
+    #[test]
+    fn flipped() {
+        let a1 = Square::at(0, 0).unwrap();
+        let h8 = Square::at(7, 7).unwrap();
+        let options = SvgOptions::default();
+        let flipped = SvgOptions {
+            flipped: true,
+            ..SvgOptions::default()
+        };
+
+        assert_eq!(corner(a1, &options), (0, 315));
+        assert_eq!(corner(a1, &flipped), (315, 0));
+        assert_eq!(corner(h8, &flipped), (0, 315));
+    }
+}
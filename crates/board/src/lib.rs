@@ -3,18 +3,30 @@
 //! Only defines structures that represent the board, does not check the legality of positions
 //! or handle move generation.
 
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
 use std::fmt::Display;
 use std::str::FromStr;
 
+use psqt::Score;
+
 pub use strum::{EnumCount, IntoEnumIterator};
 
+pub mod attacks;
+pub mod batch;
 pub mod bitboard;
+#[cfg(feature = "diagram")]
+pub mod diagram;
 pub mod moves;
+pub mod overlay;
 pub mod piece;
 pub mod position;
+pub mod psqt;
+pub mod zobrist;
 
 pub use bitboard::*;
 pub use moves::*;
+pub use overlay::*;
 pub use piece::*;
 pub use position::*;
 
@@ -65,6 +77,20 @@ impl Square {
     pub const fn from_index_unchecked(index: u8) -> Self {
         Self(index)
     }
+
+    /// Squares strictly between `a` and `b` on their common rank, file or diagonal, empty if they
+    /// don't share one.
+    #[inline]
+    pub fn between(a: Square, b: Square) -> BitBoard {
+        attacks::BETWEEN[a.0 as usize][b.0 as usize]
+    }
+
+    /// Squares from `a` through `b` to the edge of the board, without `a`. Empty if they don't
+    /// share a rank, file or diagonal.
+    #[inline]
+    pub fn ray(a: Square, b: Square) -> BitBoard {
+        attacks::RAY[a.0 as usize][b.0 as usize]
+    }
 }
 
 impl TryFrom<(u8, u8)> for Square {
@@ -112,6 +138,14 @@ pub struct Board {
     color_bb: [BitBoard; 2], // Color::COUNT
     /// Piece masks.
     piece_bb: [BitBoard; 6], // PieceKind::COUNT
+    /// Cached union of both color masks.
+    occupied: BitBoard,
+    /// Zobrist key of all pieces on the board, updated incrementally.
+    zobrist: u64,
+    /// Material of all pieces on the board, updated incrementally.
+    material: Score,
+    /// Piece-square table score of all pieces on the board, updated incrementally.
+    psqt: Score,
 }
 
 impl Board {
@@ -145,28 +179,36 @@ impl Board {
         self.piece_bb[piece as u8 as usize]
     }
 
-    /// Get an exclusive reference to the bitboard associated with a certain piece kind.
-    #[inline]
-    pub fn get_piece_kind_bb_mut(&mut self, piece: PieceKind) -> &mut BitBoard {
-        &mut self.piece_bb[piece as u8 as usize]
-    }
-
     /// Get the bitboard associated with a certain color.
     #[inline]
     pub const fn get_color_bb(&self, color: Color) -> BitBoard {
         self.color_bb[color as u8 as usize]
     }
 
-    /// Get an exclusive reference to the bitboard associated with a certain color.
+    /// Get the full board.
     #[inline]
-    pub fn get_color_bb_mut(&mut self, color: Color) -> &mut BitBoard {
-        &mut self.color_bb[color as u8 as usize]
+    pub const fn get_full_bb(&self) -> BitBoard {
+        self.occupied
     }
 
-    /// Get the full board.
+    /// All `color` pieces attacking `square`, sliders only being blocked by `occupancy`.
+    ///
+    /// Passing an occupancy other than the board's lets sliders x-ray through pieces that are
+    /// about to move, e.g. for exchange evaluation.
     #[inline]
-    pub fn get_full_bb(&self) -> BitBoard {
-        self.color_bb[0] | self.color_bb[1]
+    pub fn attackers_to(&self, square: Square, occupancy: BitBoard, color: Color) -> BitBoard {
+        let kind_bb = |kind| self.get_piece_kind_bb(kind);
+        let diagonal = kind_bb(PieceKind::Bishop) | kind_bb(PieceKind::Queen);
+        let straight = kind_bb(PieceKind::Rook) | kind_bb(PieceKind::Queen);
+
+        // look from the square outwards as each kind, meeting a piece of that kind means it attacks
+        let attackers = (attacks::pawn(square, !color) & kind_bb(PieceKind::Pawn))
+            | (attacks::knight(square) & kind_bb(PieceKind::Knight))
+            | (attacks::king(square) & kind_bb(PieceKind::King))
+            | (attacks::bishop(square, occupancy) & diagonal)
+            | (attacks::rook(square, occupancy) & straight);
+
+        attackers & self.get_color_bb(color)
     }
 
     /// Set a piece on the board.
@@ -174,18 +216,153 @@ impl Board {
     pub fn set(&mut self, square: Square, piece: Option<Piece>) {
         match piece {
             Some(piece) => {
-                self.get_color_bb_mut(piece.color).set(square, true);
-                self.get_piece_kind_bb_mut(piece.kind).set(square, true);
+                self.color_bb[piece.color as u8 as usize].set(square, true);
+                self.piece_bb[piece.kind as u8 as usize].set(square, true);
+                self.occupied.set(square, true);
+                self.zobrist ^= zobrist::piece_square(piece, square);
+                self.material += psqt::material(piece);
+                self.psqt += psqt::piece_square(piece, square);
             }
             None => {
-                for bb in &mut self.color_bb {
-                    bb.set(square, false);
+                if let Some(piece) = self.get(square) {
+                    self.zobrist ^= zobrist::piece_square(piece, square);
+                    self.material -= psqt::material(piece);
+                    self.psqt -= psqt::piece_square(piece, square);
                 }
-                for bb in &mut self.piece_bb {
-                    bb.set(square, false);
+
+                batch::clear_square(&mut self.color_bb, square);
+                batch::clear_square(&mut self.piece_bb, square);
+                self.occupied.set(square, false);
+            }
+        }
+    }
+
+    /// Move the piece on `from` to `to`, which has to be empty.
+    #[inline]
+    pub fn move_piece(&mut self, from: Square, to: Square) {
+        if let Some(piece) = self.get(from) {
+            let from_to = BitBoard::from_square(from) | BitBoard::from_square(to);
+
+            self.color_bb[piece.color as u8 as usize] ^= from_to;
+            self.piece_bb[piece.kind as u8 as usize] ^= from_to;
+            self.occupied ^= from_to;
+            self.zobrist ^= zobrist::piece_square(piece, from) ^ zobrist::piece_square(piece, to);
+            self.psqt += psqt::piece_square(piece, to) - psqt::piece_square(piece, from);
+        }
+    }
+
+    /// Zobrist key of the pieces on the board, see [`Position::zobrist_key`] for the full key.
+    #[inline]
+    pub const fn zobrist_key(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Compute the Zobrist key of the pieces on the board from scratch.
+    pub const fn compute_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+
+        let mut color = 0;
+        while color < Color::COUNT {
+            let mut kind = 0;
+            while kind < PieceKind::COUNT {
+                let mut bb = self.color_bb[color].0 & self.piece_bb[kind].0;
+
+                while bb != 0 {
+                    let square = bb.trailing_zeros() as usize;
+                    key ^= zobrist::PIECE_SQUARE[color * PieceKind::COUNT + kind][square];
+                    bb &= bb - 1;
                 }
+
+                kind += 1;
             }
+            color += 1;
         }
+
+        key
+    }
+
+    /// Material of the pieces on the board, from white's point of view.
+    #[inline]
+    pub const fn material(&self) -> Score {
+        self.material
+    }
+
+    /// Piece-square table score of the pieces on the board, from white's point of view.
+    #[inline]
+    pub const fn psqt(&self) -> Score {
+        self.psqt
+    }
+
+    /// Compute [`Board::material`] and [`Board::psqt`] from scratch.
+    pub fn compute_scores(&self) -> (Score, Score) {
+        self.get_full_bb()
+            .into_iter()
+            .filter_map(|square| self.get(square).map(|piece| (piece, square)))
+            .fold(
+                (Score::ZERO, Score::ZERO),
+                |(material, psqt), (piece, square)| {
+                    (
+                        material + psqt::material(piece),
+                        psqt + psqt::piece_square(piece, square),
+                    )
+                },
+            )
+    }
+
+    /// Check that the masks agree with each other.
+    ///
+    /// Colors and piece kinds must not overlap, every piece must have both a color and a kind,
+    /// and the cached occupancy, Zobrist key and scores must match.
+    pub fn is_consistent(&self) -> bool {
+        let colors = self.color_bb[0] | self.color_bb[1];
+        let kinds = self
+            .piece_bb
+            .iter()
+            .fold(BitBoard::ZERO, |all, bb| all | *bb);
+        let kind_count: u32 = self.piece_bb.iter().map(|bb| bb.count()).sum();
+
+        self.color_bb[0] & self.color_bb[1] == 0
+            && kind_count == kinds.count()
+            && colors == kinds
+            && self.occupied == colors
+            && self.zobrist == self.compute_zobrist_key()
+            && (self.material, self.psqt) == self.compute_scores()
+    }
+
+    /// Read the piece placement field of a FEN string, e.g. `rnbqkbnr/pppppppp/8/...`.
+    ///
+    /// Exactly eight ranks of eight squares each, from the 8th rank down.
+    pub fn from_fen_placement(placement: &str) -> Option<Self> {
+        let mut board = Self::default();
+        let mut ranks = 0;
+
+        for (rank, row) in placement.split('/').enumerate() {
+            let rank = 7u8.checked_sub(rank as u8)?;
+            let mut file = 0;
+            ranks += 1;
+
+            for c in row.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as u8;
+                    continue;
+                }
+
+                board.set(Square::at(rank, file)?, Some(Piece::from_char(c)?));
+                file += 1;
+            }
+
+            if file != 8 {
+                return None;
+            }
+        }
+
+        (ranks == 8).then_some(board)
+    }
+
+    /// The piece placement field of a FEN string for this board.
+    #[inline]
+    pub const fn fen_placement(&self) -> FenPlacement<'_> {
+        FenPlacement(self)
     }
 
     /// Generate the starting board position.
@@ -194,6 +371,11 @@ impl Board {
         let mut this = Self {
             color_bb: [BitBoard::ZERO; Color::COUNT],
             piece_bb: [BitBoard::ZERO; PieceKind::COUNT],
+            occupied: BitBoard(0xFF_FF_00_00_00_00_FF_FF),
+            zobrist: 0,
+            // both sides mirror each other, so the scores cancel out
+            material: Score::ZERO,
+            psqt: Score::ZERO,
         };
 
         this.color_bb[Color::White as u8 as usize] = BitBoard(0x00_00_00_00_00_00_FF_FF);
@@ -206,6 +388,7 @@ impl Board {
         this.piece_bb[PieceKind::Queen  as u8 as usize] = BitBoard(0x08_00_00_00_00_00_00_08);
         this.piece_bb[PieceKind::King   as u8 as usize] = BitBoard(0x10_00_00_00_00_00_00_10);
 
+        this.zobrist = this.compute_zobrist_key();
         this
     }
 }
@@ -235,6 +418,147 @@ impl Display for Board {
     }
 }
 
+/// Piece placement part of a FEN string, see [`Board::fen_placement`].
+#[derive(Debug, Clone, Copy)]
+pub struct FenPlacement<'a>(&'a Board);
+
+impl<'a> Display for FenPlacement<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+
+            for file in 0..8 {
+                match self.0.get(Square::at(rank, file).unwrap()) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            write!(f, "{empty}")?;
+                            empty = 0;
+                        }
+                        write!(f, "{}", piece.as_char())?;
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                write!(f, "{empty}")?;
+            }
+            if rank > 0 {
+                write!(f, "/")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use super::*;
+
+    #[test]
+    fn occupancy_cache() {
+        let mut board = Board::starting_position();
+        assert!(board.is_consistent());
+
+        let e2 = Square::from_str("e2").unwrap();
+        let e4 = Square::from_str("e4").unwrap();
+        board.move_piece(e2, e4);
+        assert!(board.is_consistent());
+        assert!(board.get_full_bb().get(e4));
+        assert!(!board.get_full_bb().get(e2));
+
+        let d8 = Square::from_str("d8").unwrap();
+        board.set(d8, None);
+        assert!(board.is_consistent());
+        assert!(!board.get_full_bb().get(d8));
+
+        board.set(
+            d8,
+            Some(Piece {
+                color: Color::White,
+                kind: PieceKind::Rook,
+            }),
+        );
+        assert!(board.is_consistent());
+        assert!(board.get_full_bb().get(d8));
+    }
+
+    #[test]
+    fn attackers_to() {
+        // e5 attacked by the knight on f3 and the bishop on b2, the rook on e1 stands behind the
+        // pawn on e2, defended by the d6 pawn and the queen on e7
+        let board = Board::from_fen_placement("4k3/4q3/3p4/4p3/8/5N2/1B2P3/4RK2").unwrap();
+        let e5 = Square::from_str("e5").unwrap();
+        let e2 = Square::from_str("e2").unwrap();
+        let square = |s| BitBoard::from_square(Square::from_str(s).unwrap());
+
+        assert_eq!(
+            board.attackers_to(e5, board.get_full_bb(), Color::White),
+            square("f3") | square("b2")
+        );
+        assert_eq!(
+            board.attackers_to(e5, board.get_full_bb(), Color::Black),
+            square("d6") | square("e7")
+        );
+
+        // with the e2 pawn gone the rook sees through
+        let occupancy = board.get_full_bb() & !BitBoard::from_square(e2);
+        assert_eq!(
+            board.attackers_to(e5, occupancy, Color::White),
+            square("f3") | square("b2") | square("e1")
+        );
+
+        // the king defends the pawn
+        assert_eq!(
+            board.attackers_to(e2, board.get_full_bb(), Color::White),
+            square("e1") | square("f1")
+        );
+    }
+
+    #[test]
+    fn fen_placement() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+        let board = Board::from_fen_placement(start).unwrap();
+        assert_eq!(board, Board::starting_position());
+        assert_eq!(board.fen_placement().to_string(), start);
+
+        let sparse = "8/8/3k4/8/8/4K3/8/8";
+        assert_eq!(
+            Board::from_fen_placement(sparse)
+                .unwrap()
+                .fen_placement()
+                .to_string(),
+            sparse
+        );
+
+        for bad in [
+            "",
+            "8/8/8/8/8/8/8",
+            "8/8/8/8/8/8/8/8/8",
+            "9/8/8/8/8/8/8/8",
+            "8/8/8/8/8/8/8/7",
+            "ppppppppp/8/8/8/8/8/8/8",
+            "x7/8/8/8/8/8/8/8",
+            "0p7/8/8/8/8/8/8/8",
+            "8/8/8/8/8/8/8/8 w - - 0 1",
+        ] {
+            assert_eq!(Board::from_fen_placement(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn inconsistent() {
+        let mut board = Board::starting_position();
+        board.occupied = BitBoard::ZERO;
+        assert!(!board.is_consistent());
+
+        let mut board = Board::starting_position();
+        board.piece_bb[PieceKind::Queen as u8 as usize] |= BitBoard(1);
+        assert!(!board.is_consistent());
+    }
+}
+
 #[cfg(test)]
 mod square_tests {
     use super::*;
@@ -248,6 +572,42 @@ mod square_tests {
         assert_eq!(&Square::at(8, 8), &None);
     }
 
+    #[test]
+    fn lines() {
+        let sq = |s: &str| s.parse::<Square>().unwrap();
+        let bb = |squares: &[&str]| {
+            squares
+                .iter()
+                .fold(BitBoard::ZERO, |bb, s| bb | BitBoard::from_square(sq(s)))
+        };
+
+        assert_eq!(Square::between(sq("a1"), sq("a4")), bb(&["a2", "a3"]));
+        assert_eq!(Square::between(sq("h1"), sq("e4")), bb(&["g2", "f3"]));
+        assert_eq!(Square::between(sq("e4"), sq("b4")), bb(&["c4", "d4"]));
+        assert_eq!(Square::between(sq("e4"), sq("e5")), BitBoard::ZERO);
+        assert_eq!(Square::between(sq("a1"), sq("b3")), BitBoard::ZERO);
+        assert_eq!(Square::between(sq("a1"), sq("a1")), BitBoard::ZERO);
+
+        assert_eq!(
+            Square::ray(sq("c3"), sq("d4")),
+            bb(&["d4", "e5", "f6", "g7", "h8"])
+        );
+        assert_eq!(Square::ray(sq("d4"), sq("c3")), bb(&["c3", "b2", "a1"]));
+        assert_eq!(Square::ray(sq("e1"), sq("e2")).count(), 7);
+        assert_eq!(Square::ray(sq("a1"), sq("b3")), BitBoard::ZERO);
+
+        for a in (0..64).map(Square) {
+            for b in (0..64).map(Square) {
+                assert_eq!(Square::between(a, b), Square::between(b, a));
+
+                if !Square::ray(a, b).is_empty() {
+                    assert!(Square::between(a, b).is_subset_of(Square::ray(a, b)));
+                    assert!(Square::ray(a, b).get(b));
+                }
+            }
+        }
+    }
+
     #[test]
     fn square_from_str() {
         assert_eq!(Square::from_str("a2"), Square::at(1, 0).ok_or(()));
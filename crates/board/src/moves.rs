@@ -1,13 +1,16 @@
 //! Piece move information.
 
 use std::fmt::Display;
+use std::str::FromStr;
 
-use crate::{PieceKind, Square};
+use crate::{attacks, BitBoard, Piece, PieceKind, Position, Square};
 
 /// Minimal information required to represent a move in [LAN].
 ///
+/// Formats and parses in the UCI flavour, e.g. `e2e4` or `e7e8q`.
+///
 /// [LAN]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
@@ -27,6 +30,103 @@ impl Display for Move {
     }
 }
 
+impl FromStr for Move {
+    type Err = ();
+
+    /// Parse a move in UCI notation, the null move `0000` is not accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err(());
+        }
+
+        let promotion = match s.as_bytes().get(4) {
+            None => None,
+            Some(b'n') => Some(PieceKind::Knight),
+            Some(b'b') => Some(PieceKind::Bishop),
+            Some(b'r') => Some(PieceKind::Rook),
+            Some(b'q') => Some(PieceKind::Queen),
+            Some(_) => return Err(()),
+        };
+
+        Ok(Self {
+            from: s[0..2].parse()?,
+            to: s[2..4].parse()?,
+            promotion,
+        })
+    }
+}
+
+impl Move {
+    /// Squares attacked after this move by the moving piece, and the new attacks of sliders it
+    /// uncovers.
+    ///
+    /// Castling counts both the king and the rook as moving. Legality isn't checked, an empty
+    /// from square attacks nothing.
+    pub fn resulting_attacks(&self, position: &Position) -> BitBoard {
+        let Some(piece) = position.board.get(self.from) else {
+            return BitBoard::ZERO;
+        };
+
+        let mut board = position.board.clone();
+        let castle = (piece.kind == PieceKind::King)
+            .then(|| position.castle_of(*self))
+            .flatten();
+
+        let moved = if let Some(castle) = castle {
+            let rook = Piece {
+                kind: PieceKind::Rook,
+                ..piece
+            };
+
+            board.set(castle.king_from, None);
+            board.set(castle.rook_from, None);
+            board.set(castle.king_to, Some(piece));
+            board.set(castle.rook_to, Some(rook));
+
+            BitBoard::from_square(castle.king_to) | BitBoard::from_square(castle.rook_to)
+        } else {
+            if piece.kind == PieceKind::Pawn && position.ep_target == Some(self.to) {
+                let captured = Square::at(self.from.rank(), self.to.file()).unwrap();
+                board.set(captured, None);
+            }
+
+            board.set(self.to, None);
+            board.move_piece(self.from, self.to);
+
+            if let Some(kind) = self.promotion {
+                board.set(self.to, None);
+                board.set(self.to, Some(Piece { kind, ..piece }));
+            }
+
+            BitBoard::from_square(self.to)
+        };
+
+        let before = position.board.get_full_bb();
+        let after = board.get_full_bb();
+        let mut result = BitBoard::ZERO;
+
+        for square in moved {
+            if let Some(piece) = board.get(square) {
+                result |= attacks::piece(piece, square, after);
+            }
+        }
+
+        let sliders = [PieceKind::Bishop, PieceKind::Rook, PieceKind::Queen]
+            .into_iter()
+            .fold(BitBoard::ZERO, |bb, kind| {
+                bb | board.get_piece_bb(Piece { kind, ..piece })
+            });
+
+        for square in sliders & !moved {
+            let slider = board.get(square).unwrap();
+            result |=
+                attacks::piece(slider, square, after) & !attacks::piece(slider, square, before);
+        }
+
+        result
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Capture {
     Regular(PieceKind),
@@ -50,7 +150,7 @@ impl MoveExt {
             piece_kind,
             from: p_move.from,
             to: p_move.to,
-            promotion: None,
+            promotion: p_move.promotion,
             capture: None,
         }
     }
@@ -86,3 +186,78 @@ impl Display for MoveExt {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uci_round_trip() {
+        for uci in ["e2e4", "g8f6", "e7e8q", "a2a1n"] {
+            let p_move = uci.parse::<Move>().unwrap();
+            assert_eq!(p_move.to_string(), uci);
+        }
+
+        assert_eq!(
+            "e7e8r".parse(),
+            Ok(Move {
+                from: Square::at(6, 4).unwrap(),
+                to: Square::at(7, 4).unwrap(),
+                promotion: Some(PieceKind::Rook),
+            })
+        );
+
+        for uci in ["", "0000", "e2e", "e2e9", "e7e8k", "e7e8qq", "e2é4"] {
+            assert_eq!(uci.parse::<Move>(), Err(()), "{uci}");
+        }
+    }
+
+    #[test]
+    fn resulting_attacks() {
+        let attacks = |position: &Position, uci: &str| {
+            let mut squares: Vec<_> = uci
+                .parse::<Move>()
+                .unwrap()
+                .resulting_attacks(position)
+                .into_iter()
+                .map(|square| square.to_string())
+                .collect();
+            squares.sort();
+            squares
+        };
+
+        let start = Position::starting();
+        // the h1 rook now sees f1
+        assert_eq!(
+            attacks(&start, "g1f3"),
+            ["d2", "d4", "e1", "e5", "f1", "g1", "g5", "h2", "h4"]
+        );
+        // the pawn opens the f1 bishop's and d1 queen's diagonals
+        assert_eq!(
+            attacks(&start, "e2e4"),
+            ["a6", "b5", "c4", "d3", "d5", "f3", "f5", "g4", "h5"]
+        );
+        assert!(attacks(&start, "e4e5").is_empty());
+
+        // both castling pieces, and the a1 rook now sees past e1
+        let castle = Position {
+            board: crate::Board::from_fen_placement("r3k2r/8/8/8/8/8/8/R3K2R").unwrap(),
+            ..Position::starting()
+        };
+        let castled = attacks(&castle, "e1g1");
+        for square in ["f1", "f8", "d1", "h2"] {
+            assert!(castled.iter().any(|s| s == square), "{square}");
+        }
+        assert!(!castled.iter().any(|s| s == "e2"));
+
+        // the captured pawn uncovers the rook, the pawn promotes to a knight
+        let mut ep = Position {
+            board: crate::Board::from_fen_placement("4k3/8/8/R2pP3/8/8/1p6/4K3").unwrap(),
+            ..Position::starting()
+        };
+        ep.ep_target = "d6".parse().ok();
+        assert!(attacks(&ep, "e5d6").contains(&"h5".to_owned()));
+        ep.active_color = crate::Color::Black;
+        assert_eq!(attacks(&ep, "b2b1n"), ["a3", "c3", "d2"]);
+    }
+}
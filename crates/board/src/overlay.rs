@@ -0,0 +1,132 @@
+//! Board rendering with highlighted square sets drawn on top.
+//!
+//! Useful for inspecting attack maps, pins, mobility and other bitboards in context.
+
+use std::fmt::Display;
+
+use crate::{BitBoard, Board, Square};
+
+/// Terminal color used to tint overlay squares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tint {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Tint {
+    /// ANSI escape sequence for this tint.
+    #[inline]
+    #[rustfmt::skip]
+    pub const fn ansi(&self) -> &'static str {
+        match self {
+            Tint::Red     => "\x1b[31m",
+            Tint::Green   => "\x1b[32m",
+            Tint::Yellow  => "\x1b[33m",
+            Tint::Blue    => "\x1b[34m",
+            Tint::Magenta => "\x1b[35m",
+            Tint::Cyan    => "\x1b[36m",
+        }
+    }
+}
+
+/// A set of squares to highlight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlay {
+    pub squares: BitBoard,
+    /// Drawn on empty squares and next to pieces.
+    pub marker: char,
+    /// Optional terminal color for marked squares.
+    pub tint: Option<Tint>,
+}
+
+/// A board rendered with overlays, see [`Board::with_overlays`].
+///
+/// Where overlays overlap the first one in the list wins.
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayView<'a> {
+    board: &'a Board,
+    overlays: &'a [Overlay],
+}
+
+impl Board {
+    /// Render this board with highlighted squares.
+    #[inline]
+    pub fn with_overlays<'a>(&'a self, overlays: &'a [Overlay]) -> OverlayView<'a> {
+        OverlayView {
+            board: self,
+            overlays,
+        }
+    }
+}
+
+impl<'a> Display for OverlayView<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, " a  b  c  d  e  f  g  h")?;
+
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let square = Square::at(rank, file).unwrap();
+                let piece = self.board.get(square).map(|piece| piece.as_char());
+                let overlay = self
+                    .overlays
+                    .iter()
+                    .find(|overlay| overlay.squares.get(square));
+
+                let cell = match (piece, overlay) {
+                    (Some(piece), Some(overlay)) => format!(" {piece}{}", overlay.marker),
+                    (None, Some(overlay)) => format!(" {} ", overlay.marker),
+                    (Some(piece), None) => format!(" {piece} "),
+                    (None, None) => " _ ".to_owned(),
+                };
+
+                match overlay.and_then(|overlay| overlay.tint) {
+                    Some(tint) => write!(f, "{}{cell}\x1b[0m", tint.ansi())?,
+                    None => write!(f, "{cell}")?,
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plain_matches_board_display() {
+        let board = Board::starting_position();
+        assert_eq!(board.with_overlays(&[]).to_string(), board.to_string());
+    }
+
+    #[test]
+    fn markers() {
+        let board = Board::starting_position();
+        let overlays = [
+            Overlay {
+                squares: BitBoard(0x00_00_00_00_00_FF_00_00), // 3rd rank
+                marker: '*',
+                tint: None,
+            },
+            Overlay {
+                squares: BitBoard(0x00_00_00_00_00_01_00_01), // a1, a3
+                marker: '+',
+                tint: None,
+            },
+        ];
+
+        let rendered = board.with_overlays(&overlays).to_string();
+        let lines = rendered.lines().collect::<Vec<_>>();
+
+        // first overlay wins on a3
+        assert_eq!(lines[6], " *  *  *  *  *  *  *  * ");
+        assert_eq!(lines[8], " R+ N  B  Q  K  B  N  R ");
+    }
+}
@@ -65,6 +65,20 @@ impl PieceKind {
         }
     }
 
+    /// Lower case name, as in "the knight on f3".
+    #[inline]
+    #[rustfmt::skip]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Pawn   => "pawn",
+            Knight => "knight",
+            Bishop => "bishop",
+            Rook   => "rook",
+            Queen  => "queen",
+            King   => "king",
+        }
+    }
+
     /// Piece valuation on some arbitrary scale.
     #[inline]
     #[rustfmt::skip]
@@ -119,6 +133,30 @@ impl Piece {
         }
     }
 
+    /// Unicode chess symbol for this piece.
+    #[inline]
+    #[rustfmt::skip]
+    pub const fn as_unicode(&self) -> char {
+        match self.color {
+            Color::White => match self.kind {
+                Pawn   => '♙',
+                Knight => '♘',
+                Bishop => '♗',
+                Rook   => '♖',
+                Queen  => '♕',
+                King   => '♔',
+            },
+            Color::Black => match self.kind {
+                Pawn   => '♟',
+                Knight => '♞',
+                Bishop => '♝',
+                Rook   => '♜',
+                Queen  => '♛',
+                King   => '♚',
+            },
+        }
+    }
+
     #[inline]
     pub const fn from_char(c: char) -> Option<Self> {
         let (color, kind) = match c {
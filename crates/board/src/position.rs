@@ -1,6 +1,12 @@
 //! The full game position.
 
-use crate::{bitboard, BitBoard, Board, Capture, Color, MoveExt, PieceKind, Square};
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+
+use crate::bitboard::constants;
+use crate::{
+    attacks, zobrist, BitBoard, Board, Capture, Color, Move, MoveExt, Piece, PieceKind, Square,
+};
 
 bitflags::bitflags! {
     /// Player castling availability.
@@ -17,6 +23,17 @@ bitflags::bitflags! {
 }
 
 impl CastlingRights {
+    /// The flag for one side of one player.
+    #[inline]
+    pub const fn side(color: Color, kingside: bool) -> Self {
+        match (color, kingside) {
+            (Color::White, true) => Self::WHITE_OO,
+            (Color::White, false) => Self::WHITE_OOO,
+            (Color::Black, true) => Self::BLACK_OO,
+            (Color::Black, false) => Self::BLACK_OOO,
+        }
+    }
+
     #[inline]
     pub fn unset_oo(self, color: Color) -> Self {
         match color {
@@ -28,14 +45,125 @@ impl CastlingRights {
     #[inline]
     pub fn unset_ooo(self, color: Color) -> Self {
         match color {
-            Color::White => self & !Self::WHITE_OO,
-            Color::Black => self & !Self::BLACK_OO,
+            Color::White => self & !Self::WHITE_OOO,
+            Color::Black => self & !Self::BLACK_OOO,
+        }
+    }
+}
+
+impl Display for CastlingRights {
+    /// Format in FEN style, e.g. `KQkq` or `-` when no rights are left.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+
+        for (flag, c) in [
+            (Self::WHITE_OO, 'K'),
+            (Self::WHITE_OOO, 'Q'),
+            (Self::BLACK_OO, 'k'),
+            (Self::BLACK_OOO, 'q'),
+        ] {
+            if self.contains(flag) {
+                write!(f, "{c}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Squares involved in castling on one side.
+///
+/// In Chess960 the king and rook can start anywhere on the back rank (king between the rooks), but
+/// always end up on the same squares as in standard chess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Castle {
+    pub color: Color,
+    pub kingside: bool,
+    pub king_from: Square,
+    pub king_to: Square,
+    pub rook_from: Square,
+    pub rook_to: Square,
+}
+
+impl Castle {
+    /// Castling for `color` with the king and rook on the given files of its back rank.
+    pub fn new(color: Color, kingside: bool, king_file: u8, rook_file: u8) -> Self {
+        let rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        let (king_to, rook_to) = if kingside { (6, 5) } else { (2, 3) };
+
+        Self {
+            color,
+            kingside,
+            king_from: Square::at(rank, king_file).unwrap(),
+            king_to: Square::at(rank, king_to).unwrap(),
+            rook_from: Square::at(rank, rook_file).unwrap(),
+            rook_to: Square::at(rank, rook_to).unwrap(),
+        }
+    }
+
+    /// The castling right this castle needs.
+    #[inline]
+    pub const fn right(&self) -> CastlingRights {
+        CastlingRights::side(self.color, self.kingside)
+    }
+
+    /// Whether the king and rook start where they do in standard chess.
+    #[inline]
+    pub const fn is_standard(&self) -> bool {
+        self.king_from.file() == 4 && self.rook_from.file() == if self.kingside { 7 } else { 0 }
+    }
+
+    /// Destination square of the move encoding this castle.
+    ///
+    /// The king's destination in the standard setup, the rook's square otherwise, as the king may
+    /// move by a single square or not at all in Chess960.
+    #[inline]
+    pub const fn move_to(&self) -> Square {
+        if self.is_standard() {
+            self.king_to
+        } else {
+            self.rook_from
         }
     }
+
+    /// Squares the king passes over, both ends included. None of them may be attacked.
+    #[inline]
+    pub fn king_path(&self) -> BitBoard {
+        span(self.king_from, self.king_to)
+    }
+
+    /// Squares that must be empty, apart from the castling king and rook themselves.
+    #[inline]
+    pub fn path(&self) -> BitBoard {
+        (self.king_path() | span(self.rook_from, self.rook_to))
+            & !BitBoard::from_square(self.king_from)
+            & !BitBoard::from_square(self.rook_from)
+    }
+}
+
+/// Squares from `a` to `b` on the same rank, both included.
+fn span(a: Square, b: Square) -> BitBoard {
+    let (low, high) = (
+        a.raw_index().min(b.raw_index()),
+        a.raw_index().max(b.raw_index()),
+    );
+    BitBoard((u64::MAX << low) & (u64::MAX >> (63 - high)))
+}
+
+/// Whether any piece of `color` attacks `square`.
+fn is_attacked(board: &Board, square: Square, color: Color) -> bool {
+    !board
+        .attackers_to(square, board.get_full_bb(), color)
+        .is_empty()
 }
 
 /// Full chessboard state.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone)]
 pub struct Position {
     /// Piece positions.
     pub board: Board,
@@ -43,6 +171,10 @@ pub struct Position {
     pub active_color: Color,
     /// Castling rights flags.
     pub castling: CastlingRights,
+    /// Files of the castling rooks, by color and then kingside and queenside.
+    ///
+    /// Always the corners in standard chess, but anywhere on the back rank in Chess960.
+    pub castling_rooks: [[u8; 2]; 2],
     /// En passant target square.
     pub ep_target: Option<Square>,
     /// Half-move (ply) clock.
@@ -53,79 +185,698 @@ pub struct Position {
     /// Full-move counter.
     ///
     /// A full-move consists of two half-moves, one by white and one by black. This counts the total
-    /// number of moves since the game began. It starts at 1 and increments after black's move.
-    pub fullmove_counter: u8,
+    /// number of moves since the game began. It starts at 1 and increments after black's move,
+    /// sticking at the largest value instead of overflowing.
+    pub fullmove_counter: u16,
+    /// Moves made with [`Position::make_move`], most recent last.
+    ///
+    /// Not part of the position itself, so ignored when comparing and hashing.
+    pub history: Vec<Undo>,
+}
+
+/// Why a position can't come up in a game, see [`Position::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidPosition {
+    /// Each side needs exactly one king.
+    KingCount { color: Color, count: u32 },
+    /// Pawns can't stand on the first or last rank.
+    PawnOnBackRank(Square),
+    /// The side that just moved left its king in check.
+    OpponentInCheck,
+    /// A castling right without the king or rook it needs in place.
+    Castling { color: Color, kingside: bool },
+    /// No pawn could have just double pushed past the en passant target.
+    EnPassant(Square),
+}
+
+impl Display for InvalidPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KingCount { color, count } => write!(f, "{color:?} has {count} kings"),
+            Self::PawnOnBackRank(square) => write!(f, "pawn on the back rank at {square}"),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::Castling { color, kingside } => write!(
+                f,
+                "{color:?} can castle {} without the king and rook in place",
+                if *kingside { "kingside" } else { "queenside" }
+            ),
+            Self::EnPassant(square) => write!(f, "invalid en passant target {square}"),
+        }
+    }
+}
+
+/// What [`Position::unmake_move`] needs to take back a move.
+#[derive(Debug, Clone, Copy)]
+pub struct Undo {
+    /// The move that was made.
+    pub p_move: MoveExt,
+    /// State before the move that can't be recovered from the move itself.
+    pub castling: CastlingRights,
+    pub ep_target: Option<Square>,
+    pub halfmove_clock: u8,
+    /// Zobrist key before the move.
+    pub zobrist_key: u64,
+}
+
+/// What [`Position::unmake_null`] needs to take back a null move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullUndo {
+    pub ep_target: Option<Square>,
+    pub halfmove_clock: u8,
+}
+
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.active_color == other.active_color
+            && self.castling == other.castling
+            && self.held_castling_rooks() == other.held_castling_rooks()
+            && self.ep_target == other.ep_target
+            && self.halfmove_clock == other.halfmove_clock
+            && self.fullmove_counter == other.fullmove_counter
+    }
+}
+
+impl Eq for Position {}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.hash(state);
+        self.active_color.hash(state);
+        self.castling.hash(state);
+        self.held_castling_rooks().hash(state);
+        self.ep_target.hash(state);
+        self.halfmove_clock.hash(state);
+        self.fullmove_counter.hash(state);
+    }
 }
 
 impl Position {
+    /// Castling rook files of standard chess.
+    pub const STANDARD_CASTLING_ROOKS: [[u8; 2]; 2] = [[7, 0], [7, 0]];
+
     pub fn starting() -> Self {
         Position {
             board: Board::starting_position(),
             active_color: Color::White,
             castling: CastlingRights::all(),
+            castling_rooks: Self::STANDARD_CASTLING_ROOKS,
             ep_target: None,
             halfmove_clock: 0,
             fullmove_counter: 1,
+            history: Vec::new(),
         }
     }
 
-    /// Reset castle flags if a rook on `square_bb` changes.
+    /// Serialize this position as a FEN string, same as the [`Display`] impl.
+    #[inline]
+    pub fn to_fen(&self) -> String {
+        self.to_string()
+    }
+
+    /// Zobrist key of this position.
+    ///
+    /// The piece part is maintained incrementally by the board, so this is cheap to call after
+    /// every move. Move clocks are not part of the key.
+    #[inline]
+    pub const fn zobrist_key(&self) -> u64 {
+        self.board.zobrist_key()
+            ^ zobrist::castling(self.castling)
+            ^ zobrist::ep_target(self.ep_target)
+            ^ zobrist::side(self.active_color)
+    }
+
+    /// Compute the Zobrist key of this position from scratch.
+    #[inline]
+    pub const fn compute_zobrist_key(&self) -> u64 {
+        self.board.compute_zobrist_key()
+            ^ zobrist::castling(self.castling)
+            ^ zobrist::ep_target(self.ep_target)
+            ^ zobrist::side(self.active_color)
+    }
+
+    /// The en passant target, if a pawn of the side to move stands next to the pushed pawn.
+    ///
+    /// FEN writers differ on whether they record the target after every double push or only when
+    /// a capture is possible. This doesn't check pins, so it may keep a target the move generator
+    /// has no legal capture for.
+    pub fn capturable_ep_target(&self) -> Option<Square> {
+        self.ep_target
+            .filter(|&target| self.can_capture_en_passant(target))
+    }
+
+    fn can_capture_en_passant(&self, target: Square) -> bool {
+        // the pushed pawn is one rank past the target, towards the side that pushed it
+        let rank = match self.active_color {
+            Color::White => match target.rank().checked_sub(1) {
+                Some(rank) => rank,
+                None => return false,
+            },
+            Color::Black => target.rank() + 1,
+        };
+
+        let pawn = Some(Piece {
+            color: self.active_color,
+            kind: PieceKind::Pawn,
+        });
+
+        [target.file().checked_sub(1), Some(target.file() + 1)]
+            .into_iter()
+            .flatten()
+            .filter_map(|file| Square::at(rank, file))
+            .any(|square| self.board.get(square) == pawn)
+    }
+
+    /// How many times this position came up before, looking back through the history as far as
+    /// the last capture or pawn move.
+    ///
+    /// An en passant target only makes positions differ if a pawn could capture on it.
+    pub fn repetitions(&self) -> usize {
+        // a position with a target is the one right after a double push, if it has the same
+        // pieces as this one, this board tells whether the target was capturable then
+        let key_of = |key: u64, target: Option<Square>| {
+            let capturable = target.filter(|&target| self.can_capture_en_passant(target));
+            key ^ zobrist::ep_target(target) ^ zobrist::ep_target(capturable)
+        };
+        let key = key_of(self.zobrist_key(), self.ep_target);
+
+        // only positions with the same side to move, every other ply
+        self.history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .filter(|undo| key_of(undo.zobrist_key, undo.ep_target) == key)
+            .count()
+    }
+
+    /// Whether this position has come up at least `n` times, counting this one.
+    #[inline]
+    pub fn is_repetition(&self, n: usize) -> bool {
+        self.repetitions() + 1 >= n
+    }
+
+    /// Whether this position has come up three times, so either side can claim a draw.
+    #[inline]
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.is_repetition(3)
+    }
+
+    /// Whether fifty moves by each side passed without a capture or pawn move, so either side can
+    /// claim a draw.
+    ///
+    /// A checkmate on the last of those moves still wins, which is up to the caller to check.
     #[inline]
-    fn reset_rook_castling(&mut self, square_bb: BitBoard) {
-        if square_bb & bitboard::constants::A_FILE != 0 {
-            self.castling = self.castling.unset_ooo(self.active_color)
-        } else if square_bb & bitboard::constants::H_FILE != 0 {
-            self.castling = self.castling.unset_oo(self.active_color)
+    pub const fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Whether seventy-five moves by each side passed without a capture or pawn move, which ends
+    /// the game in a draw without a claim, unless the last move mated.
+    #[inline]
+    pub const fn is_seventy_five_move_draw(&self) -> bool {
+        self.halfmove_clock >= 150
+    }
+
+    /// Whether neither side has the material left to ever checkmate: bare kings, a single minor
+    /// piece, or only bishops that all stand on squares of one color.
+    ///
+    /// Positions where mate is possible but can't be forced, like two knights against a king,
+    /// aren't counted.
+    pub fn is_insufficient_material(&self) -> bool {
+        let board = &self.board;
+        let heavy = board.get_piece_kind_bb(PieceKind::Pawn)
+            | board.get_piece_kind_bb(PieceKind::Rook)
+            | board.get_piece_kind_bb(PieceKind::Queen);
+        if !heavy.is_empty() {
+            return false;
         }
+
+        let knights = board.get_piece_kind_bb(PieceKind::Knight);
+        let bishops = board.get_piece_kind_bb(PieceKind::Bishop);
+
+        (knights | bishops).count() <= 1
+            || knights.is_empty()
+                && (bishops.is_subset_of(constants::DARK_SQUARES)
+                    || bishops.is_subset_of(constants::LIGHT_SQUARES))
     }
 
-    /// Apply a move without preliminary checks (piece existence for egs).
-    pub fn apply_move_unchecked(&mut self, p_move: MoveExt) {
-        let from_sq = BitBoard::from_square(p_move.from);
-        let to_sq = BitBoard::from_square(p_move.to);
+    /// Whether both positions are the same for the purpose of play, ignoring the move clocks and
+    /// an en passant target nobody can capture on.
+    pub fn equivalent_to(&self, other: &Position) -> bool {
+        self.board == other.board
+            && self.active_color == other.active_color
+            && self.castling == other.castling
+            && self.held_castling_rooks() == other.held_castling_rooks()
+            && self.capturable_ep_target() == other.capturable_ep_target()
+    }
 
-        // apply move
-        let color_bb = self.board.get_color_bb_mut(self.active_color);
-        *color_bb &= !from_sq;
-        *color_bb |= to_sq;
+    /// Whether the king of the side to move is attacked, false if it has no king.
+    pub fn in_check(&self) -> bool {
+        let king = self.board.get_piece_bb(Piece {
+            color: self.active_color,
+            kind: PieceKind::King,
+        });
 
-        let piece_bb = self.board.get_piece_kind_bb_mut(p_move.piece_kind);
-        *piece_bb &= !from_sq;
-        *piece_bb |= to_sq;
+        king.lsb()
+            .is_some_and(|square| is_attacked(&self.board, square, !self.active_color))
+    }
 
-        // handle castling
-        if p_move.piece_kind == PieceKind::King {
-            self.castling = self.castling.unset_oo(self.active_color);
-            self.castling = self.castling.unset_ooo(self.active_color);
+    /// Pieces of `color` pinned to their king, by a slider of either kind. Empty if there is no
+    /// king.
+    pub fn pinned_pieces(&self, color: Color) -> BitBoard {
+        self.blockers(color, !color, color)
+    }
+
+    /// Pieces of the side to move that stand between one of its sliders and the opponent's king,
+    /// moving one off that line gives check.
+    pub fn discovered_check_candidates(&self) -> BitBoard {
+        let color = self.active_color;
+        self.blockers(!color, color, color)
+    }
+
+    /// `blocker_color` pieces that are the only piece between the `king_color` king and a
+    /// `slider_color` slider aiming at it.
+    fn blockers(&self, king_color: Color, slider_color: Color, blocker_color: Color) -> BitBoard {
+        let board = &self.board;
+        let Some(king) = board
+            .get_piece_bb(Piece {
+                color: king_color,
+                kind: PieceKind::King,
+            })
+            .lsb()
+        else {
+            return BitBoard::ZERO;
+        };
+
+        let kind_bb = |kind| {
+            board.get_piece_bb(Piece {
+                color: slider_color,
+                kind,
+            })
+        };
+        let queens = kind_bb(PieceKind::Queen);
+
+        // sliders that would hit the king on an empty board
+        let snipers = (attacks::bishop(king, BitBoard::ZERO)
+            & (kind_bb(PieceKind::Bishop) | queens))
+            | (attacks::rook(king, BitBoard::ZERO) & (kind_bb(PieceKind::Rook) | queens));
+
+        snipers.iter().fold(BitBoard::ZERO, |blockers, sniper| {
+            let between = Square::between(king, sniper) & board.get_full_bb();
 
-            // do castles
-            if p_move.from.raw_index().abs_diff(p_move.to.raw_index()) == 2 {
-                // queen side
-                let (rook_from_sq, rook_to_sq) = if p_move.to.raw_index() < p_move.from.raw_index()
+            match between.exactly_one() && between.is_subset_of(board.get_color_bb(blocker_color)) {
+                true => blockers | between,
+                false => blockers,
+            }
+        })
+    }
+
+    /// Check that the position could come up in a game, as far as can be told without its
+    /// history.
+    pub fn validate(&self) -> Result<(), InvalidPosition> {
+        let board = &self.board;
+
+        for color in [Color::White, Color::Black] {
+            let count = board
+                .get_piece_bb(Piece {
+                    color,
+                    kind: PieceKind::King,
+                })
+                .count();
+
+            if count != 1 {
+                return Err(InvalidPosition::KingCount { color, count });
+            }
+        }
+
+        let back_ranks = BitBoard(0xFF_00_00_00_00_00_00_FF);
+        if let Some(square) = (board.get_piece_kind_bb(PieceKind::Pawn) & back_ranks).lsb() {
+            return Err(InvalidPosition::PawnOnBackRank(square));
+        }
+
+        let opponent_king = board
+            .get_piece_bb(Piece {
+                color: !self.active_color,
+                kind: PieceKind::King,
+            })
+            .to_square_unchecked();
+        if is_attacked(board, opponent_king, self.active_color) {
+            return Err(InvalidPosition::OpponentInCheck);
+        }
+
+        for color in [Color::White, Color::Black] {
+            for kingside in [true, false] {
+                let Some(castle) = self.castle(color, kingside) else {
+                    continue;
+                };
+
+                let back_rank = match color {
+                    Color::White => 0,
+                    Color::Black => 7,
+                };
+                let rook = Piece {
+                    color,
+                    kind: PieceKind::Rook,
+                };
+                let rook_side = match kingside {
+                    true => castle.rook_from.file() > castle.king_from.file(),
+                    false => castle.rook_from.file() < castle.king_from.file(),
+                };
+
+                if castle.king_from.rank() != back_rank
+                    || board.get(castle.rook_from) != Some(rook)
+                    || !rook_side
                 {
-                    let rfs = from_sq >> 4;
-                    let rts = from_sq >> 1;
-                    (rfs, rts)
+                    return Err(InvalidPosition::Castling { color, kingside });
                 }
-                // king side
-                else {
-                    let rfs = from_sq << 3;
-                    let rts = from_sq << 1;
-                    (rfs, rts)
+            }
+        }
+
+        if let Some(target) = self.ep_target {
+            let invalid = Err(InvalidPosition::EnPassant(target));
+
+            // the pawn passed over the target, from the rank behind it to the one in front
+            let (rank, from_rank, to_rank) = match self.active_color {
+                Color::White => (5, 6, 4),
+                Color::Black => (2, 1, 3),
+            };
+            if target.rank() != rank {
+                return invalid;
+            }
+
+            let from = Square::at(from_rank, target.file()).unwrap();
+            let to = Square::at(to_rank, target.file()).unwrap();
+            let pawn = Piece {
+                color: !self.active_color,
+                kind: PieceKind::Pawn,
+            };
+
+            if board.get(to) != Some(pawn)
+                || board.get(target).is_some()
+                || board.get(from).is_some()
+            {
+                return invalid;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Castling rook files that still have their castling right.
+    fn held_castling_rooks(&self) -> [[Option<u8>; 2]; 2] {
+        let mut rooks = [[None; 2]; 2];
+
+        for color in [Color::White, Color::Black] {
+            for (side, kingside) in [true, false].into_iter().enumerate() {
+                if self
+                    .castling
+                    .contains(CastlingRights::side(color, kingside))
+                {
+                    rooks[color as usize][side] = Some(self.castling_rooks[color as usize][side]);
+                }
+            }
+        }
+
+        rooks
+    }
+
+    /// Castling for `color` on one side, if the right is still there.
+    ///
+    /// Whether it is currently possible is up to the move generator.
+    pub fn castle(&self, color: Color, kingside: bool) -> Option<Castle> {
+        if !self
+            .castling
+            .contains(CastlingRights::side(color, kingside))
+        {
+            return None;
+        }
+
+        let king = self.board.get_piece_bb(Piece {
+            color,
+            kind: PieceKind::King,
+        });
+        if !king.exactly_one() {
+            return None;
+        }
+
+        Some(Castle::new(
+            color,
+            kingside,
+            king.to_square_unchecked().file(),
+            self.castling_rooks[color as usize][!kingside as usize],
+        ))
+    }
+
+    /// The castle a move of the side to move stands for, if any.
+    ///
+    /// Both the encoding of [`Castle::move_to`] and king takes own rook, as used by UCI in Chess960
+    /// mode, are recognized.
+    pub fn castle_of(&self, p_move: Move) -> Option<Castle> {
+        [true, false]
+            .into_iter()
+            .filter_map(|kingside| self.castle(self.active_color, kingside))
+            .find(|castle| {
+                castle.king_from == p_move.from
+                    && (p_move.to == castle.move_to() || p_move.to == castle.rook_from)
+            })
+    }
+
+    /// Like [`Position::castle_of`] for an already made king move, with the rights before it.
+    fn castle_made(
+        &self,
+        color: Color,
+        castling: CastlingRights,
+        p_move: MoveExt,
+    ) -> Option<Castle> {
+        if p_move.piece_kind != PieceKind::King {
+            return None;
+        }
+
+        [true, false]
+            .into_iter()
+            .filter(|&kingside| castling.contains(CastlingRights::side(color, kingside)))
+            .map(|kingside| {
+                let rook_file = self.castling_rooks[color as usize][!kingside as usize];
+                Castle::new(color, kingside, p_move.from.file(), rook_file)
+            })
+            .find(|castle| castle.king_from == p_move.from && p_move.to == castle.move_to())
+    }
+
+    /// A printable diagram of the board with the rest of the game state underneath.
+    #[inline]
+    pub const fn diagram(&self) -> Diagram<'_> {
+        Diagram {
+            position: self,
+            unicode: false,
+        }
+    }
+
+    /// Reset castle flags if a rook leaves or is captured on its starting `square`.
+    #[inline]
+    fn reset_rook_castling(&mut self, square: Square) {
+        let color = match square.rank() {
+            0 => Color::White,
+            7 => Color::Black,
+            _ => return,
+        };
+
+        let [oo, ooo] = self.castling_rooks[color as usize];
+        if square.file() == oo {
+            self.castling = self.castling.unset_oo(color);
+        }
+        if square.file() == ooo {
+            self.castling = self.castling.unset_ooo(color);
+        }
+    }
+
+    /// Make a move, filling in the moving piece and capture from the board.
+    ///
+    /// Only checks that the side to move has a piece on the from square, legality is up to the
+    /// caller. Returns the full move that was made.
+    pub fn make_move(&mut self, p_move: Move) -> Option<MoveExt> {
+        let piece = self.board.get(p_move.from)?;
+
+        if piece.color != self.active_color {
+            return None;
+        }
+
+        if let Some(castle) = self.castle_of(p_move) {
+            let p_move = MoveExt {
+                piece_kind: PieceKind::King,
+                from: castle.king_from,
+                to: castle.move_to(),
+                promotion: None,
+                capture: None,
+            };
+
+            self.make_move_ext(p_move);
+            return Some(p_move);
+        }
+
+        let capture = match self.board.get_piece_kind(p_move.to) {
+            Some(kind) => Some(Capture::Regular(kind)),
+            None if piece.kind == PieceKind::Pawn && Some(p_move.to) == self.ep_target => {
+                Some(Capture::EnPassant)
+            }
+            None => None,
+        };
+
+        let p_move = MoveExt {
+            piece_kind: piece.kind,
+            from: p_move.from,
+            to: p_move.to,
+            promotion: p_move.promotion,
+            capture,
+        };
+
+        self.make_move_ext(p_move);
+        Some(p_move)
+    }
+
+    /// Make an already resolved move, e.g. one from the move generator, so it can be unmade.
+    pub fn make_move_ext(&mut self, p_move: MoveExt) {
+        self.history.push(Undo {
+            p_move,
+            castling: self.castling,
+            ep_target: self.ep_target,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key(),
+        });
+
+        self.apply_move_unchecked(p_move);
+    }
+
+    /// Take back the last move made with [`Position::make_move`], returning it.
+    pub fn unmake_move(&mut self) -> Option<MoveExt> {
+        let undo = self.history.pop()?;
+        let p_move = undo.p_move;
+
+        self.active_color = self.active_color.opposite();
+        if self.active_color == Color::Black {
+            self.fullmove_counter = self.fullmove_counter.saturating_sub(1);
+        }
+
+        let color = self.active_color;
+
+        if p_move.promotion.is_some() {
+            self.board.set(p_move.to, None);
+            self.board.set(
+                p_move.to,
+                Some(Piece {
+                    color,
+                    kind: PieceKind::Pawn,
+                }),
+            );
+        }
+
+        match self.castle_made(color, undo.castling, p_move) {
+            Some(castle) => self.uncastle(castle),
+            None => self.board.move_piece(p_move.to, p_move.from),
+        }
+
+        match p_move.capture {
+            Some(Capture::Regular(kind)) => self.board.set(
+                p_move.to,
+                Some(Piece {
+                    color: color.opposite(),
+                    kind,
+                }),
+            ),
+            Some(Capture::EnPassant) => {
+                let captured_sq = match color {
+                    Color::White => p_move.to.raw_index() - 8,
+                    Color::Black => p_move.to.raw_index() + 8,
                 };
+                self.board.set(
+                    Square::from_index_unchecked(captured_sq),
+                    Some(Piece {
+                        color: color.opposite(),
+                        kind: PieceKind::Pawn,
+                    }),
+                );
+            }
+            None => {}
+        }
+
+        self.castling = undo.castling;
+        self.ep_target = undo.ep_target;
+        self.halfmove_clock = undo.halfmove_clock;
+
+        debug_assert!(self.board.is_consistent());
+        debug_assert_eq!(self.zobrist_key(), undo.zobrist_key);
+
+        Some(p_move)
+    }
 
-                let rook_bb = self.board.get_piece_kind_bb_mut(PieceKind::Rook);
-                *rook_bb &= !rook_from_sq;
-                *rook_bb |= rook_to_sq;
+    /// Pass the turn without moving, for null move pruning. Illegal in chess, and meaningless in
+    /// check.
+    ///
+    /// The pass isn't recorded in the history. The halfmove clock restarts instead, so no
+    /// repetition is looked for across it.
+    pub fn make_null(&mut self) -> NullUndo {
+        let undo = NullUndo {
+            ep_target: self.ep_target,
+            halfmove_clock: self.halfmove_clock,
+        };
+
+        self.ep_target = None;
+        self.halfmove_clock = 0;
+        if self.active_color == Color::Black {
+            self.fullmove_counter = self.fullmove_counter.saturating_add(1);
+        }
+        self.active_color = self.active_color.opposite();
+
+        undo
+    }
+
+    /// Take back the null move that returned `undo`.
+    pub fn unmake_null(&mut self, undo: NullUndo) {
+        self.active_color = self.active_color.opposite();
+        if self.active_color == Color::Black {
+            self.fullmove_counter = self.fullmove_counter.saturating_sub(1);
+        }
+        self.ep_target = undo.ep_target;
+        self.halfmove_clock = undo.halfmove_clock;
+    }
+
+    /// Apply a move without preliminary checks (piece existence for egs).
+    pub fn apply_move_unchecked(&mut self, p_move: MoveExt) {
+        // check for capture
+        match p_move.capture {
+            Some(Capture::Regular(cap)) => {
+                self.board.set(p_move.to, None);
 
-                let color_bb = self.board.get_color_bb_mut(self.active_color);
-                *color_bb &= !rook_from_sq;
-                *color_bb |= rook_to_sq;
+                if cap == PieceKind::Rook {
+                    self.reset_rook_castling(p_move.to);
+                }
+            }
+            Some(Capture::EnPassant) => {
+                let captured_sq = match self.active_color {
+                    Color::White => p_move.to.raw_index() - 8,
+                    Color::Black => p_move.to.raw_index() + 8,
+                };
+                self.board
+                    .set(Square::from_index_unchecked(captured_sq), None);
             }
+            _ => {}
+        }
+
+        // apply move
+        match self.castle_made(self.active_color, self.castling, p_move) {
+            Some(castle) => self.castle_pieces(castle),
+            None => self.board.move_piece(p_move.from, p_move.to),
+        }
+
+        // handle castling
+        if p_move.piece_kind == PieceKind::King {
+            self.castling = self.castling.unset_oo(self.active_color);
+            self.castling = self.castling.unset_ooo(self.active_color);
         }
 
         if p_move.piece_kind == PieceKind::Rook {
-            self.reset_rook_castling(from_sq);
+            self.reset_rook_castling(p_move.from);
         }
 
         // handle special pawn cases
@@ -135,12 +886,15 @@ impl Position {
 
         if p_move.piece_kind == PieceKind::Pawn {
             if let Some(promotion) = p_move.promotion {
-                // remove previously moved pawn
-                let pawn_bb = self.board.get_piece_kind_bb_mut(PieceKind::Pawn);
-                *pawn_bb &= !to_sq;
-
-                let promo_bb = self.board.get_piece_kind_bb_mut(promotion);
-                *promo_bb |= to_sq;
+                // replace previously moved pawn
+                self.board.set(p_move.to, None);
+                self.board.set(
+                    p_move.to,
+                    Some(Piece {
+                        color: self.active_color,
+                        kind: promotion,
+                    }),
+                );
             }
 
             // double push - set ep target
@@ -154,35 +908,568 @@ impl Position {
             }
         }
 
-        // check for capture
-        match p_move.capture {
-            Some(Capture::Regular(cap)) => {
-                *self.board.get_color_bb_mut(self.active_color.opposite()) &= !to_sq;
-                *self.board.get_piece_kind_bb_mut(cap) &= !to_sq;
-                *self.board.get_piece_kind_bb_mut(p_move.piece_kind) |= to_sq; // in case they're the same type
+        debug_assert!(self.board.is_consistent());
 
-                if cap == PieceKind::Rook {
-                    self.reset_rook_castling(to_sq);
+        // increment counters
+        if p_move.capture.is_some() || p_move.piece_kind == PieceKind::Pawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock = self.halfmove_clock.saturating_add(1);
+        }
+        if self.active_color == Color::Black {
+            self.fullmove_counter = self.fullmove_counter.saturating_add(1);
+        }
+        self.active_color = self.active_color.opposite();
+    }
+
+    /// Move the king and rook of a castle, which may land on each other's squares in Chess960.
+    fn castle_pieces(&mut self, castle: Castle) {
+        self.board.set(castle.king_from, None);
+        self.board.set(castle.rook_from, None);
+        self.board.set(
+            castle.king_to,
+            Some(Piece {
+                color: castle.color,
+                kind: PieceKind::King,
+            }),
+        );
+        self.board.set(
+            castle.rook_to,
+            Some(Piece {
+                color: castle.color,
+                kind: PieceKind::Rook,
+            }),
+        );
+    }
+
+    /// Undo [`Position::castle_pieces`].
+    fn uncastle(&mut self, castle: Castle) {
+        self.board.set(castle.king_to, None);
+        self.board.set(castle.rook_to, None);
+        self.board.set(
+            castle.king_from,
+            Some(Piece {
+                color: castle.color,
+                kind: PieceKind::King,
+            }),
+        );
+        self.board.set(
+            castle.rook_from,
+            Some(Piece {
+                color: castle.color,
+                kind: PieceKind::Rook,
+            }),
+        );
+    }
+
+    /// Castling field of the FEN: `KQkq` for the outermost rooks as in X-FEN, file letters as in
+    /// Shredder-FEN for any other rook.
+    fn write_castling(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.castling.is_empty() {
+            return write!(f, "-");
+        }
+
+        for color in [Color::White, Color::Black] {
+            let rank = match color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let rook = Some(Piece {
+                color,
+                kind: PieceKind::Rook,
+            });
+
+            for (side, kingside) in [true, false].into_iter().enumerate() {
+                if !self
+                    .castling
+                    .contains(CastlingRights::side(color, kingside))
+                {
+                    continue;
                 }
-            }
-            Some(Capture::EnPassant) => {
-                let captured_sq = match self.active_color {
-                    Color::White => to_sq >> 8,
-                    Color::Black => to_sq << 8,
+
+                let file = self.castling_rooks[color as usize][side];
+                let mut outer = if kingside { file + 1..8 } else { 0..file };
+                let outermost =
+                    !outer.any(|f| self.board.get(Square::at(rank, f).unwrap()) == rook);
+
+                let c = match (outermost, kingside) {
+                    (true, true) => 'K',
+                    (true, false) => 'Q',
+                    (false, _) => (b'A' + file) as char,
                 };
-                *self.board.get_color_bb_mut(self.active_color.opposite()) &= !captured_sq;
-                *self.board.get_piece_kind_bb_mut(PieceKind::Pawn) &= !captured_sq;
+
+                match color {
+                    Color::White => write!(f, "{c}")?,
+                    Color::Black => write!(f, "{}", c.to_ascii_lowercase())?,
+                }
             }
-            _ => {}
         }
 
-        // increment counters
-        if p_move.capture.is_none() || p_move.piece_kind != PieceKind::Pawn {
-            self.halfmove_clock += 1;
+        Ok(())
+    }
+}
+
+/// Formats the position as FEN.
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board.fen_placement())?;
+
+        let side = match self.active_color {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        write!(f, " {side} ")?;
+        self.write_castling(f)?;
+        write!(f, " ")?;
+        match self.ep_target {
+            Some(square) => write!(f, "{square}")?,
+            None => write!(f, "-")?,
         }
-        if self.active_color == Color::Black {
-            self.fullmove_counter += 1;
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_counter)
+    }
+}
+
+/// Board diagram with a game state footer, see [`Position::diagram`].
+#[derive(Debug, Clone, Copy)]
+pub struct Diagram<'a> {
+    position: &'a Position,
+    unicode: bool,
+}
+
+impl<'a> Diagram<'a> {
+    /// Draw pieces with unicode chess symbols instead of letters.
+    #[inline]
+    pub const fn unicode(self) -> Self {
+        Self {
+            unicode: true,
+            ..self
         }
-        self.active_color = self.active_color.opposite();
+    }
+}
+
+impl<'a> Display for Diagram<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let position = self.position;
+
+        writeln!(f, "   a  b  c  d  e  f  g  h")?;
+
+        for rank in (0..8).rev() {
+            write!(f, "{}", rank + 1)?;
+
+            for file in 0..8 {
+                let square = Square::at(rank, file).unwrap();
+
+                match position.board.get(square) {
+                    Some(piece) if self.unicode => write!(f, "  {}", piece.as_unicode())?,
+                    Some(piece) => write!(f, "  {}", piece.as_char())?,
+                    None => write!(f, "  .")?,
+                }
+            }
+
+            writeln!(f, "  {}", rank + 1)?;
+        }
+
+        writeln!(f, "   a  b  c  d  e  f  g  h")?;
+        writeln!(f)?;
+
+        let side = match position.active_color {
+            Color::White => "white",
+            Color::Black => "black",
+        };
+        writeln!(f, "Side to move: {side}")?;
+        writeln!(f, "Castling:     {}", position.castling)?;
+        match position.ep_target {
+            Some(square) => writeln!(f, "En passant:   {square}")?,
+            None => writeln!(f, "En passant:   -")?,
+        }
+        writeln!(f, "Halfmove:     {}", position.halfmove_clock)?;
+        writeln!(f, "Fullmove:     {}", position.fullmove_counter)?;
+        writeln!(f, "Key:          {:016x}", position.zobrist_key())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let position = |placement: &str| Position {
+            board: Board::from_fen_placement(placement).unwrap(),
+            castling: CastlingRights::empty(),
+            ..Position::starting()
+        };
+        let square = |s: &str| s.parse::<Square>().unwrap();
+
+        assert_eq!(Position::starting().validate(), Ok(()));
+
+        assert_eq!(
+            position("8/8/8/8/8/8/8/4K3").validate(),
+            Err(InvalidPosition::KingCount {
+                color: Color::Black,
+                count: 0
+            })
+        );
+        assert_eq!(
+            position("4k3/8/8/8/8/8/8/3KK3").validate(),
+            Err(InvalidPosition::KingCount {
+                color: Color::White,
+                count: 2
+            })
+        );
+        assert_eq!(
+            position("4k2P/8/8/8/8/8/8/4K3").validate(),
+            Err(InvalidPosition::PawnOnBackRank(square("h8")))
+        );
+        // white to move while black is in check from the rook
+        assert_eq!(
+            position("4k3/8/8/8/8/8/8/4R1K1").validate(),
+            Err(InvalidPosition::OpponentInCheck)
+        );
+
+        let mut castling = position("r3k2r/8/8/8/8/8/8/R3K1R1");
+        castling.castling = CastlingRights::all();
+        assert_eq!(
+            castling.validate(),
+            Err(InvalidPosition::Castling {
+                color: Color::White,
+                kingside: true
+            })
+        );
+        castling.castling = CastlingRights::all() - CastlingRights::WHITE_OO;
+        assert_eq!(castling.validate(), Ok(()));
+
+        let mut ep = position("4k3/8/8/3pP3/8/8/8/4K3");
+        ep.ep_target = Some(square("d6"));
+        assert_eq!(ep.validate(), Ok(()));
+        ep.ep_target = Some(square("e6"));
+        assert_eq!(ep.validate(), Err(InvalidPosition::EnPassant(square("e6"))));
+        ep.ep_target = Some(square("d3"));
+        assert_eq!(ep.validate(), Err(InvalidPosition::EnPassant(square("d3"))));
+    }
+
+    #[test]
+    fn castling_rights_fmt() {
+        assert_eq!(CastlingRights::all().to_string(), "KQkq");
+        assert_eq!(CastlingRights::empty().to_string(), "-");
+        assert_eq!(
+            (CastlingRights::WHITE_OOO | CastlingRights::BLACK_OO).to_string(),
+            "Qk"
+        );
+    }
+
+    #[test]
+    fn repetition() {
+        let mut position = Position::starting();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        assert!(position.is_repetition(1));
+        assert!(!position.is_repetition(2));
+
+        for uci in shuffle {
+            position.make_move(uci.parse().unwrap()).unwrap();
+        }
+        assert_eq!(position.repetitions(), 1);
+        assert!(!position.is_threefold_repetition());
+
+        // the knights are out again, a second time
+        position.make_move("g1f3".parse().unwrap()).unwrap();
+        assert_eq!(position.repetitions(), 1);
+
+        for uci in &shuffle[1..] {
+            position.make_move(uci.parse().unwrap()).unwrap();
+        }
+        assert_eq!(position.repetitions(), 2);
+        assert!(position.is_threefold_repetition());
+
+        // a pawn move can't be undone, nothing before it counts, and the target behind the pawn
+        // doesn't matter when no black pawn can take
+        position.make_move("e2e4".parse().unwrap()).unwrap();
+        for uci in ["g8f6", "g1f3", "f6g8", "f3g1"] {
+            position.make_move(uci.parse().unwrap()).unwrap();
+        }
+        assert_eq!(position.repetitions(), 1);
+    }
+
+    #[test]
+    fn fifty_moves() {
+        let mut position = Position::starting();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        for uci in shuffle.iter().cycle().take(99) {
+            position.make_move(uci.parse().unwrap()).unwrap();
+        }
+        assert_eq!(position.halfmove_clock, 99);
+        assert!(!position.is_fifty_move_draw());
+
+        position.make_move("f6g8".parse().unwrap()).unwrap();
+        assert!(position.is_fifty_move_draw());
+        assert!(!position.is_seventy_five_move_draw());
+
+        // unmaking restores the clock, a pawn move resets it
+        position.unmake_move();
+        assert_eq!(position.halfmove_clock, 99);
+        position.make_move("e7e5".parse().unwrap()).unwrap();
+        assert_eq!(position.halfmove_clock, 0);
+        assert!(!position.is_fifty_move_draw());
+
+        let position = Position {
+            halfmove_clock: 150,
+            ..Position::starting()
+        };
+        assert!(position.is_seventy_five_move_draw());
+    }
+
+    #[test]
+    fn in_check() {
+        let position = |placement: &str, active_color| Position {
+            board: Board::from_fen_placement(placement).unwrap(),
+            active_color,
+            castling: CastlingRights::empty(),
+            ..Position::starting()
+        };
+
+        assert!(!Position::starting().in_check());
+        assert!(position("4k3/8/8/8/8/8/8/4K2r", Color::White).in_check());
+        assert!(!position("4k3/8/8/8/8/8/8/4K2r", Color::Black).in_check());
+        assert!(position("4k3/8/3N4/8/8/8/8/4K3", Color::Black).in_check());
+        // the pawn blocks the rook
+        assert!(!position("4k3/4p3/8/8/8/8/4R3/4K3", Color::Black).in_check());
+        assert!(!position("8/8/8/8/8/8/8/4K3", Color::Black).in_check());
+    }
+
+    #[test]
+    fn pins() {
+        let position = |placement: &str, active_color| Position {
+            board: Board::from_fen_placement(placement).unwrap(),
+            active_color,
+            castling: CastlingRights::empty(),
+            ..Position::starting()
+        };
+        let squares = |names: &[&str]| {
+            names.iter().fold(BitBoard::ZERO, |bb, name| {
+                bb | BitBoard::from_square(name.parse().unwrap())
+            })
+        };
+
+        // the c3 pawn and d2 bishop shield each other from the a5 bishop
+        let white = position("4k3/8/8/b3r3/8/2P5/3BN3/4K3", Color::White);
+        assert_eq!(white.pinned_pieces(Color::White), squares(&["e2"]));
+        assert_eq!(white.pinned_pieces(Color::Black), BitBoard::ZERO);
+
+        let white = position("4k3/8/8/b3r3/8/2P5/4N3/4K3", Color::White);
+        assert_eq!(white.pinned_pieces(Color::White), squares(&["c3", "e2"]));
+
+        // the black knight is in the way too
+        let white = position("4k3/4r3/4n3/8/8/8/4N3/4K3", Color::White);
+        assert_eq!(white.pinned_pieces(Color::White), BitBoard::ZERO);
+
+        let white = position("4k3/8/2P5/4N3/B7/8/8/4R1K1", Color::White);
+        assert_eq!(white.discovered_check_candidates(), squares(&["c6", "e5"]));
+
+        let black = position("4k3/8/2P5/4N3/B7/8/8/4R1K1", Color::Black);
+        assert_eq!(black.discovered_check_candidates(), BitBoard::ZERO);
+
+        let white = position("4k3/4p3/8/4N3/8/8/8/4R1K1", Color::White);
+        assert_eq!(white.discovered_check_candidates(), BitBoard::ZERO);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let position = |placement: &str| Position {
+            board: Board::from_fen_placement(placement).unwrap(),
+            castling: CastlingRights::empty(),
+            ..Position::starting()
+        };
+
+        for (placement, insufficient) in [
+            ("8/8/4k3/8/8/3K4/8/8", true),
+            ("8/8/4k3/8/8/3K4/5B2/8", true),
+            ("8/8/4k3/8/2n5/3K4/8/8", true),
+            // bishops on c1 and f8 are both on dark squares
+            ("5b2/8/4k3/8/8/3K4/8/2B5", true),
+            ("5b2/8/4k3/8/8/3K4/8/2B1B3", true),
+            ("4b3/8/4k3/8/8/3K4/8/2B5", false),
+            ("8/8/4k3/8/8/3K4/8/1NB5", false),
+            ("8/8/4k3/8/8/3K4/8/1N1N4", false),
+            ("8/8/4k3/8/8/3K4/4P3/8", false),
+            ("8/8/4k3/8/8/3K4/8/7r", false),
+        ] {
+            let position = position(placement);
+            assert_eq!(
+                position.is_insufficient_material(),
+                insufficient,
+                "{placement}"
+            );
+        }
+
+        assert!(!Position::starting().is_insufficient_material());
+    }
+
+    #[test]
+    fn zobrist_key() {
+        let start = Position::starting();
+        assert_eq!(start.zobrist_key(), start.compute_zobrist_key());
+
+        // knights out and back transposes to the starting position
+        let mut position = start.clone();
+        for (from, to) in [("g1", "f3"), ("b8", "c6"), ("f3", "g1"), ("c6", "b8")] {
+            let from: Square = from.parse().unwrap();
+            let to: Square = to.parse().unwrap();
+            position.apply_move_unchecked(MoveExt {
+                piece_kind: PieceKind::Knight,
+                from,
+                to,
+                promotion: None,
+                capture: None,
+            });
+
+            assert_eq!(position.zobrist_key(), position.compute_zobrist_key());
+        }
+
+        assert_eq!(position.zobrist_key(), start.zobrist_key());
+
+        // ... except for the move counters
+        position.halfmove_clock = 0;
+        position.fullmove_counter = 1;
+        assert_eq!(position, start);
+    }
+
+    #[test]
+    fn make_unmake() {
+        let start = Position::starting();
+        let mut position = start.clone();
+
+        for (from, to) in [
+            ("e2", "e4"),
+            ("d7", "d5"),
+            ("e4", "d5"),
+            ("g8", "f6"),
+            ("f1", "b5"),
+        ] {
+            let p_move = Move {
+                from: from.parse().unwrap(),
+                to: to.parse().unwrap(),
+                promotion: None,
+            };
+            assert!(position.make_move(p_move).is_some(), "{from}{to}");
+        }
+
+        assert_eq!(
+            position.to_fen(),
+            "rnbqkb1r/ppp1pppp/5n2/1B1P4/8/8/PPPP1PPP/RNBQK1NR b KQkq - 2 3"
+        );
+        assert_eq!(position.history.len(), 5);
+
+        while position.unmake_move().is_some() {}
+
+        assert_eq!(position, start);
+        assert_eq!(position.zobrist_key(), start.zobrist_key());
+    }
+
+    #[test]
+    fn null_move() {
+        let mut start = Position::starting();
+        start.make_move("e2e4".parse().unwrap()).unwrap();
+        start.halfmove_clock = 4;
+        let mut position = start.clone();
+
+        let undo = position.make_null();
+        assert_eq!(
+            position.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+        assert_eq!(position.history.len(), 1);
+        assert_ne!(position.zobrist_key(), start.zobrist_key());
+
+        position.unmake_null(undo);
+        assert_eq!(position, start);
+        assert_eq!(position.zobrist_key(), start.zobrist_key());
+    }
+
+    #[test]
+    fn long_games() {
+        let mut position = Position::starting();
+        position.make_move("e2e4".parse().unwrap()).unwrap();
+        position.fullmove_counter = 255;
+        let start = position.clone();
+
+        position.make_move("e7e5".parse().unwrap()).unwrap();
+        assert_eq!(position.fullmove_counter, 256);
+        position.unmake_move();
+        assert_eq!(position, start);
+
+        position.fullmove_counter = u16::MAX;
+        position.make_move("e7e5".parse().unwrap()).unwrap();
+        assert_eq!(position.fullmove_counter, u16::MAX);
+    }
+
+    #[test]
+    fn to_fen() {
+        assert_eq!(
+            Position::starting().to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn equivalent_to() {
+        let mut after_push = Position::starting();
+        let e2e4 = Move {
+            from: "e2".parse().unwrap(),
+            to: "e4".parse().unwrap(),
+            promotion: None,
+        };
+        after_push.make_move(e2e4).unwrap();
+        assert_eq!(after_push.ep_target, "e3".parse().ok());
+        assert_eq!(after_push.capturable_ep_target(), None);
+
+        let mut without_ep = after_push.clone();
+        without_ep.ep_target = None;
+        without_ep.halfmove_clock = 10;
+        without_ep.fullmove_counter = 30;
+        assert!(after_push.equivalent_to(&without_ep));
+        assert_ne!(after_push, without_ep);
+
+        // a black pawn on d4 could take on e3
+        let d4 = "d4".parse().unwrap();
+        after_push.board.set(
+            d4,
+            Some(Piece {
+                color: Color::Black,
+                kind: PieceKind::Pawn,
+            }),
+        );
+        without_ep.board.set(
+            d4,
+            Some(Piece {
+                color: Color::Black,
+                kind: PieceKind::Pawn,
+            }),
+        );
+        assert_eq!(after_push.capturable_ep_target(), after_push.ep_target);
+        assert!(!after_push.equivalent_to(&without_ep));
+
+        without_ep.active_color = Color::White;
+        assert!(!without_ep.equivalent_to(&Position::starting()));
+    }
+
+    #[test]
+    fn diagram() {
+        let position = Position::starting();
+        let ascii = position.diagram().to_string();
+        let lines = ascii.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[1], "8  r  n  b  q  k  b  n  r  8");
+        assert_eq!(lines[5], "4  .  .  .  .  .  .  .  .  4");
+        assert_eq!(lines[8], "1  R  N  B  Q  K  B  N  R  1");
+        assert!(ascii.contains("Side to move: white"));
+        assert!(ascii.contains("Castling:     KQkq"));
+        assert!(ascii.contains("En passant:   -"));
+
+        let unicode = position.diagram().unicode().to_string();
+        assert!(unicode.contains("8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜  8"));
     }
 }
@@ -0,0 +1,306 @@
+//! Material and piece-square table scores, summed up by [`crate::Board`] as pieces come and go.
+//!
+//! Scores are kept apart for the middlegame and the endgame and are from white's point of view,
+//! black pieces count negative. The tables are the simplified evaluation function's, with an
+//! endgame king that heads for the centre and endgame pawns that push on. The two are blended by
+//! the [`phase`] of the game.
+
+use derive_more::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::{Board, Color, Piece, PieceKind, Square};
+
+/// A middlegame and an endgame score.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Add, AddAssign, Sub, SubAssign, Neg, Mul,
+)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    #[inline]
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Same as `+`, usable in const contexts.
+    #[inline]
+    pub const fn plus(self, other: Self) -> Self {
+        Self::new(self.mg + other.mg, self.eg + other.eg)
+    }
+
+    /// The middlegame score at [`MAX_PHASE`], the endgame score at 0 and in between in
+    /// proportion.
+    #[inline]
+    pub const fn taper(self, phase: i32) -> i32 {
+        let phase = if phase > MAX_PHASE { MAX_PHASE } else { phase };
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+
+    #[inline]
+    const fn signed(self, color: Color) -> Self {
+        match color {
+            Color::White => self,
+            Color::Black => Self::new(-self.mg, -self.eg),
+        }
+    }
+}
+
+/// Phase with every piece on the board.
+pub const MAX_PHASE: i32 = 24;
+
+/// How far from the endgame a board is, from the pieces left besides pawns and kings: a knight
+/// or bishop counts 1, a rook 2 and a queen 4, up to [`MAX_PHASE`] with promotions.
+pub fn phase(board: &Board) -> i32 {
+    let phase = [Color::White, Color::Black]
+        .into_iter()
+        .flat_map(|color| {
+            [
+                (PieceKind::Knight, 1),
+                (PieceKind::Bishop, 1),
+                (PieceKind::Rook, 2),
+                (PieceKind::Queen, 4),
+            ]
+            .map(|(kind, weight)| board.get_piece_bb(Piece { color, kind }).count() as i32 * weight)
+        })
+        .sum::<i32>();
+
+    phase.min(MAX_PHASE)
+}
+
+/// Material value of a piece, the king isn't counted.
+#[inline]
+pub const fn material(piece: Piece) -> Score {
+    let value = match piece.kind {
+        PieceKind::King => 0,
+        kind => kind.score() as i32,
+    };
+
+    Score::new(value, value).signed(piece.color)
+}
+
+/// Piece-square table bonus of a piece on a square, without its material.
+#[inline]
+pub const fn piece_square(piece: Piece, square: Square) -> Score {
+    // the tables are laid out as seen by white, a8 first
+    let index = match piece.color {
+        Color::White => square.raw_index() ^ 56,
+        Color::Black => square.raw_index(),
+    } as usize;
+    let kind = piece.kind as usize;
+
+    Score::new(MG[kind][index] as i32, EG[kind][index] as i32).signed(piece.color)
+}
+
+/// What the piece on each square adds to [`Board::material`] and [`Board::psqt`], by square
+/// index. Empty squares add nothing.
+pub fn by_square(board: &Board) -> [(Score, Score); 64] {
+    let mut scores = [(Score::ZERO, Score::ZERO); 64];
+
+    for square in board.get_full_bb() {
+        if let Some(piece) = board.get(square) {
+            scores[square.raw_index() as usize] = (material(piece), piece_square(piece, square));
+        }
+    }
+
+    scores
+}
+
+#[rustfmt::skip]
+const MG: [[i8; 64]; 6] = [
+    // pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         10,  10,  20,  30,  30,  20,  10,  10,
+          5,   5,  10,  25,  25,  10,   5,   5,
+          0,   0,   0,  20,  20,   0,   0,   0,
+          5,  -5, -10,   0,   0, -10,  -5,   5,
+          5,  10,  10, -20, -20,  10,  10,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    KNIGHT,
+    BISHOP,
+    ROOK,
+    QUEEN,
+    // king, behind its pawns
+    [
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -20, -30, -30, -40, -40, -30, -30, -20,
+        -10, -20, -20, -20, -20, -20, -20, -10,
+         20,  20,   0,   0,   0,   0,  20,  20,
+         20,  30,  10,   0,   0,  10,  30,  20,
+    ],
+];
+
+#[rustfmt::skip]
+const EG: [[i8; 64]; 6] = [
+    // pawn, the closer to promotion the better
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         80,  80,  80,  80,  80,  80,  80,  80,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         30,  30,  30,  30,  30,  30,  30,  30,
+         15,  15,  15,  15,  15,  15,  15,  15,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    KNIGHT,
+    BISHOP,
+    ROOK,
+    QUEEN,
+    // king, in the centre
+    [
+        -50, -40, -30, -20, -20, -30, -40, -50,
+        -30, -20, -10,   0,   0, -10, -20, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -30,   0,   0,   0,   0, -30, -30,
+        -50, -30, -30, -30, -30, -30, -30, -50,
+    ],
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i8; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i8; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i8; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i8; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn mirrored() {
+        for kind in PieceKind::iter() {
+            let white = Piece {
+                color: Color::White,
+                kind,
+            };
+            let black = Piece {
+                color: Color::Black,
+                kind,
+            };
+
+            assert_eq!(material(white), -material(black));
+
+            for index in 0..64 {
+                let square = Square::from_index_unchecked(index);
+                let mirrored = Square::from_index_unchecked(index ^ 56);
+
+                assert_eq!(piece_square(white, square), -piece_square(black, mirrored));
+            }
+        }
+
+        // a knight is better centralized, a king castled in the middlegame
+        let knight = Piece {
+            color: Color::White,
+            kind: PieceKind::Knight,
+        };
+        let d4 = Square::at(3, 3).unwrap();
+        let a1 = Square::at(0, 0).unwrap();
+        assert!(piece_square(knight, d4).mg > piece_square(knight, a1).mg);
+
+        let king = Piece {
+            color: Color::White,
+            kind: PieceKind::King,
+        };
+        let g1 = Square::at(0, 6).unwrap();
+        assert!(piece_square(king, g1).mg > piece_square(king, d4).mg);
+        assert!(piece_square(king, g1).eg < piece_square(king, d4).eg);
+    }
+
+    #[test]
+    fn tapering() {
+        assert_eq!(phase(&Board::starting_position()), MAX_PHASE);
+        assert_eq!(
+            phase(&Board::from_fen_placement("4k3/pppppppp/8/8/8/8/8/4K3").unwrap()),
+            0
+        );
+        // a rook each and a knight
+        let endgame = Board::from_fen_placement("r3k3/8/8/8/8/8/8/R3K1N1").unwrap();
+        assert_eq!(phase(&endgame), 5);
+        // promotions don't go past the start
+        let queens = Board::from_fen_placement("QQQQkQQQ/8/8/8/8/8/8/4K3").unwrap();
+        assert_eq!(phase(&queens), MAX_PHASE);
+
+        let score = Score::new(100, -20);
+        assert_eq!(score.taper(MAX_PHASE), 100);
+        assert_eq!(score.taper(0), -20);
+        assert_eq!(score.taper(MAX_PHASE / 2), 40);
+        assert_eq!(score.taper(MAX_PHASE + 8), 100);
+    }
+
+    #[test]
+    fn by_square_sums() {
+        let board =
+            Board::from_fen_placement("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1")
+                .unwrap();
+        let scores = by_square(&board);
+
+        let (material, placement) = scores
+            .iter()
+            .fold((Score::ZERO, Score::ZERO), |(m, p), &(dm, dp)| {
+                (m + dm, p + dp)
+            });
+        assert_eq!((material, placement), (board.material(), board.psqt()));
+
+        let e4 = Square::at(3, 4).unwrap();
+        let e3 = Square::at(2, 4).unwrap();
+        assert_eq!(scores[e4.raw_index() as usize].0, Score::new(100, 100));
+        assert_eq!(scores[e3.raw_index() as usize], (Score::ZERO, Score::ZERO));
+    }
+}
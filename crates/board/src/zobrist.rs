@@ -0,0 +1,139 @@
+//! Zobrist hashing keys.
+//!
+//! A position's key is the XOR of the keys for every piece on its square, the castling rights,
+//! the en passant file (if any) and the side to move (if black). Keys are generated at compile
+//! time from a fixed seed so they are stable across runs.
+
+use crate::{CastlingRights, Color, Piece, Square};
+
+/// splitmix64 step, returning the next state and output.
+const fn splitmix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const SEED: u64 = 0x5EA1_1011_C4E5_5000;
+
+/// Keys for a piece (`color * 6 + kind`) on a square.
+pub const PIECE_SQUARE: [[u64; 64]; 12] = {
+    let mut keys = [[0; 64]; 12];
+    let mut state = SEED;
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            let (next, key) = splitmix(state);
+            state = next;
+            keys[piece][square] = key;
+            square += 1;
+        }
+        piece += 1;
+    }
+
+    keys
+};
+
+/// Keys for every combination of castling rights.
+pub const CASTLING: [u64; 16] = {
+    // one key per right, combined so that toggling a single right is a single XOR
+    let mut rights = [0; 4];
+    let mut state = SEED ^ 0xCA57;
+
+    let mut i = 0;
+    while i < 4 {
+        let (next, key) = splitmix(state);
+        state = next;
+        rights[i] = key;
+        i += 1;
+    }
+
+    let mut keys = [0; 16];
+    let mut bits = 0;
+    while bits < 16 {
+        let mut i = 0;
+        while i < 4 {
+            if bits & (1 << i) != 0 {
+                keys[bits] ^= rights[i];
+            }
+            i += 1;
+        }
+        bits += 1;
+    }
+
+    keys
+};
+
+/// Keys for the en passant target's file.
+pub const EP_FILE: [u64; 8] = {
+    let mut keys = [0; 8];
+    let mut state = SEED ^ 0xE9;
+
+    let mut i = 0;
+    while i < 8 {
+        let (next, key) = splitmix(state);
+        state = next;
+        keys[i] = key;
+        i += 1;
+    }
+
+    keys
+};
+
+/// Key toggled when black is to move.
+pub const BLACK_TO_MOVE: u64 = splitmix(SEED ^ 0xB1AC).1;
+
+/// Key for a piece on a square.
+#[inline]
+pub const fn piece_square(piece: Piece, square: Square) -> u64 {
+    PIECE_SQUARE[piece.color as usize * 6 + piece.kind as usize][square.raw_index() as usize]
+}
+
+/// Key for a set of castling rights.
+#[inline]
+pub const fn castling(rights: CastlingRights) -> u64 {
+    CASTLING[rights.bits() as usize]
+}
+
+/// Key for an en passant target square.
+#[inline]
+pub const fn ep_target(square: Option<Square>) -> u64 {
+    match square {
+        Some(square) => EP_FILE[square.file() as usize],
+        None => 0,
+    }
+}
+
+/// Key for the side to move.
+#[inline]
+pub const fn side(color: Color) -> u64 {
+    match color {
+        Color::White => 0,
+        Color::Black => BLACK_TO_MOVE,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn keys_are_unique() {
+        let mut keys = HashSet::new();
+
+        for piece in PIECE_SQUARE {
+            keys.extend(piece);
+        }
+        keys.extend(&CASTLING[1..]);
+        keys.extend(EP_FILE);
+        keys.insert(BLACK_TO_MOVE);
+
+        assert_eq!(keys.len(), 12 * 64 + 15 + 8 + 1);
+        assert!(!keys.contains(&0));
+    }
+}
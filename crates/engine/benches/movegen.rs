@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-use sealion_board::{IntoEnumIterator, Piece, PieceKind, Position, Square};
+use sealion_board::{IntoEnumIterator, PieceKind, Position, Square};
 use sealion_engine::movegen::{Generator, MoveList};
 use sealion_engine::state::PositionState;
 
@@ -0,0 +1,175 @@
+//! Compact move sequences, for storing lots of games.
+//!
+//! A move is stored as its index in the position's legal move list, using just enough bits for
+//! that list's length, so a forced move takes none and a typical middlegame move 5 or 6. The
+//! sequence starts with its length as a LEB128 varint. Decoding has to start from the same
+//! position and needs the move generator to keep its order, so this isn't meant for long term
+//! archives.
+
+use sealion_board::{Move, MoveExt, Position};
+
+use crate::movegen::MoveList;
+use crate::state::PositionState;
+
+/// Encode moves played from `start`, `None` if one of them isn't legal.
+pub fn encode(start: &Position, moves: &[Move]) -> Option<Vec<u8>> {
+    let mut position = start.clone();
+    let mut writer = BitWriter::default();
+
+    let mut len = moves.len();
+    loop {
+        let byte = (len & 0x7F) as u8;
+        len >>= 7;
+
+        if len == 0 {
+            writer.bytes.push(byte);
+            break;
+        }
+        writer.bytes.push(byte | 0x80);
+    }
+
+    for &p_move in moves {
+        let legal = legal_moves(&position)?;
+        let index = legal.iter().position(|m| m.to_move() == p_move)?;
+
+        writer.write(index as u32, index_bits(legal.len()));
+        position.make_move_ext(legal[index]);
+    }
+
+    Some(writer.bytes)
+}
+
+/// Replay moves encoded by [`encode`] from `start`, `None` if the data doesn't fit the position.
+pub fn decode(start: &Position, bytes: &[u8]) -> Option<Vec<MoveExt>> {
+    let mut len = 0usize;
+    let mut header = 0;
+
+    loop {
+        let byte = *bytes.get(header)?;
+        len |= ((byte & 0x7F) as usize).checked_shl(7 * header as u32)?;
+        header += 1;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let mut position = start.clone();
+    let mut reader = BitReader::new(&bytes[header..]);
+    // the length is untrusted, reserve no more than a move per bit that follows
+    let mut moves = Vec::with_capacity(len.min((bytes.len() - header) * 8));
+
+    for _ in 0..len {
+        let legal = legal_moves(&position)?;
+        let index = reader.read(index_bits(legal.len()))? as usize;
+        let p_move = *legal.get(index)?;
+
+        position.make_move_ext(p_move);
+        moves.push(p_move);
+    }
+
+    Some(moves)
+}
+
+fn legal_moves(position: &Position) -> Option<Vec<MoveExt>> {
+    let state = PositionState::generate(position);
+
+    match MoveList::generate(&state) {
+        MoveList::Moves(moves) => Some(moves),
+        _ => None,
+    }
+}
+
+/// Bits needed for an index into a list of `len` moves.
+#[inline]
+const fn index_bits(len: usize) -> u32 {
+    usize::BITS - len.saturating_sub(1).leading_zeros()
+}
+
+/// Appends values least significant bit first.
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits used in the last byte, 0 if it's full or there is none.
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        for bit in 0..bits {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+
+            let last = self.bytes.last_mut().unwrap();
+            *last |= (((value >> bit) & 1) as u8) << self.used;
+            self.used = (self.used + 1) % 8;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read(&mut self, bits: u32) -> Option<u32> {
+        let mut value = 0;
+
+        for bit in 0..bits {
+            let byte = self.bytes.get(self.position / 8)?;
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << bit;
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::random::random_position;
+
+    #[test]
+    fn round_trip() {
+        for seed in 1..20 {
+            let end = random_position(seed, 200);
+            let moves = end
+                .history
+                .iter()
+                .map(|undo| undo.p_move.to_move())
+                .collect::<Vec<_>>();
+
+            let bytes = encode(&Position::starting(), &moves).unwrap();
+            let decoded = decode(&Position::starting(), &bytes).unwrap();
+
+            assert!(bytes.len() < moves.len() + 2);
+            assert_eq!(
+                decoded.iter().map(MoveExt::to_move).collect::<Vec<_>>(),
+                moves
+            );
+        }
+
+        assert_eq!(encode(&Position::starting(), &[]), Some(vec![0]));
+        assert!(decode(&Position::starting(), &[0]).is_some_and(|moves| moves.is_empty()));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let start = Position::starting();
+
+        assert_eq!(encode(&start, &["e2e5".parse().unwrap()]), None);
+        assert!(decode(&start, &[]).is_none());
+        // three moves announced, only enough bits for one
+        assert!(decode(&start, &[3, 0]).is_none());
+        // an absurd length with nothing after it
+        assert!(decode(&start, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]).is_none());
+    }
+}
@@ -0,0 +1,475 @@
+//! Explanations for illegal moves.
+//!
+//! Meant for interactive modes: instead of just rejecting user input, say what is wrong with it.
+
+use std::fmt::Display;
+
+use sealion_board::{
+    attacks, BitBoard, Board, Castle, CastlingRights, Color, Move, MoveExt, Piece, PieceKind,
+    Square,
+};
+
+use PieceKind::*;
+
+use crate::movegen::{Generator, MoveList};
+use crate::state::PositionState;
+
+/// Reason a move was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Illegal {
+    /// There is nothing on the from square.
+    NoPiece(Square),
+    /// The piece on the from square belongs to the opponent.
+    NotYourPiece(PieceKind, Square),
+    /// The target square is taken by one of our pieces.
+    OwnPiece(Square),
+    /// The piece can't move like that.
+    Unreachable(PieceKind, Square, Square),
+    /// A pawn reached the last rank without a promotion.
+    MissingPromotion,
+    /// A promotion on a move that doesn't promote, or to a piece that can't be promoted to.
+    BadPromotion(PieceKind),
+    /// The castling right for that side is gone.
+    CastlingRightsLost(CastlingRights),
+    /// Castling while in check.
+    CastlingOutOfCheck,
+    /// A piece stands between the king and rook.
+    CastlingBlocked(Square),
+    /// The king would pass an attacked square.
+    CastlingThroughCheck(Square, PieceKind, Square),
+    /// Capturing en passant when the double push wasn't the last move.
+    EnPassantUnavailable,
+    /// The king would move into check.
+    KingAttacked(PieceKind, Square),
+    /// The move doesn't resolve the current check.
+    InCheck(PieceKind, Square),
+    /// The piece is pinned to the king.
+    Pinned(PieceKind, Square, PieceKind, Square),
+}
+
+impl Display for Illegal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::NoPiece(square) => write!(f, "there is no piece on {square}"),
+            Self::NotYourPiece(kind, square) => write!(
+                f,
+                "the {} on {square} belongs to your opponent",
+                kind.name()
+            ),
+            Self::OwnPiece(square) => write!(f, "you can't capture your own piece on {square}"),
+            Self::Unreachable(kind, from, to) => {
+                write!(f, "a {} on {from} can't move to {to}", kind.name())
+            }
+            Self::MissingPromotion => write!(f, "a pawn reaching the last rank has to promote"),
+            Self::BadPromotion(kind) => write!(f, "can't promote to a {} here", kind.name()),
+            Self::CastlingRightsLost(side) => {
+                let side = if side.intersects(CastlingRights::WHITE_OO | CastlingRights::BLACK_OO) {
+                    "kingside"
+                } else {
+                    "queenside"
+                };
+                write!(f, "{side} castling rights lost")
+            }
+            Self::CastlingOutOfCheck => write!(f, "can't castle out of check"),
+            Self::CastlingBlocked(square) => {
+                write!(f, "can't castle with a piece in the way on {square}")
+            }
+            Self::CastlingThroughCheck(square, kind, attacker) => write!(
+                f,
+                "can't castle through {square}, it is attacked by the {} on {attacker}",
+                kind.name()
+            ),
+            Self::EnPassantUnavailable => write!(f, "en passant no longer available"),
+            Self::KingAttacked(kind, attacker) => write!(
+                f,
+                "your king would be in check from the {} on {attacker}",
+                kind.name()
+            ),
+            Self::InCheck(kind, attacker) => write!(
+                f,
+                "your king is in check from the {} on {attacker}",
+                kind.name()
+            ),
+            Self::Pinned(kind, square, pinner, pinner_sq) => write!(
+                f,
+                "the {} on {square} is pinned to your king by the {} on {pinner_sq}",
+                kind.name(),
+                pinner.name()
+            ),
+        }
+    }
+}
+
+/// Resolve `p_move` to a legal move, or explain why it isn't one.
+pub fn check_move(state: &PositionState, mut p_move: Move) -> Result<MoveExt, Illegal> {
+    let position = state.position;
+    let color = position.active_color;
+
+    // king takes own rook castling
+    if let Some(castle) = position.castle_of(p_move) {
+        p_move.to = castle.move_to();
+    }
+
+    let piece = position
+        .board
+        .get(p_move.from)
+        .ok_or(Illegal::NoPiece(p_move.from))?;
+
+    if piece.color != color {
+        return Err(Illegal::NotYourPiece(piece.kind, p_move.from));
+    }
+
+    let moves = match MoveList::generate(state) {
+        MoveList::Moves(moves) => moves,
+        _ => vec![],
+    };
+    let mut same_squares = moves
+        .iter()
+        .filter(|m| m.from == p_move.from && m.to == p_move.to)
+        .peekable();
+
+    if same_squares.peek().is_some() {
+        return match (
+            same_squares.find(|m| m.promotion == p_move.promotion),
+            p_move.promotion,
+        ) {
+            (Some(legal), _) => Ok(*legal),
+            (None, Some(promotion)) => Err(Illegal::BadPromotion(promotion)),
+            (None, None) => Err(Illegal::MissingPromotion),
+        };
+    }
+
+    if let Some(promotion) = p_move.promotion {
+        let last_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+
+        if piece.kind != Pawn || p_move.to.rank() != last_rank {
+            return Err(Illegal::BadPromotion(promotion));
+        }
+    }
+
+    if piece.kind == King && p_move.from.rank() == p_move.to.rank() {
+        if let Some(reason) = check_castling(state, p_move) {
+            return Err(reason);
+        }
+    }
+
+    let generator = Generator::new(state);
+    let to_bb = BitBoard::from_square(p_move.to);
+
+    if generator.pseudo_moves(p_move.from, piece.kind) & to_bb == 0 {
+        if position.board.get_color_bb(color) & to_bb != 0 {
+            return Err(Illegal::OwnPiece(p_move.to));
+        }
+
+        // diagonal pawn step onto an empty square, next to a pawn that double pushed earlier
+        if piece.kind == Pawn
+            && p_move.from.file() != p_move.to.file()
+            && Generator::pawn_attacks(p_move.from, color) & to_bb != 0
+        {
+            let passed = Square::at(p_move.from.rank(), p_move.to.file()).unwrap();
+            let enemy_pawn = Some(Piece {
+                color: !color,
+                kind: Pawn,
+            });
+
+            if position.board.get(passed) == enemy_pawn {
+                return Err(Illegal::EnPassantUnavailable);
+            }
+        }
+
+        return Err(Illegal::Unreachable(piece.kind, p_move.from, p_move.to));
+    }
+
+    // pseudo legal, so the king is left in check
+    let capture = match piece.kind {
+        Pawn => state.resolve_capture(p_move.to),
+        _ => state.resolve_capture_only(p_move.to),
+    };
+
+    let mut next = position.clone();
+    next.apply_move_unchecked(MoveExt {
+        piece_kind: piece.kind,
+        from: p_move.from,
+        to: p_move.to,
+        promotion: p_move.promotion,
+        capture,
+    });
+
+    let king_sq = next
+        .board
+        .get_piece_bb(Piece { color, kind: King })
+        .to_square_unchecked();
+    let (kind, attacker) = attacker_of(&next.board, king_sq, !color)
+        .ok_or(Illegal::Unreachable(piece.kind, p_move.from, p_move.to))?;
+
+    Err(if piece.kind == King {
+        Illegal::KingAttacked(kind, attacker)
+    } else if state.in_check() {
+        Illegal::InCheck(kind, attacker)
+    } else {
+        Illegal::Pinned(piece.kind, p_move.from, kind, attacker)
+    })
+}
+
+/// Explain why a king move that stands for a castle isn't a legal one.
+fn check_castling(state: &PositionState, p_move: Move) -> Option<Illegal> {
+    let position = state.position;
+    let color = position.active_color;
+    let rook = Some(Piece { color, kind: Rook });
+
+    let castle = [true, false]
+        .into_iter()
+        .map(|kingside| {
+            let rook_file = position.castling_rooks[color as usize][!kingside as usize];
+            Castle::new(color, kingside, p_move.from.file(), rook_file)
+        })
+        .find(|castle| {
+            castle.king_from == p_move.from
+                && (p_move.to == castle.move_to()
+                    || (p_move.to == castle.rook_from && position.board.get(p_move.to) == rook))
+        })?;
+
+    if !position.castling.contains(castle.right()) {
+        return Some(Illegal::CastlingRightsLost(castle.right()));
+    }
+
+    if state.in_check() {
+        return Some(Illegal::CastlingOutOfCheck);
+    }
+
+    if let Some(square) = (castle.path() & position.board.get_full_bb())
+        .set_iter()
+        .next()
+    {
+        return Some(Illegal::CastlingBlocked(square));
+    }
+
+    // squares the king passes over, nearest first
+    let mut passed = castle
+        .king_path()
+        .set_iter()
+        .filter(|&square| square != castle.king_from)
+        .collect::<Vec<_>>();
+    passed.sort_by_key(|square| square.file().abs_diff(castle.king_from.file()));
+
+    for square in passed {
+        if let Some((kind, attacker)) = attacker_of(&position.board, square, !color) {
+            return Some(Illegal::CastlingThroughCheck(square, kind, attacker));
+        }
+    }
+
+    // the castling rook may have been shielding the king's destination
+    let mut board = position.board.clone();
+    board.set(castle.king_from, None);
+    board.set(castle.rook_from, None);
+    board.set(castle.rook_to, rook);
+
+    if let Some((kind, attacker)) = attacker_of(&board, castle.king_to, !color) {
+        return Some(Illegal::CastlingThroughCheck(
+            castle.king_to,
+            kind,
+            attacker,
+        ));
+    }
+
+    None
+}
+
+/// Any piece of `color` attacking `square`.
+fn attacker_of(board: &Board, square: Square, color: Color) -> Option<(PieceKind, Square)> {
+    let blockers = board.get_full_bb();
+    let target = BitBoard::from_square(square);
+
+    board.get_color_bb(color).set_iter().find_map(|from| {
+        let kind = board.get_piece_kind(from)?;
+
+        let attacks = match kind {
+            Pawn => Generator::pawn_attacks(from, color),
+            Knight => Generator::knight_attacks(from),
+            Bishop => attacks::bishop(from, blockers),
+            Rook => attacks::rook(from, blockers),
+            Queen => attacks::queen(from, blockers),
+            King => Generator::king_attacks(from),
+        };
+
+        (attacks & target != 0).then_some((kind, from))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(fen: &str, from: &str, to: &str, promotion: Option<PieceKind>) -> String {
+        let position = sealion_fen::from_str(fen).unwrap();
+        let state = PositionState::generate(&position);
+
+        let p_move = Move {
+            from: from.parse().unwrap(),
+            to: to.parse().unwrap(),
+            promotion,
+        };
+
+        match check_move(&state, p_move) {
+            Ok(legal) => format!("legal {legal}"),
+            Err(reason) => reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn explanations() {
+        let tests = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e2",
+                "e4",
+                None,
+                "legal",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e3",
+                "e4",
+                None,
+                "there is no piece on e3",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e7",
+                "e5",
+                None,
+                "the pawn on e7 belongs to your opponent",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "g1",
+                "g3",
+                None,
+                "a knight on g1 can't move to g3",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "g1",
+                "e2",
+                None,
+                "you can't capture your own piece on e2",
+            ),
+            // knight pinned by the bishop
+            (
+                "rnbqk1nr/pppp1ppp/8/4p3/1b6/2NP4/PPP1PPPP/R1BQKBNR w KQkq - 0 1",
+                "c3",
+                "e4",
+                None,
+                "the knight on c3 is pinned to your king by the bishop on b4",
+            ),
+            (
+                "rnbqk1nr/pppp1ppp/8/4p3/1b6/3P4/PPP1PPPP/RNBQKBNR w KQkq - 0 1",
+                "e1",
+                "d2",
+                None,
+                "your king would be in check from the bishop on b4",
+            ),
+            (
+                "rnbqk1nr/pppp1ppp/8/4p3/1b6/2P5/PP1PPPPP/RNBQKBNR w KQkq - 0 1",
+                "a2",
+                "a3",
+                None,
+                "legal",
+            ),
+            (
+                "rnb1kbnr/pppp1ppp/8/4p3/7q/5P2/PPPPP1PP/RNBQKBNR w KQkq - 0 1",
+                "a2",
+                "a3",
+                None,
+                "your king is in check from the queen on h4",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1",
+                "e1",
+                "g1",
+                None,
+                "kingside castling rights lost",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K1NR w KQkq - 0 1",
+                "e1",
+                "g1",
+                None,
+                "can't castle with a piece in the way on g1",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1",
+                "e1",
+                "g1",
+                None,
+                "can't castle through f1, it is attacked by the rook on f2",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/4r3/R3K2R w KQkq - 0 1",
+                "e1",
+                "c1",
+                None,
+                "can't castle out of check",
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3",
+                "e5",
+                "d6",
+                None,
+                "en passant no longer available",
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+                "e5",
+                "d6",
+                None,
+                "legal",
+            ),
+            (
+                "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+                "e7",
+                "e8",
+                None,
+                "a pawn reaching the last rank has to promote",
+            ),
+            (
+                "8/4P3/8/8/8/8/k7/4K3 w - - 0 1",
+                "e7",
+                "e8",
+                Some(King),
+                "can't promote to a king here",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e2",
+                "e4",
+                Some(Queen),
+                "can't promote to a queen here",
+            ),
+            // Chess960, king takes own rook
+            (
+                "4k3/8/8/8/8/8/8/1R3K2 w Q - 0 1",
+                "f1",
+                "b1",
+                None,
+                "legal Kf1b1",
+            ),
+            // ... but the rook was shielding c1
+            (
+                "4k3/8/8/8/8/8/8/rR3K2 w Q - 0 1",
+                "f1",
+                "b1",
+                None,
+                "can't castle through c1, it is attacked by the rook on a1",
+            ),
+        ];
+
+        for (fen, from, to, promotion, expected) in tests {
+            let result = check(fen, from, to, promotion);
+            assert!(result.starts_with(expected), "{fen} {from}{to}: {result}");
+        }
+    }
+}
@@ -1,4 +1,12 @@
 #![allow(clippy::comparison_chain)]
 
+pub mod compress;
+pub mod legality;
 pub mod movegen;
+pub mod perft;
+pub mod random;
+pub mod san;
+pub mod score;
 pub mod state;
+pub mod status;
+pub mod threats;
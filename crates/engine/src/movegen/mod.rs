@@ -1,16 +1,14 @@
 //! Move generator implementation.
 
 use std::cmp::min;
-use std::ops::BitOr;
+use std::ops::{BitOr, ControlFlow};
 
-use sealion_board::{BitBoard, CastlingRights, Color, MoveExt, PieceKind, Square};
+use sealion_board::{attacks, BitBoard, Color, MoveExt, Piece, PieceKind, Square};
 use smallvec::SmallVec;
 
 use crate::state::PositionState;
 use PieceKind::*;
 
-mod tables;
-
 #[inline]
 pub fn merge_bb<const U: usize>(boards: [BitBoard; U]) -> BitBoard {
     boards.into_iter().fold(BitBoard::ZERO, BitOr::bitor)
@@ -50,7 +48,7 @@ impl<'a> Generator<'a> {
         let move_list = self.generate_impl();
 
         if move_list.is_empty() {
-            if self.state.attacks.bb & self.state.board_ext.king_bb != 0 {
+            if self.state.in_check() {
                 return MoveList::Checkmate;
             }
             return MoveList::Stalemate;
@@ -59,12 +57,58 @@ impl<'a> Generator<'a> {
         MoveList::Moves(move_list)
     }
 
+    /// Legal captures and promotions only, for the quiescence search. Without the quiet moves
+    /// this can't tell checkmate or stalemate, see [`PositionState::is_checkmate`].
+    pub fn generate_captures(&self) -> Vec<MoveExt> {
+        let mut moves = Vec::with_capacity(32);
+
+        let _ = self.visit_moves(true, |p_move| {
+            moves.push(p_move);
+            ControlFlow::Continue(())
+        });
+
+        moves
+    }
+
+    /// Check if there is at least one legal move, stopping at the first one found.
+    #[inline]
+    pub fn has_legal_move(&self) -> bool {
+        self.visit_moves(false, |_| ControlFlow::Break(()))
+            .is_break()
+    }
+
     fn generate_impl(&self) -> Vec<MoveExt> {
         let mut moves = Vec::with_capacity(256);
 
+        let _ = self.visit_moves(false, |p_move| {
+            moves.push(p_move);
+            ControlFlow::Continue(())
+        });
+
+        moves
+    }
+
+    /// Pass every legal move to `visit` until it breaks, or only captures and promotions.
+    fn visit_moves<F>(&self, captures_only: bool, mut visit: F) -> ControlFlow<()>
+    where
+        F: FnMut(MoveExt) -> ControlFlow<()>,
+    {
+        let color = self.state.position.active_color;
+        let targets = if captures_only {
+            self.state.position.board.get_color_bb(color.opposite())
+        } else {
+            BitBoard(u64::MAX)
+        };
+        // pawns also promote without capturing
+        let pawn_targets = match (captures_only, color) {
+            (false, _) => targets,
+            (true, Color::White) => targets | BitBoard::from_rank_occupancy(7, 0xff),
+            (true, Color::Black) => targets | BitBoard::from_rank_occupancy(0, 0xff),
+        };
+
         // initial king move generation
         let king_sq = self.state.board_ext.king_bb.to_square_unchecked();
-        let king_moves = self.pseudo_king_moves(king_sq) & !self.state.attacks.bb;
+        let king_moves = self.pseudo_king_moves(king_sq) & !self.state.attacks.bb & targets;
 
         for to_square in king_moves.set_iter() {
             let p_move = MoveExt {
@@ -75,13 +119,13 @@ impl<'a> Generator<'a> {
                 capture: self.state.resolve_capture_only(to_square),
             };
 
-            moves.push(p_move);
+            visit(p_move)?;
         }
 
         // Double check
         // - Forced king move
         if self.state.attacks.checkers.melee.len() + self.state.attacks.checkers.sliders.len() > 1 {
-            return moves;
+            return ControlFlow::Continue(());
         }
 
         let mut restricted = BitBoard(u64::MAX);
@@ -89,7 +133,7 @@ impl<'a> Generator<'a> {
         // Melee check
         // - Checker can be captured
         // ~ King move to non-attacked square
-        if let Some(checker_sq) = self.state.attacks.checkers.melee.get(0) {
+        if let Some(checker_sq) = self.state.attacks.checkers.melee.first() {
             restricted = BitBoard::from_square(*checker_sq);
         }
 
@@ -97,7 +141,7 @@ impl<'a> Generator<'a> {
         // - Checker can be captured
         // - Checker can be blocked along attack-ray
         // ~ King move to non-attacked square
-        if let Some(checker_ray) = self.state.attacks.checkers.sliders.get(0) {
+        if let Some(checker_ray) = self.state.attacks.checkers.sliders.first() {
             restricted = *checker_ray;
         }
 
@@ -107,12 +151,13 @@ impl<'a> Generator<'a> {
             .position
             .board
             .get_color_bb(self.state.position.active_color);
+        let check_restricted = restricted;
 
-        for square in friendly.set_iter() {
+        for square in (friendly & !self.state.board_ext.king_bb).set_iter() {
             let square_bb = BitBoard::from_square(square);
 
             // Handle pins
-            let mut restricted = restricted;
+            let mut restricted = check_restricted;
 
             for pinned in &self.state.attacks.pinners {
                 if square_bb & *pinned != 0 {
@@ -127,7 +172,21 @@ impl<'a> Generator<'a> {
 
             if p_kind == Pawn {
                 // insert pawn moves separately
-                let legal_moves = p_moves & restricted;
+                let mut legal_moves = p_moves & restricted & pawn_targets;
+
+                // en passant is checked on its own, it can evade a check by the pawn it
+                // captures and can expose the king along the rank of both pawns
+                if let Some(ep_target) = self.state.position.ep_target {
+                    let ep_bb = BitBoard::from_square(ep_target);
+
+                    if p_moves & ep_bb != 0 {
+                        legal_moves &= !ep_bb;
+
+                        if self.ep_is_legal(square, ep_target, check_restricted) {
+                            legal_moves |= ep_bb;
+                        }
+                    }
+                }
 
                 // handle inserting pawn moves
                 let promotable = match self.state.position.active_color {
@@ -146,10 +205,10 @@ impl<'a> Generator<'a> {
                         };
 
                         for promote_to in PieceKind::PROMOTABLE {
-                            moves.push(MoveExt {
+                            visit(MoveExt {
                                 promotion: Some(promote_to),
                                 ..p_move
-                            });
+                            })?;
                         }
                     }
                 } else {
@@ -162,12 +221,12 @@ impl<'a> Generator<'a> {
                             capture: self.state.resolve_capture(to_square),
                         };
 
-                        moves.push(p_move);
+                        visit(p_move)?;
                     }
                 }
             } else {
                 // insert other piece moves
-                let legal_moves = p_moves & restricted;
+                let legal_moves = p_moves & restricted & targets;
 
                 for to_square in legal_moves.set_iter() {
                     let p_move = MoveExt {
@@ -178,20 +237,57 @@ impl<'a> Generator<'a> {
                         capture: self.state.resolve_capture_only(to_square),
                     };
 
-                    moves.push(p_move);
+                    visit(p_move)?;
                 }
             }
         }
 
         // Castling moves
-        let castling = self.castling_moves();
-        moves.extend(castling);
+        if !captures_only {
+            for p_move in self.castling_moves() {
+                visit(p_move)?;
+            }
+        }
 
-        moves
+        ControlFlow::Continue(())
     }
 }
 
 impl<'a> Generator<'a> {
+    /// Check an en passant capture by actually removing both pawns and looking for sliders that
+    /// now attack the king.
+    fn ep_is_legal(&self, from: Square, ep_target: Square, check_restricted: BitBoard) -> bool {
+        let position = self.state.position;
+        let color = position.active_color;
+
+        let captured = match color {
+            Color::White => Square::from_index_unchecked(ep_target.raw_index() - 8),
+            Color::Black => Square::from_index_unchecked(ep_target.raw_index() + 8),
+        };
+        let captured_bb = BitBoard::from_square(captured);
+        let ep_bb = BitBoard::from_square(ep_target);
+
+        // has to resolve a check, either by capturing the checker or blocking its ray
+        if (captured_bb | ep_bb) & check_restricted == 0 {
+            return false;
+        }
+
+        let blockers =
+            (position.board.get_full_bb() & !BitBoard::from_square(from) & !captured_bb) | ep_bb;
+        let king_sq = self.state.board_ext.king_bb.to_square_unchecked();
+
+        let enemy = |kind| {
+            position.board.get_piece_bb(Piece {
+                color: color.opposite(),
+                kind,
+            })
+        };
+        let queens = enemy(Queen);
+
+        attacks::bishop(king_sq, blockers) & (enemy(Bishop) | queens) == 0
+            && attacks::rook(king_sq, blockers) & (enemy(Rook) | queens) == 0
+    }
+
     #[rustfmt::skip]
     pub fn pseudo_moves(&self, square: Square, kind: PieceKind) -> BitBoard {
         match kind {
@@ -206,28 +302,25 @@ impl<'a> Generator<'a> {
 
     #[inline]
     pub fn pseudo_bishop_moves(&self, square: Square) -> BitBoard {
-        self.sliding_moves::<0>(square)
+        attacks::bishop(square, self.state.position.board.get_full_bb()) & !self.friendly()
     }
 
     #[inline]
     pub fn pseudo_rook_moves(&self, square: Square) -> BitBoard {
-        self.sliding_moves::<1>(square)
+        attacks::rook(square, self.state.position.board.get_full_bb()) & !self.friendly()
     }
 
     #[inline]
-    fn sliding_moves<const DIR: u8>(&self, square: Square) -> BitBoard {
-        let friendly = self
-            .state
+    fn friendly(&self) -> BitBoard {
+        self.state
             .position
             .board
-            .get_color_bb(self.state.position.active_color);
-        let blockers = self.state.position.board.get_full_bb();
-
-        let attacks = Self::sliding_attacks::<DIR>(square, blockers);
-
-        merge_bb(attacks) & !friendly
+            .get_color_bb(self.state.position.active_color)
     }
 
+    /// Slider attacks split by direction, see [`attacks`] for the merged lookups.
+    ///
+    /// Only needed where the individual rays matter, like finding pins.
     pub fn sliding_attacks<const DIR: u8>(square: Square, blockers: BitBoard) -> [BitBoard; 4] {
         let mut moves = [BitBoard::ZERO; 4];
 
@@ -278,7 +371,7 @@ impl<'a> Generator<'a> {
     }
 
     pub fn knight_attacks(square: Square) -> BitBoard {
-        tables::KNIGHT_ATTACKS[square.raw_index() as usize]
+        attacks::knight(square)
     }
 
     #[inline]
@@ -292,7 +385,7 @@ impl<'a> Generator<'a> {
     }
 
     pub fn pawn_attacks(square: Square, color: Color) -> BitBoard {
-        tables::PAWN_ATTACKS[(square.raw_index() + (color as u8 * 64)) as usize]
+        attacks::pawn(square, color)
     }
 
     pub fn pseudo_pawn_moves(&self, square: Square) -> BitBoard {
@@ -313,7 +406,7 @@ impl<'a> Generator<'a> {
 
         match self.state.position.active_color {
             Color::White => {
-                let next = start << 8;
+                let next = start.shift_north();
 
                 if blockers & next == 0 {
                     // single push
@@ -321,7 +414,7 @@ impl<'a> Generator<'a> {
                         moves |= next;
 
                         // double push
-                        let next_2 = next << 8;
+                        let next_2 = next.shift_north();
 
                         if square.rank() == 1 && blockers & next_2 == 0 {
                             moves |= next_2;
@@ -331,15 +424,15 @@ impl<'a> Generator<'a> {
             }
             Color::Black => {
                 // single push
-                let next = start >> 8;
+                let next = start.shift_south();
 
                 if blockers & next == 0 {
                     // single push
-                    if square.rank() > 1 {
+                    if square.rank() > 0 {
                         moves |= next;
 
                         // double push
-                        let next_2 = next >> 8;
+                        let next_2 = next.shift_south();
 
                         if square.rank() == 6 && blockers & next_2 == 0 {
                             moves |= next_2;
@@ -353,7 +446,7 @@ impl<'a> Generator<'a> {
     }
 
     pub fn king_attacks(square: Square) -> BitBoard {
-        tables::KING_ATTACKS[square.raw_index() as usize]
+        attacks::king(square)
     }
 
     #[inline]
@@ -366,118 +459,55 @@ impl<'a> Generator<'a> {
                 .get_color_bb(self.state.position.active_color)
     }
 
-    const CASTLING_CHECKS: [CastlingChecks; 4] = {
-        // white
-        let start = 0b1110;
-
-        let mut checks_woo = CastlingChecks::zero();
-        checks_woo.clear = BitBoard(start << 4 & !(1 << 7));
-        checks_woo.safe = BitBoard(start << 3);
-        checks_woo.to_sq = BitBoard(1 << 6).to_square_unchecked();
-
-        let mut checks_wooo = CastlingChecks::zero();
-        checks_wooo.clear = BitBoard(start);
-        checks_wooo.safe = BitBoard(start << 1);
-        checks_wooo.to_sq = BitBoard(1 << 2).to_square_unchecked();
-
-        // black
-        let start = 0b111 << 57;
-
-        let mut checks_boo = CastlingChecks::zero();
-        checks_boo.clear = BitBoard(start << 4 & !(1 << 63));
-        checks_boo.safe = BitBoard(start << 3);
-        checks_boo.to_sq = BitBoard(1 << 58).to_square_unchecked();
-
-        let mut checks_booo = CastlingChecks::zero();
-        checks_booo.clear = BitBoard(start);
-        checks_booo.safe = BitBoard(start << 1);
-        checks_booo.to_sq = BitBoard(1 << 62).to_square_unchecked();
-
-        [checks_woo, checks_wooo, checks_boo, checks_booo]
-    };
-
     fn castling_moves(&self) -> SmallVec<[MoveExt; 2]> {
         let mut moves = SmallVec::new();
 
-        let blockers = self.state.position.board.get_full_bb();
+        let position = self.state.position;
+        let color = position.active_color;
+        let blockers = position.board.get_full_bb();
+        let rook = Some(Piece { color, kind: Rook });
+        let enemy_rooks = position.board.get_piece_bb(Piece {
+            color: !color,
+            kind: Rook,
+        }) | position.board.get_piece_bb(Piece {
+            color: !color,
+            kind: Queen,
+        });
+
+        for kingside in [true, false] {
+            let Some(castle) = position.castle(color, kingside) else {
+                continue;
+            };
 
-        let mut do_checks = |checks: CastlingChecks| {
-            if checks.clear & blockers == 0 && checks.safe & self.state.attacks.bb == 0 {
-                moves.push(MoveExt {
-                    piece_kind: King,
-                    from: self.state.board_ext.king_bb.to_square_unchecked(),
-                    to: checks.to_sq,
-                    promotion: None,
-                    capture: None,
-                });
+            if position.board.get(castle.rook_from) != rook
+                || castle.path() & blockers != 0
+                || castle.king_path() & self.state.attacks.bb != 0
+            {
+                continue;
             }
-        };
 
-        match self.state.position.active_color {
-            Color::White => {
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::WHITE_OO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[0]);
-                }
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::WHITE_OOO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[1]);
-                }
-            }
-            Color::Black => {
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::BLACK_OO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[2]);
-                }
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::BLACK_OOO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[3]);
-                }
+            // in Chess960 the castling rook may be all that shields the king's destination
+            let after = (blockers
+                & !BitBoard::from_square(castle.king_from)
+                & !BitBoard::from_square(castle.rook_from))
+                | BitBoard::from_square(castle.rook_to);
+            if attacks::rook(castle.king_to, after) & enemy_rooks != 0 {
+                continue;
             }
+
+            moves.push(MoveExt {
+                piece_kind: King,
+                from: castle.king_from,
+                to: castle.move_to(),
+                promotion: None,
+                capture: None,
+            });
         }
 
         moves
     }
 }
 
-/// Secondary checks for a valid castling move.
-#[derive(Debug, Clone, Copy)]
-struct CastlingChecks {
-    /// Squares in between the king and rook are not occupied.
-    clear: BitBoard,
-    /// Castling squares are not under attack.
-    safe: BitBoard,
-    /// Final square.
-    to_sq: Square,
-}
-
-impl CastlingChecks {
-    #[inline]
-    const fn zero() -> Self {
-        Self {
-            clear: BitBoard::ZERO,
-            safe: BitBoard::ZERO,
-            to_sq: Square::from_index_unchecked(0),
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -496,10 +526,10 @@ mod test {
             F: Fn(Generator<'_>, Square) -> BitBoard,
         {
             let position = sealion_fen::from_str(self.fen)
-                .expect(&format!("`{}` failed due to bad fen", self.name));
+                .unwrap_or_else(|_| panic!("`{}` failed due to bad fen", self.name));
             let state = PositionState::generate(&position);
             let square = Square::try_from(self.sq)
-                .expect(&format!("`{}` failed due to bad square", self.name));
+                .unwrap_or_else(|_| panic!("`{}` failed due to bad square", self.name));
             let generator = Generator::new(&state);
 
             let result = f(generator, square);
@@ -584,7 +614,7 @@ mod test {
                 name: "king side",
                 sq: (4, 0),
                 fen: "rnbqkbnr/pppp1ppp/8/K3p3/1P6/8/PP1PPPPP/RNBQ1BNR w kq - 0 1",
-                result: 0x03_82_01_80_00_00,
+                result: 0x03_02_01_00_00_00,
             },
         ];
 
@@ -593,6 +623,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn terminal_positions() {
+        let tests = [
+            // smothered mate
+            ("5rkr/4Nppp/8/8/8/8/8/6K1 b - - 0 1", true, false),
+            ("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1", false, true),
+            // en passant is the only way out of check
+            ("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", false, false),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                false,
+                false,
+            ),
+            (
+                "r4rk1/pQ1nppbp/2p1b1p1/8/3q2n1/2N1N1P1/PP2PPBP/R1B2RK1 b - - 2 9",
+                false,
+                false,
+            ),
+        ];
+
+        for (fen, checkmate, stalemate) in tests {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+
+            assert_eq!(state.is_checkmate(), checkmate, "{fen}");
+            assert_eq!(state.is_stalemate(), stalemate, "{fen}");
+            assert_eq!(
+                Generator::new(&state).has_legal_move(),
+                matches!(MoveList::generate(&state), MoveList::Moves(_)),
+                "{fen}"
+            );
+        }
+    }
+
     #[test]
     fn full_move_gen() {
         let position = Position::starting();
@@ -606,4 +670,36 @@ mod test {
             _ => panic!("starting position is not mate"),
         }
     }
+
+    #[test]
+    fn captures() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            // en passant, and promotions with and without a capture
+            "1r2k3/2P5/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            "4k3/8/8/8/8/8/6p1/4K2R b - - 0 1",
+            // evasions only, the check can't be captured
+            "4k3/8/8/8/8/8/3p4/r3K3 w - - 0 1",
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+            let MoveList::Moves(all) = MoveList::generate(&state) else {
+                panic!("{fen} has moves");
+            };
+            let expected = all
+                .into_iter()
+                .filter(|m| m.capture.is_some() || m.promotion.is_some())
+                .map(|m| m.to_move())
+                .collect::<Vec<_>>();
+            let captures = Generator::new(&state)
+                .generate_captures()
+                .into_iter()
+                .map(|m| m.to_move())
+                .collect::<Vec<_>>();
+
+            // in the same order as among all moves
+            assert!(!captures.is_empty(), "{fen}");
+            assert_eq!(captures, expected, "{fen}");
+        }
+    }
 }
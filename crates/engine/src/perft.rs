@@ -0,0 +1,93 @@
+//! Move path enumeration, for validating move generation against known node counts.
+//!
+//! <https://www.chessprogramming.org/Perft>
+
+use sealion_board::{MoveExt, Position};
+
+use crate::movegen::MoveList;
+use crate::state::PositionState;
+
+/// Count the leaf nodes of the legal move tree `depth` plies deep.
+pub fn perft(position: &mut Position, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let state = PositionState::generate(position);
+    let moves = match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves,
+        _ => return 0,
+    };
+
+    // bulk count, the moves are legal already
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+
+    for p_move in moves {
+        position.make_move_ext(p_move);
+        nodes += perft(position, depth - 1);
+        position.unmake_move();
+    }
+
+    nodes
+}
+
+/// Like [`perft`], but with the node count below each root move.
+pub fn perft_divide(position: &mut Position, depth: usize) -> Vec<(MoveExt, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+
+    let state = PositionState::generate(position);
+    let moves = match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves,
+        _ => return vec![],
+    };
+
+    moves
+        .into_iter()
+        .map(|p_move| {
+            position.make_move_ext(p_move);
+            let nodes = perft(position, depth - 1);
+            position.unmake_move();
+
+            (p_move, nodes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let mut position = Position::starting();
+
+        let divide = perft_divide(&mut position, 3);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8_902);
+        assert_eq!(perft(&mut position, 3), 8_902);
+
+        let e2e4 = divide
+            .iter()
+            .find(|(p_move, _)| p_move.to_move() == "e2e4".parse().unwrap())
+            .unwrap();
+        assert_eq!(e2e4.1, 600);
+
+        assert_eq!(position, Position::starting());
+    }
+
+    #[test]
+    fn terminal() {
+        // checkmated, nothing to count past the root
+        let mut position = sealion_fen::from_str("5rkr/4Nppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+
+        assert_eq!(perft(&mut position, 0), 1);
+        assert_eq!(perft(&mut position, 2), 0);
+        assert!(perft_divide(&mut position, 1).is_empty());
+    }
+}
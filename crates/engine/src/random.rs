@@ -0,0 +1,72 @@
+//! Reproducible random positions, for benchmarks, fuzzing and opening variety.
+
+use sealion_board::Position;
+
+use crate::movegen::MoveList;
+use crate::state::PositionState;
+
+/// Small xorshift generator, good enough for picking moves reproducibly.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// A zero seed would only ever give zeros, so it's replaced.
+    #[inline]
+    pub const fn new(seed: u64) -> Self {
+        Self(if seed == 0 { 0x5EA1_1011 } else { seed })
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`, `n` has to be positive.
+    #[inline]
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Play `plies` random legal moves from the starting position.
+///
+/// The same seed always gives the same position. Stops early if the game ends, the moves played
+/// are in the position's history.
+pub fn random_position(seed: u64, plies: usize) -> Position {
+    let mut rng = Rng::new(seed);
+    let mut position = Position::starting();
+
+    for _ in 0..plies {
+        let state = PositionState::generate(&position);
+        let MoveList::Moves(moves) = MoveList::generate(&state) else {
+            break;
+        };
+
+        let p_move = moves[rng.below(moves.len())];
+        position.make_move_ext(p_move);
+    }
+
+    position
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        for seed in [0, 1, 0xDEAD_BEEF] {
+            let position = random_position(seed, 40);
+
+            assert_eq!(position, random_position(seed, 40));
+            assert_eq!(position.history.len(), 40);
+            assert!(position.board.is_consistent());
+        }
+
+        assert_ne!(random_position(1, 20), random_position(2, 20));
+        assert_eq!(random_position(1, 0), Position::starting());
+    }
+}
@@ -0,0 +1,307 @@
+//! Standard algebraic notation (SAN) parsing and writing.
+//!
+//! <https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29>
+
+use std::fmt::Display;
+
+use sealion_board::{MoveExt, PieceKind, Square};
+
+use PieceKind::*;
+
+use crate::movegen::MoveList;
+use crate::state::PositionState;
+use crate::status::GameStatus;
+
+/// Why a SAN move couldn't be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanError {
+    /// Not SAN at all.
+    Syntax,
+    /// No legal move fits.
+    NoMatch,
+    /// More than one legal move fits, the move needs disambiguation.
+    Ambiguous,
+}
+
+impl Display for SanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax => write!(f, "not a SAN move"),
+            Self::NoMatch => write!(f, "no legal move matches"),
+            Self::Ambiguous => write!(f, "more than one legal move matches"),
+        }
+    }
+}
+
+/// Resolve a SAN move like `Nbd2`, `exd6 e.p.`, `O-O-O` or `e8=Q+` to a legal move.
+///
+/// Check, mate and annotation suffixes are accepted but not verified.
+pub fn parse_san(state: &PositionState, san: &str) -> Result<MoveExt, SanError> {
+    let san = san.trim();
+    let san = san.strip_suffix("e.p.").unwrap_or(san).trim_end();
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+    let moves = match MoveList::generate(state) {
+        MoveList::Moves(moves) => moves,
+        _ => return Err(SanError::NoMatch),
+    };
+
+    // castling, also with zeros as some software writes it
+    let kingside = match san {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+
+    if let Some(kingside) = kingside {
+        let position = state.position;
+        let castle = position
+            .castle(position.active_color, kingside)
+            .ok_or(SanError::NoMatch)?;
+
+        return only_match(moves.into_iter().filter(|m| {
+            m.piece_kind == King && m.from == castle.king_from && m.to == castle.move_to()
+        }));
+    }
+
+    let (kind, rest) = match san.chars().next().and_then(piece_kind) {
+        Some(kind) => (kind, &san[1..]),
+        None => (Pawn, san),
+    };
+
+    // promotion suffix, with or without `=`
+    let (rest, promotion) = match rest.char_indices().last() {
+        Some((i, c)) if kind == Pawn && !c.is_ascii_digit() => {
+            let promotion = piece_kind(c.to_ascii_uppercase()).ok_or(SanError::Syntax)?;
+            (
+                rest[..i].strip_suffix('=').unwrap_or(&rest[..i]),
+                Some(promotion),
+            )
+        }
+        _ => (rest, None),
+    };
+
+    if rest.len() < 2 || !rest.is_ascii() {
+        return Err(SanError::Syntax);
+    }
+
+    let (from_hint, to) = rest.split_at(rest.len() - 2);
+    let to = to.parse::<Square>().map_err(|_| SanError::Syntax)?;
+    let from_hint = from_hint.strip_suffix('x').unwrap_or(from_hint);
+
+    let (mut from_file, mut from_rank) = (None, None);
+    for c in from_hint.bytes() {
+        match c {
+            b'a'..=b'h' if from_file.is_none() && from_rank.is_none() => from_file = Some(c - b'a'),
+            b'1'..=b'8' if from_rank.is_none() => from_rank = Some(c - b'1'),
+            _ => return Err(SanError::Syntax),
+        }
+    }
+
+    only_match(moves.into_iter().filter(|m| {
+        m.piece_kind == kind
+            && m.to == to
+            && m.promotion == promotion
+            && from_file.is_none_or(|file| m.from.file() == file)
+            && from_rank.is_none_or(|rank| m.from.rank() == rank)
+    }))
+}
+
+/// Write a legal move in SAN, with as little disambiguation as needed and a check or mate suffix.
+pub fn to_san(state: &PositionState, p_move: MoveExt) -> String {
+    let position = state.position;
+    let mut san = String::new();
+
+    let castle = (p_move.piece_kind == King)
+        .then(|| position.castle_of(p_move.to_move()))
+        .flatten();
+
+    if let Some(castle) = castle {
+        san += if castle.kingside { "O-O" } else { "O-O-O" };
+    } else {
+        if p_move.piece_kind == Pawn {
+            if p_move.capture.is_some() {
+                san.push((b'a' + p_move.from.file()) as char);
+            }
+        } else {
+            san.push(p_move.piece_kind.as_char());
+
+            let others = match MoveList::generate(state) {
+                MoveList::Moves(moves) => moves,
+                _ => vec![],
+            };
+            let others = others
+                .into_iter()
+                .filter(|m| m.piece_kind == p_move.piece_kind && m.to == p_move.to)
+                .filter(|m| m.from != p_move.from)
+                .collect::<Vec<_>>();
+
+            let file = (b'a' + p_move.from.file()) as char;
+            let rank = (b'1' + p_move.from.rank()) as char;
+
+            if !others.is_empty() {
+                if others.iter().all(|m| m.from.file() != p_move.from.file()) {
+                    san.push(file);
+                } else if others.iter().all(|m| m.from.rank() != p_move.from.rank()) {
+                    san.push(rank);
+                } else {
+                    san.push(file);
+                    san.push(rank);
+                }
+            }
+        }
+
+        if p_move.capture.is_some() {
+            san.push('x');
+        }
+        san += &p_move.to.to_string();
+
+        if let Some(promotion) = p_move.promotion {
+            san.push('=');
+            san.push(promotion.as_char());
+        }
+    }
+
+    let mut after = position.clone();
+    after.make_move_ext(p_move);
+    if after.is_checkmate() {
+        san.push('#');
+    } else if after.in_check() {
+        san.push('+');
+    }
+
+    san
+}
+
+fn piece_kind(c: char) -> Option<PieceKind> {
+    match c {
+        'N' => Some(Knight),
+        'B' => Some(Bishop),
+        'R' => Some(Rook),
+        'Q' => Some(Queen),
+        'K' => Some(King),
+        _ => None,
+    }
+}
+
+fn only_match(mut moves: impl Iterator<Item = MoveExt>) -> Result<MoveExt, SanError> {
+    match (moves.next(), moves.next()) {
+        (Some(p_move), None) => Ok(p_move),
+        (None, _) => Err(SanError::NoMatch),
+        (Some(_), Some(_)) => Err(SanError::Ambiguous),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(fen: &str, san: &str) -> Result<String, SanError> {
+        let position = sealion_fen::from_str(fen).unwrap();
+        let state = PositionState::generate(&position);
+
+        parse_san(&state, san).map(|p_move| p_move.to_move().to_string())
+    }
+
+    #[test]
+    fn moves() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(parse(start, "e4"), Ok("e2e4".to_owned()));
+        assert_eq!(parse(start, "Nf3"), Ok("g1f3".to_owned()));
+        assert_eq!(parse(start, "Nf3!?"), Ok("g1f3".to_owned()));
+        assert_eq!(parse(start, "e5"), Err(SanError::NoMatch));
+        assert_eq!(parse(start, "Zf3"), Err(SanError::Syntax));
+        assert_eq!(parse(start, ""), Err(SanError::Syntax));
+
+        // knights on b1 and f3 can both reach d2
+        let knights = "rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1";
+        assert_eq!(parse(knights, "Nd2"), Err(SanError::Ambiguous));
+        assert_eq!(parse(knights, "Nbd2"), Ok("b1d2".to_owned()));
+        assert_eq!(parse(knights, "Nfd2"), Ok("f3d2".to_owned()));
+
+        // rooks on a1 and a5
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(parse(rooks, "R1a3"), Ok("a1a3".to_owned()));
+        assert_eq!(parse(rooks, "R5a3"), Ok("a5a3".to_owned()));
+        assert_eq!(parse(rooks, "Ra1a3"), Ok("a1a3".to_owned()));
+    }
+
+    #[test]
+    fn special_moves() {
+        let ep = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        assert_eq!(parse(ep, "exd6 e.p."), Ok("e5d6".to_owned()));
+        assert_eq!(parse(ep, "exd6"), Ok("e5d6".to_owned()));
+
+        let castle = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+        assert_eq!(parse(castle, "O-O-O"), Ok("e8c8".to_owned()));
+        assert_eq!(parse(castle, "0-0"), Ok("e8g8".to_owned()));
+
+        let chess960 = "4k3/8/8/8/8/8/8/1R3KR1 w KQ - 0 1";
+        assert_eq!(parse(chess960, "O-O"), Ok("f1g1".to_owned()));
+        assert_eq!(parse(chess960, "O-O-O"), Ok("f1b1".to_owned()));
+
+        let promote = "3qk3/4P3/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(parse(promote, "exd8=Q+"), Ok("e7d8q".to_owned()));
+        assert_eq!(parse(promote, "exd8N"), Ok("e7d8n".to_owned()));
+        assert_eq!(parse(promote, "exd8"), Err(SanError::NoMatch));
+        assert_eq!(parse(promote, "exd8=K"), Err(SanError::NoMatch));
+    }
+
+    fn write(fen: &str, uci: &str) -> String {
+        let position = sealion_fen::from_str(fen).unwrap();
+        let state = PositionState::generate(&position);
+        let MoveList::Moves(moves) = MoveList::generate(&state) else {
+            panic!("no moves in {fen}");
+        };
+        let p_move = moves
+            .into_iter()
+            .find(|m| m.to_move().to_string() == uci)
+            .unwrap();
+
+        to_san(&state, p_move)
+    }
+
+    #[test]
+    fn writing() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(write(start, "e2e4"), "e4");
+        assert_eq!(write(start, "g1f3"), "Nf3");
+
+        let knights = "rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1";
+        assert_eq!(write(knights, "b1d2"), "Nbd2");
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(write(rooks, "a1a3"), "R1a3");
+        // queens on a1, a3 and c1 all reach c3
+        let queens = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(write(queens, "a1c3"), "Qa1c3");
+
+        let ep = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        assert_eq!(write(ep, "e5d6"), "exd6");
+        let castle = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+        assert_eq!(write(castle, "e8c8"), "O-O-O");
+        let chess960 = "4k3/8/8/8/8/8/8/1R3KR1 w KQ - 0 1";
+        assert_eq!(write(chess960, "f1g1"), "O-O");
+        let promote = "3qk3/4P3/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(write(promote, "e7d8n"), "exd8=N");
+        assert_eq!(write(promote, "e7d8q"), "exd8=Q+");
+        let back_rank = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+        assert_eq!(write(back_rank, "a1a8"), "Ra8#");
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in 0..200 {
+            let position = crate::random::random_position(seed, 30);
+            let state = PositionState::generate(&position);
+            let MoveList::Moves(moves) = MoveList::generate(&state) else {
+                continue;
+            };
+
+            for p_move in moves {
+                let san = to_san(&state, p_move);
+                let parsed = parse_san(&state, &san).unwrap();
+                assert_eq!(parsed.to_move(), p_move.to_move(), "{san}");
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Rescaling internal scores for display.
+//!
+//! Evaluations don't agree on what a score of 100 means. For output, a raw score is rescaled so
+//! that +1.00 is where the side to move wins half its games, following the win rate model
+//! `1 / (1 + e^((a - score) / b))`. Search and move ordering keep using the raw score.
+
+/// Parameters of the win rate model for one evaluation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// Raw score with a 50% win rate.
+    pub a: f64,
+    /// How quickly the win rate grows with the score.
+    pub b: f64,
+}
+
+impl Calibration {
+    /// For the material count, a rough guess until there are self-play results to fit it to.
+    pub const MATERIAL: Self = Self { a: 250.0, b: 90.0 };
+
+    /// A raw score on the centipawn scale shown to users.
+    #[inline]
+    pub fn normalize(&self, raw: i32) -> i32 {
+        (raw as f64 * 100.0 / self.a).round() as i32
+    }
+
+    /// Win, draw and loss chances for the side to move, in per mille and summing to 1000.
+    pub fn wdl(&self, raw: i32) -> (u32, u32, u32) {
+        let rate = |score: f64| 1000.0 / (1.0 + ((self.a - score) / self.b).exp());
+
+        let win = rate(raw as f64).round() as u32;
+        let loss = rate(-raw as f64).round() as u32;
+
+        (win, 1000 - win - loss, loss)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn calibration() {
+        let calibration = Calibration::MATERIAL;
+
+        assert_eq!(calibration.normalize(0), 0);
+        assert_eq!(calibration.normalize(250), 100);
+        assert_eq!(calibration.normalize(-500), -200);
+
+        let (win, draw, loss) = calibration.wdl(250);
+        assert_eq!(win, 500);
+        assert_eq!(win + draw + loss, 1000);
+
+        assert_eq!(calibration.wdl(0).0, calibration.wdl(0).2);
+        assert_eq!(calibration.wdl(-250), (loss, draw, win));
+        assert!(calibration.wdl(2000).0 > 990);
+    }
+}
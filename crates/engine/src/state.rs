@@ -1,11 +1,11 @@
 //! Extended board state.
 
-use sealion_board::{BitBoard, Capture, Piece, PieceKind, Position, Square};
+use sealion_board::{attacks, psqt, BitBoard, Capture, Color, Piece, PieceKind, Position, Square};
 use smallvec::SmallVec;
 
 use PieceKind::*;
 
-use crate::movegen::{merge_bb, Generator};
+use crate::movegen::Generator;
 
 #[derive(Debug, Clone)]
 pub struct BoardExt {
@@ -100,10 +100,16 @@ impl<'a> PositionState<'a> {
                     this.generate_attacks(square, piece.kind);
                 }
 
-                // TODO: positional + attacked score
+                // TODO: attacked score
             }
         }
 
+        let psqt = position.board.psqt().taper(psqt::phase(&position.board)) as i16;
+        this.score.position = match position.active_color {
+            Color::White => psqt,
+            Color::Black => -psqt,
+        };
+
         this
     }
 
@@ -118,11 +124,13 @@ impl<'a> PositionState<'a> {
             .board
             .get_color_bb(self.position.active_color.opposite());
 
+        // rays only stop at the attacker's own pieces and our king, so our pieces in between are
+        // counted to tell a check from a pin
         let mut handle_king_atk = |pinner: [BitBoard; 4]| {
             for ray in pinner {
                 if ray & self.board_ext.king_bb != 0 {
-                    let intersect = ray & unfriendly;
-                    let n_intersect = intersect.0.count_ones();
+                    let intersect = ray & friendly;
+                    let n_intersect = intersect.count();
 
                     if n_intersect == 1 {
                         // only king intersects - check
@@ -146,16 +154,14 @@ impl<'a> PositionState<'a> {
                 // ignore king while generating ray attacks
                 // this is so king movement is restricted along the ray as well
                 // also will reveal hidden moves during evaluation
-                let attack = Generator::sliding_attacks::<0>(square, unfriendly | minions);
-                self.attacks.bb |= merge_bb(attack);
+                self.attacks.bb |= attacks::bishop(square, unfriendly | minions);
             }
             Rook => {
                 let king_atk =
                     Generator::sliding_attacks::<1>(square, unfriendly | self.board_ext.king_bb);
                 (handle_king_atk)(king_atk);
 
-                let attack = Generator::sliding_attacks::<1>(square, unfriendly | minions);
-                self.attacks.bb |= merge_bb(attack);
+                self.attacks.bb |= attacks::rook(square, unfriendly | minions);
             }
             Queen => {
                 self.generate_attacks(square, Bishop);
@@ -187,6 +193,24 @@ impl<'a> PositionState<'a> {
         }
     }
 
+    /// Check if the side to move is in check.
+    #[inline]
+    pub fn in_check(&self) -> bool {
+        self.attacks.bb & self.board_ext.king_bb != 0
+    }
+
+    /// Check if the side to move is checkmated, without generating the full move list.
+    #[inline]
+    pub fn is_checkmate(&self) -> bool {
+        self.in_check() && !Generator::new(self).has_legal_move()
+    }
+
+    /// Check if the side to move is stalemated, without generating the full move list.
+    #[inline]
+    pub fn is_stalemate(&self) -> bool {
+        !self.in_check() && !Generator::new(self).has_legal_move()
+    }
+
     #[inline]
     pub fn resolve_capture_only(&self, to_sq: Square) -> Option<Capture> {
         if let Some(piece) = self.board_ext.pieces[to_sq.raw_index() as usize] {
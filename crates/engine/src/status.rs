@@ -0,0 +1,120 @@
+//! Whether a game is over, for positions that need the move generator to tell.
+
+use std::fmt::Display;
+
+use sealion_board::{Color, Position};
+
+use crate::state::PositionState;
+
+/// Why a game ended without either side having to claim anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate { winner: Color },
+    Stalemate,
+    InsufficientMaterial,
+    SeventyFiveMoves,
+    FivefoldRepetition,
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Checkmate {
+                winner: Color::White,
+            } => write!(f, "checkmate, white wins"),
+            Self::Checkmate {
+                winner: Color::Black,
+            } => write!(f, "checkmate, black wins"),
+            Self::Stalemate => write!(f, "stalemate"),
+            Self::InsufficientMaterial => write!(f, "insufficient material"),
+            Self::SeventyFiveMoves => write!(f, "seventy-five move rule"),
+            Self::FivefoldRepetition => write!(f, "fivefold repetition"),
+        }
+    }
+}
+
+/// Game end checks on [`Position`], beside [`Position::in_check`].
+pub trait GameStatus {
+    fn is_checkmate(&self) -> bool;
+    fn is_stalemate(&self) -> bool;
+
+    /// How the game ended, if it did. Mate is checked first, it wins even on the move that
+    /// would otherwise end the game in a draw.
+    fn termination(&self) -> Option<Termination>;
+}
+
+impl GameStatus for Position {
+    #[inline]
+    fn is_checkmate(&self) -> bool {
+        PositionState::generate(self).is_checkmate()
+    }
+
+    #[inline]
+    fn is_stalemate(&self) -> bool {
+        PositionState::generate(self).is_stalemate()
+    }
+
+    fn termination(&self) -> Option<Termination> {
+        let state = PositionState::generate(self);
+
+        if state.is_checkmate() {
+            Some(Termination::Checkmate {
+                winner: !self.active_color,
+            })
+        } else if state.is_stalemate() {
+            Some(Termination::Stalemate)
+        } else if self.is_insufficient_material() {
+            Some(Termination::InsufficientMaterial)
+        } else if self.is_seventy_five_move_draw() {
+            Some(Termination::SeventyFiveMoves)
+        } else if self.is_repetition(5) {
+            Some(Termination::FivefoldRepetition)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn termination() {
+        let position = |fen: &str| sealion_fen::from_str(fen).unwrap();
+
+        let mate = position("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+        assert!(mate.in_check());
+        assert!(mate.is_checkmate());
+        assert!(!mate.is_stalemate());
+        assert_eq!(
+            mate.termination(),
+            Some(Termination::Checkmate {
+                winner: Color::Black
+            })
+        );
+
+        let stalemate = position("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert!(!stalemate.in_check());
+        assert!(stalemate.is_stalemate());
+        assert_eq!(stalemate.termination(), Some(Termination::Stalemate));
+
+        assert_eq!(
+            position("8/8/4k3/8/8/3K4/8/8 w - - 0 1").termination(),
+            Some(Termination::InsufficientMaterial)
+        );
+        assert_eq!(
+            position("8/8/4k3/8/8/3K4/8/7R w - - 150 100").termination(),
+            Some(Termination::SeventyFiveMoves)
+        );
+
+        let mut start = Position::starting();
+        assert_eq!(start.termination(), None);
+        for _ in 0..4 {
+            for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                start.make_move(uci.parse().unwrap()).unwrap();
+            }
+        }
+        assert_eq!(start.termination(), Some(Termination::FivefoldRepetition));
+    }
+}
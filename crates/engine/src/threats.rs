@@ -0,0 +1,151 @@
+//! Per square attacker counts for both sides, and quiet move safety.
+
+use sealion_board::{
+    attacks::AttackMap, Board, Color, IntoEnumIterator, MoveExt, PieceKind, Position, Square,
+};
+
+/// How many pieces of each side attack every square, and the cheapest of them.
+///
+/// Only direct attacks count, pieces lined up behind another slider (x-rays) are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackTable {
+    /// Attacker counts, indexed by color then square.
+    pub counts: [[u8; 64]; 2],
+    /// Least valuable attacker, indexed by color then square.
+    pub least_valuable: [[Option<PieceKind>; 64]; 2],
+}
+
+impl AttackTable {
+    /// Count the attacks of every piece on the board.
+    pub fn generate(board: &Board) -> Self {
+        let mut table = Self {
+            counts: [[0; 64]; 2],
+            least_valuable: [[None; 64]; 2],
+        };
+
+        let attacks = AttackMap::new(board);
+
+        for square in board.get_full_bb().set_iter() {
+            let Some(piece) = board.get(square) else {
+                continue;
+            };
+
+            let color = piece.color as usize;
+
+            for target in attacks.from(square).set_iter() {
+                let target = target.raw_index() as usize;
+                table.counts[color][target] += 1;
+
+                let least = &mut table.least_valuable[color][target];
+                if least.is_none_or(|least| piece.kind.score() < least.score()) {
+                    *least = Some(piece.kind);
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Number of pieces of `color` attacking `square`.
+    #[inline]
+    pub const fn attackers(&self, square: Square, color: Color) -> u8 {
+        self.counts[color as usize][square.raw_index() as usize]
+    }
+
+    /// Cheapest piece of `color` attacking `square`.
+    #[inline]
+    pub const fn least_valuable(&self, square: Square, color: Color) -> Option<PieceKind> {
+        self.least_valuable[color as usize][square.raw_index() as usize]
+    }
+}
+
+/// Whether a quiet move puts the piece where it's likely to be lost: attacked by a cheaper piece, or
+/// by more pieces than defend it.
+///
+/// Meant for demoting and pruning quiet moves, captures are better judged by a full exchange
+/// evaluation. Only direct attacks are counted, as in [`AttackTable`].
+pub fn is_unsafe_quiet(position: &Position, p_move: MoveExt) -> bool {
+    let color = position.active_color;
+
+    // the moving piece no longer blocks its from square
+    let mut board = position.board.clone();
+    board.move_piece(p_move.from, p_move.to);
+
+    let (attackers, least) = attackers_of(&board, p_move.to, !color);
+    let Some(least) = least else {
+        return false;
+    };
+
+    let kind = p_move.promotion.unwrap_or(p_move.piece_kind);
+    let (defenders, _) = attackers_of(&board, p_move.to, color);
+
+    least.score() < kind.score() || attackers > defenders
+}
+
+/// Number of `color` pieces attacking `square` and the cheapest of them.
+fn attackers_of(board: &Board, square: Square, color: Color) -> (u8, Option<PieceKind>) {
+    let attackers = board.attackers_to(square, board.get_full_bb(), color);
+
+    // kinds go from cheapest to most valuable
+    let least = PieceKind::iter().find(|&kind| attackers.intersects(board.get_piece_kind_bb(kind)));
+
+    (attackers.count() as u8, least)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use PieceKind::*;
+
+    #[test]
+    fn counts() {
+        // e5 pawn attacked by the knight on f3 and the bishop on b2, defended by the d6 pawn and
+        // the queen on e7
+        let position = sealion_fen::from_str("4k3/4q3/3p4/4p3/8/5N2/1B6/4K3 w - - 0 1").unwrap();
+        let table = AttackTable::generate(&position.board);
+        let e5 = "e5".parse().unwrap();
+
+        assert_eq!(table.attackers(e5, Color::White), 2);
+        assert_eq!(table.attackers(e5, Color::Black), 2);
+        assert_eq!(table.least_valuable(e5, Color::White), Some(Knight));
+        assert_eq!(table.least_valuable(e5, Color::Black), Some(Pawn));
+
+        // the queen is blocked by the pawn, no x-ray
+        let e4 = "e4".parse().unwrap();
+        assert_eq!(table.attackers(e4, Color::Black), 0);
+        assert_eq!(table.least_valuable(e4, Color::Black), None);
+
+        let start = AttackTable::generate(&sealion_board::Board::starting_position());
+        let f3 = "f3".parse().unwrap();
+        assert_eq!(start.attackers(f3, Color::White), 3);
+        assert_eq!(start.least_valuable(f3, Color::White), Some(Pawn));
+    }
+
+    #[test]
+    fn unsafe_quiets() {
+        let is_unsafe = |fen: &str, uci: &str| {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = crate::state::PositionState::generate(&position);
+            let crate::movegen::MoveList::Moves(moves) = crate::movegen::MoveList::generate(&state)
+            else {
+                unreachable!()
+            };
+
+            let p_move = moves
+                .into_iter()
+                .find(|p_move| p_move.to_move() == uci.parse().unwrap())
+                .unwrap();
+            is_unsafe_quiet(&position, p_move)
+        };
+
+        // attacked by a cheaper pawn
+        assert!(is_unsafe("4k3/8/2p5/8/8/3B4/8/4K3 w - - 0 1", "d3b5"));
+        // attacked by a rook and not defended
+        assert!(is_unsafe("4k3/r7/8/8/8/8/8/R3K3 w - - 0 1", "a1a6"));
+        // ... and defended by a pawn, an even trade at worst
+        assert!(!is_unsafe("4k3/r7/8/1P6/8/8/8/R3K3 w - - 0 1", "a1a6"));
+        assert!(!is_unsafe("4k3/r7/8/8/8/8/8/R3K3 w - - 0 1", "a1b1"));
+        // the rook was blocking the attack on a1 itself
+        assert!(is_unsafe("r3k3/8/8/8/8/8/R7/4K3 w - - 0 1", "a2a1"));
+    }
+}
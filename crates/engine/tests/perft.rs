@@ -1,39 +1,12 @@
 use paste::paste;
 
-use sealion_board::Position;
-use sealion_engine::movegen::MoveList;
-use sealion_engine::state::PositionState;
+use sealion_engine::perft::perft;
 
-pub fn perft(position: &Position, depth: usize, debug_depth: usize) -> usize {
-    if depth == 0 {
-        return 1;
-    }
-
-    let mut nodes = 0;
-
-    let state = PositionState::generate(&position);
-
-    if let MoveList::Moves(moves) = MoveList::generate(&state) {
-        for p_move in moves.into_iter() {
-            let mut new_position = position.clone();
-            new_position.apply_move_unchecked(p_move);
-            let move_nodes = perft(&new_position, depth - 1, debug_depth);
-
-            if depth == debug_depth {
-                println!("{}: {}", p_move.to_move(), move_nodes);
-            }
-
-            nodes += move_nodes;
-        }
-    }
-
-    nodes
-}
-
-fn do_perft(fen: &str, x: usize, result: usize) {
-    let position = sealion_fen::from_str(fen).unwrap();
-    let nodes = perft(&position, x, x);
+fn do_perft(fen: &str, x: usize, result: u64) {
+    let mut position = sealion_fen::from_str(fen).unwrap();
+    let nodes = perft(&mut position, x);
     assert_eq!(nodes, result);
+    assert_eq!(position.to_fen(), fen);
 }
 
 macro_rules! def_test {
@@ -71,3 +44,74 @@ def_test! {
         4 => 2_103_487
     ]
 }
+
+def_test! {
+    // https://www.chessprogramming.org/Perft_Results#Position_2
+    kiwipete "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1" => [
+        1 => 48,
+        2 => 2_039,
+        3 => 97_862,
+        4 => 4_085_603
+    ]
+}
+
+def_test! {
+    // https://www.chessprogramming.org/Perft_Results#Position_3
+    pos_3 "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1" => [
+        1 => 14,
+        2 => 191,
+        3 => 2_812,
+        4 => 43_238,
+        5 => 674_624
+    ]
+}
+
+def_test! {
+    // https://www.chessprogramming.org/Perft_Results#Position_4
+    pos_4 "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1" => [
+        1 => 6,
+        2 => 264,
+        3 => 9_467,
+        4 => 422_333
+    ]
+}
+
+def_test! {
+    // Chess960, from the perft suite of https://www.chessprogramming.org/Chess960_Perft_Results
+    chess960_1 "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9" => [
+        1 => 21,
+        2 => 528,
+        3 => 12_189,
+        4 => 326_672
+    ]
+}
+
+def_test! {
+    chess960_2 "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w KQkq - 1 9" => [
+        1 => 21,
+        2 => 807,
+        3 => 18_002,
+        4 => 667_366
+    ]
+}
+
+def_test! {
+    chess960_3 "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w KQ - 1 9" => [
+        1 => 20,
+        2 => 479,
+        3 => 10_471,
+        4 => 273_318
+    ]
+}
+
+#[test]
+fn concurrent_perft() {
+    // independent positions share only the read-only attack tables
+    let handles = (0..4)
+        .map(|_| std::thread::spawn(|| do_perft(KIWIPETE, 3, 97_862)))
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
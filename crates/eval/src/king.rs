@@ -0,0 +1,227 @@
+//! King safety: the pawn shield, open files next to the king and pieces attacking around it.
+//!
+//! As with [`crate::pawns`], the features are counted separately from their weights, so a tuner
+//! can fit the weights below. Shield and file terms only count in the middlegame; attacks fade
+//! out through the taper as the attacking pieces come off.
+
+use sealion_board::attacks::{self, AttackMap};
+use sealion_board::psqt::Score;
+use sealion_board::{BitBoard, Board, Color, Piece, PieceKind, Square};
+
+/// Per file next to the king whose shield pawn is two ranks in front of it.
+pub const SHIELD_ADVANCED: Score = Score::new(-10, 0);
+/// Per file next to the king without an own pawn on the two ranks in front of it.
+pub const SHIELD_MISSING: Score = Score::new(-25, 0);
+/// Per file next to the king without any own pawns.
+pub const SEMI_OPEN_FILE: Score = Score::new(-15, 0);
+/// Per file next to the king without any pawns at all, on top of it being semi-open.
+pub const OPEN_FILE: Score = Score::new(-15, 0);
+/// By the attack units on the king zone, when at least two pieces attack it.
+pub static KING_ATTACK: [Score; 16] = [
+    Score::ZERO,
+    Score::ZERO,
+    Score::ZERO,
+    Score::new(-10, -2),
+    Score::new(-20, -4),
+    Score::new(-30, -6),
+    Score::new(-45, -9),
+    Score::new(-60, -12),
+    Score::new(-80, -16),
+    Score::new(-100, -20),
+    Score::new(-125, -25),
+    Score::new(-150, -30),
+    Score::new(-180, -36),
+    Score::new(-210, -42),
+    Score::new(-240, -48),
+    Score::new(-270, -54),
+];
+
+/// Attack units of an attacker in the king zone, by kind.
+const ATTACK_UNITS: [(PieceKind, u32); 4] = [
+    (PieceKind::Knight, 2),
+    (PieceKind::Bishop, 2),
+    (PieceKind::Rook, 3),
+    (PieceKind::Queen, 5),
+];
+
+/// How exposed the king of one side is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KingFeatures {
+    pub color: Color,
+    /// Files next to the king, its own included, with the shield pawn two ranks in front of it.
+    pub shield_advanced: u32,
+    /// Files next to the king without an own pawn on the two ranks in front of it.
+    pub shield_missing: u32,
+    pub semi_open_files: u32,
+    pub open_files: u32,
+    /// Enemy pieces besides pawns and the king attacking the king zone.
+    pub attackers: u32,
+    /// The attackers weighted by kind, see [`KING_ATTACK`].
+    pub attack_units: u32,
+}
+
+impl KingFeatures {
+    pub fn new(board: &Board, attacks: &AttackMap, color: Color) -> Self {
+        let mut features = Self {
+            color,
+            shield_advanced: 0,
+            shield_missing: 0,
+            semi_open_files: 0,
+            open_files: 0,
+            attackers: 0,
+            attack_units: 0,
+        };
+
+        let king = board.get_piece_bb(Piece {
+            color,
+            kind: PieceKind::King,
+        });
+        let Some(king) = king.lsb() else {
+            return features;
+        };
+
+        let own_pawns = board.get_piece_bb(Piece {
+            color,
+            kind: PieceKind::Pawn,
+        });
+        let pawns = board.get_piece_kind_bb(PieceKind::Pawn);
+        let ahead = |ranks: i8| {
+            let rank = king.rank() as i8 + ranks * forward(color);
+            (0..8).contains(&rank).then_some(rank as u8)
+        };
+
+        for file in king.file().saturating_sub(1)..=(king.file() + 1).min(7) {
+            let file_bb = BitBoard::from_file_occupancy(file, 0xff);
+            let pawn_at = |rank: Option<u8>| {
+                rank.and_then(|rank| Square::at(rank, file))
+                    .is_some_and(|square| own_pawns.get(square))
+            };
+
+            if !pawn_at(ahead(1)) {
+                if pawn_at(ahead(2)) {
+                    features.shield_advanced += 1;
+                } else {
+                    features.shield_missing += 1;
+                }
+            }
+
+            if !own_pawns.intersects(file_bb) {
+                features.semi_open_files += 1;
+            }
+            if !pawns.intersects(file_bb) {
+                features.open_files += 1;
+            }
+        }
+
+        let attackers = attacks.attackers(zone(king, color), !color);
+        for (kind, units) in ATTACK_UNITS {
+            let count = (attackers & board.get_piece_kind_bb(kind)).count();
+            features.attackers += count;
+            features.attack_units += count * units;
+        }
+
+        features
+    }
+
+    /// Score of the features, from white's point of view.
+    pub fn score(&self) -> Score {
+        let mut score = SHIELD_ADVANCED * self.shield_advanced as i32
+            + SHIELD_MISSING * self.shield_missing as i32
+            + SEMI_OPEN_FILE * self.semi_open_files as i32
+            + OPEN_FILE * self.open_files as i32;
+
+        // a lone attacker can rarely do much
+        if self.attackers >= 2 {
+            score += KING_ATTACK[(self.attack_units as usize).min(KING_ATTACK.len() - 1)];
+        }
+
+        match self.color {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+/// King safety of both sides, from white's point of view.
+pub fn evaluate_king_safety(board: &Board, attacks: &AttackMap) -> Score {
+    KingFeatures::new(board, attacks, Color::White).score()
+        + KingFeatures::new(board, attacks, Color::Black).score()
+}
+
+/// The squares around a king of `color` on `king`, and the three in front of those.
+pub fn zone(king: Square, color: Color) -> BitBoard {
+    let around = attacks::king(king) | BitBoard::from_square(king);
+    match color {
+        Color::White => around | around.shift_north(),
+        Color::Black => around | around.shift_south(),
+    }
+}
+
+#[inline]
+const fn forward(color: Color) -> i8 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn features(placement: &str, color: Color) -> KingFeatures {
+        let board = Board::from_fen_placement(placement).unwrap();
+        KingFeatures::new(&board, &AttackMap::new(&board), color)
+    }
+
+    #[test]
+    fn shield() {
+        let castled = features("6k1/5ppp/8/8/8/8/5PPP/6K1", Color::White);
+        assert_eq!(castled.shield_missing + castled.shield_advanced, 0);
+        assert_eq!(castled.score(), Score::ZERO);
+
+        // g-pawn pushed, h-pawn gone
+        let weakened = features("6k1/5ppp/8/8/8/6P1/5P2/6K1", Color::White);
+        assert_eq!(weakened.shield_advanced, 1);
+        assert_eq!(weakened.shield_missing, 1);
+        assert_eq!(weakened.semi_open_files, 1);
+        assert_eq!(weakened.open_files, 0);
+
+        let pushed = features("6k1/5ppp/8/8/8/6PP/5P2/6K1", Color::White);
+        assert_eq!(pushed.shield_advanced, 2);
+        let pushed = features("6k1/5p1p/6p1/8/8/8/8/6K1", Color::Black);
+        assert_eq!(pushed.shield_advanced, 1);
+        assert_eq!(pushed.shield_missing, 0);
+
+        // on the edge only two files count
+        let corner = features("7k/8/8/8/8/8/8/K7", Color::White);
+        assert_eq!(corner.shield_missing, 2);
+        assert_eq!(corner.open_files, 2);
+    }
+
+    #[test]
+    fn attackers() {
+        // the queen and the knight hit the squares in front of the king, the rook is far off
+        let attacked = features("r5k1/8/8/8/6q1/5n2/5PPP/6K1", Color::White);
+        assert_eq!(attacked.attackers, 2);
+        assert_eq!(attacked.attack_units, 7);
+        assert!(attacked.score().mg < 0);
+
+        let lone = features("r5k1/8/8/8/8/5n2/5PPP/6K1", Color::White);
+        assert_eq!(lone.attackers, 1);
+        assert_eq!(lone.score(), Score::ZERO);
+
+        assert_eq!(zone(Square::at(0, 6).unwrap(), Color::White).count(), 9);
+        assert_eq!(zone(Square::at(7, 6).unwrap(), Color::Black).count(), 9);
+    }
+
+    #[test]
+    fn symmetry() {
+        let white = Board::from_fen_placement("r5k1/5ppp/8/8/6q1/5n2/5P1P/6K1").unwrap();
+        let black = Board::from_fen_placement("6k1/5p1p/5N2/6Q1/8/8/5PPP/R5K1").unwrap();
+        let white = evaluate_king_safety(&white, &AttackMap::new(&white));
+        let black = evaluate_king_safety(&black, &AttackMap::new(&black));
+        assert!(white.mg < 0);
+        assert_eq!(white, -black);
+    }
+}
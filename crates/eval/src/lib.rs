@@ -0,0 +1,71 @@
+//! Static evaluation: how good a position is for the side to move, without searching it.
+//!
+//! Material and piece-square tables, both summed up by the board as moves are made, so
+//! evaluating is cheap enough for every leaf of the search. Their middlegame and endgame scores
+//! are blended by the [`phase`] of the game, which matters most for the king: sheltered while
+//! there are pieces to attack it, in the centre once they're gone. The [`pawns`] structure and
+//! the safety of the [`king`]s are scored on top.
+
+use sealion_board::attacks::AttackMap;
+use sealion_board::psqt::phase;
+use sealion_board::{Color, Position};
+
+pub mod king;
+pub mod params;
+pub mod pawns;
+
+/// Score of `position` in centipawns, from the side to move's point of view.
+pub fn evaluate(position: &Position) -> i32 {
+    let board = &position.board;
+    let attacks = AttackMap::new(board);
+    let score = (board.material()
+        + board.psqt()
+        + pawns::evaluate_pawns(board)
+        + king::evaluate_king_safety(board, &attacks))
+    .taper(phase(board));
+
+    match position.active_color {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eval(fen: &str) -> i32 {
+        evaluate(&sealion_fen::from_str(fen).unwrap())
+    }
+
+    #[test]
+    fn side_to_move() {
+        assert_eq!(evaluate(&Position::starting()), 0);
+
+        // the same position with the colors swapped scores the same for the side to move
+        let white = eval("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        let black = eval("rnbqkb1r/pppp1ppp/5n2/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2");
+        assert_eq!(white, black);
+        assert!(white < 0);
+
+        let up_a_queen = "3qk3/8/8/8/8/8/8/4K3 w - - 0 1";
+        assert!(eval(up_a_queen) < -800);
+        assert_eq!(eval(up_a_queen), -eval("3qk3/8/8/8/8/8/8/4K3 b - - 0 1"));
+    }
+
+    #[test]
+    fn sane_moves() {
+        // a knight in the centre beats one on the rim
+        assert!(eval("4k3/8/8/8/3N4/8/8/4K3 b - - 0 1") < eval("4k3/8/8/8/8/8/8/N3K3 b - - 0 1"));
+        // developing beats moving the king in the middlegame, the king belongs in the centre
+        // in the endgame
+        assert!(
+            eval("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1")
+                < eval("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR b kq - 1 1")
+        );
+        assert!(
+            eval("4k3/pppp4/8/8/8/8/PPPP4/6K1 b - - 0 1")
+                > eval("4k3/pppp4/8/8/4K3/8/PPPP4/8 b - - 0 1")
+        );
+    }
+}
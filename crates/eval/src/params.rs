@@ -0,0 +1,255 @@
+//! The evaluation as a sum of weights times feature counts, for tuning the weights.
+//!
+//! Every weight is a [`Score`] at a fixed index of a flat list: material, the piece-square tables,
+//! then the [`pawns`] and [`king`] terms. A [`Trace`] of a board holds how often each weight
+//! counts for white minus for black, so evaluating it with the current [`Weights`] gives the same
+//! score as [`crate::evaluate`].
+
+use std::fmt::{Display, Formatter};
+
+use sealion_board::attacks::AttackMap;
+use sealion_board::psqt::{self, Score};
+use sealion_board::{Board, Color, IntoEnumIterator, Piece, PieceKind, Square};
+
+use crate::king::{self, KingFeatures};
+use crate::pawns::{self, PawnFeatures};
+
+/// Material of pawns to queens, the king has none. Its middlegame and endgame values are the
+/// same, from [`PieceKind::score`].
+pub const MATERIAL: usize = 0;
+/// Piece-square tables by kind, each laid out as seen by white, a8 first.
+pub const PSQT: usize = MATERIAL + 5;
+pub const DOUBLED: usize = PSQT + 6 * 64;
+pub const ISOLATED: usize = DOUBLED + 1;
+pub const BACKWARD: usize = ISOLATED + 1;
+/// Passed pawns by relative rank.
+pub const PASSED: usize = BACKWARD + 1;
+pub const SHIELD_ADVANCED: usize = PASSED + 8;
+pub const SHIELD_MISSING: usize = SHIELD_ADVANCED + 1;
+pub const SEMI_OPEN_FILE: usize = SHIELD_MISSING + 1;
+pub const OPEN_FILE: usize = SEMI_OPEN_FILE + 1;
+/// King attacks by attack units.
+pub const KING_ATTACK: usize = OPEN_FILE + 1;
+/// Number of weights.
+pub const COUNT: usize = KING_ATTACK + king::KING_ATTACK.len();
+
+/// A value for every weight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Weights(pub Vec<Score>);
+
+impl Weights {
+    /// The weights the evaluation uses.
+    pub fn current() -> Self {
+        let mut weights = vec![Score::ZERO; COUNT];
+
+        for kind in PieceKind::iter() {
+            let piece = Piece {
+                color: Color::White,
+                kind,
+            };
+            if kind != PieceKind::King {
+                weights[MATERIAL + kind as usize] = psqt::material(piece);
+            }
+            for index in 0..64 {
+                let square = Square::from_index_unchecked(index as u8 ^ 56);
+                weights[PSQT + kind as usize * 64 + index] = psqt::piece_square(piece, square);
+            }
+        }
+
+        weights[DOUBLED] = pawns::DOUBLED;
+        weights[ISOLATED] = pawns::ISOLATED;
+        weights[BACKWARD] = pawns::BACKWARD;
+        weights[PASSED..PASSED + 8].copy_from_slice(&pawns::PASSED);
+        weights[SHIELD_ADVANCED] = king::SHIELD_ADVANCED;
+        weights[SHIELD_MISSING] = king::SHIELD_MISSING;
+        weights[SEMI_OPEN_FILE] = king::SEMI_OPEN_FILE;
+        weights[OPEN_FILE] = king::OPEN_FILE;
+        weights[KING_ATTACK..COUNT].copy_from_slice(&king::KING_ATTACK);
+
+        Self(weights)
+    }
+}
+
+/// The weights as the Rust items they come from, to paste over the old ones.
+impl Display for Weights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let score = |score: Score| format!("Score::new({}, {})", score.mg, score.eg);
+        let list = |f: &mut Formatter<'_>, name: &str, scores: &[Score]| {
+            writeln!(f, "pub static {name}: [Score; {}] = [", scores.len())?;
+            for &value in scores {
+                writeln!(f, "    {},", score(value))?;
+            }
+            writeln!(f, "];")
+        };
+
+        writeln!(f, "// piece.rs, PieceKind::score")?;
+        for kind in PieceKind::iter().take(5) {
+            let name = format!("{kind:?}");
+            writeln!(f, "{name:<6} => {},", self.0[MATERIAL + kind as usize].mg)?;
+        }
+
+        for (name, phase) in [("MG", 0), ("EG", 1)] {
+            writeln!(f, "\n#[rustfmt::skip]\nconst {name}: [[i8; 64]; 6] = [")?;
+            for kind in PieceKind::iter() {
+                writeln!(f, "    // {kind:?}\n    [")?;
+                let table = &self.0[PSQT + kind as usize * 64..][..64];
+                for rank in table.chunks(8) {
+                    let rank = rank
+                        .iter()
+                        .map(|score| format!("{:4}", [score.mg, score.eg][phase]))
+                        .collect::<Vec<_>>();
+                    writeln!(f, "       {},", rank.join(","))?;
+                }
+                writeln!(f, "    ],")?;
+            }
+            writeln!(f, "];")?;
+        }
+
+        writeln!(f, "\n// pawns.rs")?;
+        for (name, index) in [
+            ("DOUBLED", DOUBLED),
+            ("ISOLATED", ISOLATED),
+            ("BACKWARD", BACKWARD),
+        ] {
+            writeln!(f, "pub const {name}: Score = {};", score(self.0[index]))?;
+        }
+        list(f, "PASSED", &self.0[PASSED..PASSED + 8])?;
+
+        writeln!(f, "\n// king.rs")?;
+        for (name, index) in [
+            ("SHIELD_ADVANCED", SHIELD_ADVANCED),
+            ("SHIELD_MISSING", SHIELD_MISSING),
+            ("SEMI_OPEN_FILE", SEMI_OPEN_FILE),
+            ("OPEN_FILE", OPEN_FILE),
+        ] {
+            writeln!(f, "pub const {name}: Score = {};", score(self.0[index]))?;
+        }
+        list(f, "KING_ATTACK", &self.0[KING_ATTACK..COUNT])
+    }
+}
+
+/// How often each weight counts in a position, white's minus black's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    /// See [`psqt::phase`].
+    pub phase: i32,
+    /// Weight indices and their counts, an index can appear more than once.
+    pub terms: Vec<(u16, i8)>,
+}
+
+impl Trace {
+    pub fn new(board: &Board) -> Self {
+        let mut terms = vec![];
+        let mut add = |index: usize, count: i32, color: Color| {
+            let count = match color {
+                Color::White => count,
+                Color::Black => -count,
+            };
+            if count != 0 {
+                terms.push((index as u16, count as i8));
+            }
+        };
+
+        for square in board.get_full_bb() {
+            let Some(piece) = board.get(square) else {
+                continue;
+            };
+
+            if piece.kind != PieceKind::King {
+                add(MATERIAL + piece.kind as usize, 1, piece.color);
+            }
+            let index = match piece.color {
+                Color::White => square.raw_index() ^ 56,
+                Color::Black => square.raw_index(),
+            } as usize;
+            add(PSQT + piece.kind as usize * 64 + index, 1, piece.color);
+        }
+
+        let attacks = AttackMap::new(board);
+        for color in [Color::White, Color::Black] {
+            let pawns = PawnFeatures::new(board, color);
+            add(DOUBLED, pawns.doubled.count() as i32, color);
+            add(ISOLATED, pawns.isolated.count() as i32, color);
+            add(BACKWARD, pawns.backward.count() as i32, color);
+            for square in pawns.passed {
+                let rank = match color {
+                    Color::White => square.rank(),
+                    Color::Black => 7 - square.rank(),
+                };
+                add(PASSED + rank as usize, 1, color);
+            }
+
+            let king = KingFeatures::new(board, &attacks, color);
+            add(SHIELD_ADVANCED, king.shield_advanced as i32, color);
+            add(SHIELD_MISSING, king.shield_missing as i32, color);
+            add(SEMI_OPEN_FILE, king.semi_open_files as i32, color);
+            add(OPEN_FILE, king.open_files as i32, color);
+            if king.attackers >= 2 {
+                let units = (king.attack_units as usize).min(king::KING_ATTACK.len() - 1);
+                add(KING_ATTACK + units, 1, color);
+            }
+        }
+
+        Self {
+            phase: psqt::phase(board),
+            terms,
+        }
+    }
+
+    /// Score with the given weights, from white's point of view.
+    pub fn evaluate(&self, weights: &Weights) -> i32 {
+        self.terms
+            .iter()
+            .map(|&(index, count)| weights.0[index as usize] * count as i32)
+            .fold(Score::ZERO, Score::plus)
+            .taper(self.phase)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sealion_board::Position;
+
+    use super::*;
+
+    #[test]
+    fn matches_evaluate() {
+        let weights = Weights::current();
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r5k1/5ppp/8/8/6q1/5n2/5P1P/6K1 b - - 0 1",
+            "4k3/p5p1/8/1P5P/8/6p1/3P4/4K3 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let score = Trace::new(&position.board).evaluate(&weights);
+            let expected = match position.active_color {
+                Color::White => crate::evaluate(&position),
+                Color::Black => -crate::evaluate(&position),
+            };
+            assert_eq!(score, expected, "{fen}");
+        }
+
+        assert!(Trace::new(&Position::starting().board)
+            .terms
+            .iter()
+            .all(|&(index, _)| (index as usize) < COUNT));
+    }
+
+    #[test]
+    fn printing() {
+        let printed = Weights::current().to_string();
+
+        assert!(printed.contains("Pawn   => 100,\nKnight => 300,"));
+        assert!(printed.contains(&format!(
+            "pub const DOUBLED: Score = Score::new({}, {});",
+            pawns::DOUBLED.mg,
+            pawns::DOUBLED.eg
+        )));
+        // the pawn's middlegame table, 7th rank
+        assert!(printed.contains("        50,  50,  50,  50,  50,  50,  50,  50,"));
+        assert_eq!(printed.matches("Score::new").count(), 3 + 8 + 4 + 16);
+    }
+}
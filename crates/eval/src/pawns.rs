@@ -0,0 +1,214 @@
+//! Pawn structure: doubled, isolated, backward and passed pawns.
+//!
+//! The features are sets of pawns, found for all pawns of a side at once by shifting and filling
+//! bitboards, and are public so a tuner can count them to fit the weights below.
+
+use sealion_board::psqt::Score;
+use sealion_board::{BitBoard, Board, Color, Piece, PieceKind};
+
+/// Per pawn with another of its own ahead of it on the file.
+pub const DOUBLED: Score = Score::new(-10, -20);
+/// Per pawn without any of its own on the neighbouring files.
+pub const ISOLATED: Score = Score::new(-12, -16);
+/// Per pawn that can't be defended by its neighbours and can't safely advance.
+pub const BACKWARD: Score = Score::new(-8, -10);
+/// Per passed pawn by rank, counted from its own side.
+pub static PASSED: [Score; 8] = [
+    Score::ZERO,
+    Score::new(5, 10),
+    Score::new(5, 15),
+    Score::new(10, 25),
+    Score::new(20, 45),
+    Score::new(35, 75),
+    Score::new(60, 120),
+    Score::ZERO,
+];
+
+/// The pawns of one side, by weakness or strength. A pawn can be in more than one set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnFeatures {
+    pub color: Color,
+    /// Pawns with another of their own ahead on the file, all but the front one of a file.
+    pub doubled: BitBoard,
+    /// Pawns without any of their own on the neighbouring files.
+    pub isolated: BitBoard,
+    /// Pawns whose neighbours are all ahead of them and whose stop square an enemy pawn attacks.
+    pub backward: BitBoard,
+    /// Pawns no enemy pawn can stop or take on their way to promotion, and not behind their own.
+    pub passed: BitBoard,
+}
+
+impl PawnFeatures {
+    pub fn new(board: &Board, color: Color) -> Self {
+        let own = board.get_piece_bb(Piece {
+            color,
+            kind: PieceKind::Pawn,
+        });
+        let enemy = board.get_piece_bb(Piece {
+            color: !color,
+            kind: PieceKind::Pawn,
+        });
+
+        let doubled = own & behind(own, color);
+
+        let files = own.fill_files();
+        let isolated = own & !(files.shift_east() | files.shift_west());
+
+        // stop squares no own pawn can ever defend, attacked by an enemy pawn
+        let defensible = fill_forward(attacks(own, color), color);
+        let stops = forward(own, color) & attacks(enemy, !color) & !defensible;
+        let backward = forward(stops, !color) & !isolated;
+
+        // ahead of an enemy pawn is behind it from our side
+        let enemy_front = behind(enemy, color);
+        let stoppers = enemy_front | enemy_front.shift_east() | enemy_front.shift_west();
+        let passed = own & !stoppers & !doubled;
+
+        Self {
+            color,
+            doubled,
+            isolated,
+            backward,
+            passed,
+        }
+    }
+
+    /// Score of the features, from white's point of view.
+    pub fn score(&self) -> Score {
+        let passed = self
+            .passed
+            .iter()
+            .map(|square| match self.color {
+                Color::White => PASSED[square.rank() as usize],
+                Color::Black => PASSED[7 - square.rank() as usize],
+            })
+            .fold(Score::ZERO, Score::plus);
+
+        let score = DOUBLED * self.doubled.count() as i32
+            + ISOLATED * self.isolated.count() as i32
+            + BACKWARD * self.backward.count() as i32
+            + passed;
+
+        match self.color {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+/// Pawn structure score of both sides, from white's point of view.
+pub fn evaluate_pawns(board: &Board) -> Score {
+    PawnFeatures::new(board, Color::White).score() + PawnFeatures::new(board, Color::Black).score()
+}
+
+/// One rank towards the side of `color`'s promotion.
+#[inline]
+fn forward(bb: BitBoard, color: Color) -> BitBoard {
+    match color {
+        Color::White => bb.shift_north(),
+        Color::Black => bb.shift_south(),
+    }
+}
+
+#[inline]
+fn fill_forward(bb: BitBoard, color: Color) -> BitBoard {
+    match color {
+        Color::White => bb.fill_north(),
+        Color::Black => bb.fill_south(),
+    }
+}
+
+/// Squares behind a set square on its file, as seen from `color`'s side.
+#[inline]
+fn behind(bb: BitBoard, color: Color) -> BitBoard {
+    fill_forward(forward(bb, !color), !color)
+}
+
+/// Squares attacked by pawns of `color` on `bb`.
+#[inline]
+fn attacks(bb: BitBoard, color: Color) -> BitBoard {
+    let ahead = forward(bb, color);
+    ahead.shift_east() | ahead.shift_west()
+}
+
+#[cfg(test)]
+mod test {
+    use sealion_board::Square;
+
+    use super::*;
+
+    fn features(placement: &str, color: Color) -> PawnFeatures {
+        PawnFeatures::new(&Board::from_fen_placement(placement).unwrap(), color)
+    }
+
+    fn squares(bb: BitBoard) -> Vec<String> {
+        let mut squares = bb
+            .iter()
+            .map(|square| square.to_string())
+            .collect::<Vec<_>>();
+        squares.sort();
+        squares
+    }
+
+    #[test]
+    fn doubled_and_isolated() {
+        // doubled on the c-file, the a-pawn and the h-pawns isolated
+        let white = features("4k3/8/8/8/2P4P/2P5/P1PP3P/4K3", Color::White);
+        assert_eq!(squares(white.doubled), ["c2", "c3", "h2"]);
+        assert_eq!(squares(white.isolated), ["a2", "h2", "h4"]);
+
+        let black = features("4k3/p1pp3p/2p5/2p4p/8/8/8/4K3", Color::Black);
+        assert_eq!(squares(black.doubled), ["c6", "c7", "h7"]);
+        assert_eq!(squares(black.isolated), ["a7", "h5", "h7"]);
+    }
+
+    #[test]
+    fn backward() {
+        // the d-pawn lags behind the c- and e-pawns and can't go to d3, attacked by the e-pawn
+        let white = features("4k3/8/8/8/2P1p3/4P3/3P4/4K3", Color::White);
+        assert_eq!(squares(white.backward), ["d2"]);
+        let black = features("4k3/3p4/4p3/2p1P3/8/8/8/4K3", Color::Black);
+        assert_eq!(squares(black.backward), ["d7"]);
+
+        // with the stop square safe, or a neighbour level to defend it, it's fine
+        assert!(features("4k3/8/8/8/2P5/4P3/3P4/4K3", Color::White)
+            .backward
+            .is_empty());
+        assert!(features("4k3/8/8/8/4p3/4P3/2PP4/4K3", Color::White)
+            .backward
+            .is_empty());
+    }
+
+    #[test]
+    fn passed() {
+        let white = features("4k3/p5p1/8/1P5P/8/6p1/3P4/4K3", Color::White);
+        // a7 stops b5 and g7 h5, d2 is free
+        assert_eq!(squares(white.passed), ["d2"]);
+        let black = features("4k3/p5p1/8/1P5P/8/6p1/3P4/4K3", Color::Black);
+        assert_eq!(squares(black.passed), ["g3"]);
+
+        // only the front one of doubled pawns
+        let doubled = features("4k3/8/8/3P4/8/3P4/8/4K3", Color::White);
+        assert_eq!(squares(doubled.passed), ["d5"]);
+
+        let seventh = Board::from_fen_placement("4k3/6P1/8/8/8/8/8/4K3").unwrap();
+        let second = Board::from_fen_placement("4k3/8/8/8/8/8/6P1/4K3").unwrap();
+        assert!(evaluate_pawns(&seventh).eg > evaluate_pawns(&second).eg);
+        assert_eq!(
+            PawnFeatures::new(&seventh, Color::White).passed,
+            BitBoard::from_square(Square::at(6, 6).unwrap())
+        );
+    }
+
+    #[test]
+    fn symmetry() {
+        assert_eq!(evaluate_pawns(&Board::starting_position()), Score::ZERO);
+
+        let white = Board::from_fen_placement("4k3/p7/8/1P5P/2P5/6p1/2PP4/4K3").unwrap();
+        let black = Board::from_fen_placement("4k3/2pp4/6P1/2p5/1p5p/8/P7/4K3").unwrap();
+        let white = evaluate_pawns(&white);
+        let black = evaluate_pawns(&black);
+        assert_ne!(white, Score::ZERO);
+        assert_eq!(white, -black);
+    }
+}
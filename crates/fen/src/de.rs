@@ -4,40 +4,17 @@
 
 use std::str::FromStr;
 
-use nom::bytes::complete::{is_not, take_till};
+use nom::bytes::complete::is_not;
 use nom::character::complete::{digit1, one_of, space0, space1};
-use nom::character::{is_digit, is_space};
-use nom::combinator::map_res;
-use nom::multi::{many0, many1};
+use nom::combinator::{map_opt, map_res};
+use nom::multi::many1;
 use nom::sequence::Tuple;
 use nom::IResult;
 
-use sealion_board::{Board, CastlingRights, Color, Piece, Position, Square};
+use sealion_board::{Board, CastlingRights, Color, Piece, PieceKind, Position, Square};
 
-fn parse_board(mut input: &str) -> IResult<&str, Board> {
-    let mut board = Board::default();
-    let mut square = Square::at(7, 0).unwrap();
-
-    for _ in 0..8 {
-        let (new_input, rank) = take_till(|c| c == '/' || is_space(c as u8))(input)?;
-        let (new_input, _) = many0(one_of("/"))(new_input)?;
-
-        for char in rank.chars() {
-            // FIXME: add some checks to increment square index
-            if is_digit(char as u8) {
-                *square.raw_index_mut() += (char as u8).saturating_sub(b'0');
-                continue;
-            }
-
-            board.set(square, Piece::from_char(char));
-            *square.raw_index_mut() += 1;
-        }
-
-        input = new_input;
-        *square.raw_index_mut() = square.raw_index().saturating_sub(16);
-    }
-
-    Ok((input, board))
+fn parse_board(input: &str) -> IResult<&str, Board> {
+    map_opt(is_not(" \t\r\n"), Board::from_fen_placement)(input)
 }
 
 fn parse_active_color(input: &str) -> IResult<&str, Color> {
@@ -52,24 +29,59 @@ fn parse_active_color(input: &str) -> IResult<&str, Color> {
     Ok((input, active_color))
 }
 
-fn parse_castling_rights(input: &str) -> IResult<&str, CastlingRights> {
-    let (input, castle_str) = many1(one_of("KQkq-"))(input)?;
+fn parse_castling_rights(input: &str) -> IResult<&str, Vec<char>> {
+    many1(one_of("KQkqABCDEFGHabcdefgh-"))(input)
+}
+
+/// Resolve the castling field against the board.
+///
+/// `KQkq` stand for the outermost rook on that side of the king (X-FEN), file letters for the rook
+/// on that file (Shredder-FEN), so Chess960 positions can be read in either notation.
+fn resolve_castling(board: &Board, castle_chars: &[char]) -> (CastlingRights, [[u8; 2]; 2]) {
     let mut castling_rights = CastlingRights::empty();
+    let mut castling_rooks = Position::STANDARD_CASTLING_ROOKS;
+
+    for &c in castle_chars {
+        let color = match c {
+            'A'..='Z' => Color::White,
+            'a'..='z' => Color::Black,
+            _ => continue,
+        };
+        let rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+
+        let king_file = board
+            .get_piece_bb(Piece {
+                color,
+                kind: PieceKind::King,
+            })
+            .set_iter()
+            .find(|square| square.rank() == rank)
+            .map_or(4, |square| square.file());
+        let is_rook = |file: &u8| {
+            board.get(Square::at(rank, *file).unwrap())
+                == Some(Piece {
+                    color,
+                    kind: PieceKind::Rook,
+                })
+        };
+
+        let (kingside, rook_file) = match c.to_ascii_uppercase() {
+            'K' => (true, (king_file + 1..8).rev().find(is_rook).unwrap_or(7)),
+            'Q' => (false, (0..king_file).find(is_rook).unwrap_or(0)),
+            file => {
+                let file = file as u8 - b'A';
+                (file > king_file, file)
+            }
+        };
 
-    if castle_str.contains(&'K') {
-        castling_rights |= CastlingRights::WHITE_OO;
-    }
-    if castle_str.contains(&'Q') {
-        castling_rights |= CastlingRights::WHITE_OOO;
-    }
-    if castle_str.contains(&'k') {
-        castling_rights |= CastlingRights::BLACK_OO;
-    }
-    if castle_str.contains(&'q') {
-        castling_rights |= CastlingRights::BLACK_OOO;
+        castling_rights |= CastlingRights::side(color, kingside);
+        castling_rooks[color as usize][!kingside as usize] = rook_file;
     }
 
-    Ok((input, castling_rights))
+    (castling_rights, castling_rooks)
 }
 
 fn parse_ep_target(input: &str) -> IResult<&str, Option<Square>> {
@@ -88,6 +100,10 @@ fn parse_u8(input: &str) -> IResult<&str, u8> {
     map_res(digit1, str::parse)(input)
 }
 
+fn parse_u16(input: &str) -> IResult<&str, u16> {
+    map_res(digit1, str::parse)(input)
+}
+
 /// Parse a chessboard state from the provided FEN string.
 pub fn parse(input: &str) -> IResult<&str, Position> {
     let (
@@ -118,19 +134,23 @@ pub fn parse(input: &str) -> IResult<&str, Position> {
         space1,
         parse_u8,
         space1,
-        parse_u8,
+        parse_u16,
     )
         .parse(input)?;
 
+    let (castling, castling_rooks) = resolve_castling(&board, &castling);
+
     Ok((
         input,
         Position {
             board,
             active_color,
             castling,
+            castling_rooks,
             ep_target,
             halfmove_clock,
             fullmove_counter,
+            history: Vec::new(),
         },
     ))
 }
@@ -151,10 +171,52 @@ mod test {
                 board: Board::starting_position(),
                 active_color: Color::White,
                 castling: CastlingRights::all(),
+                castling_rooks: Position::STANDARD_CASTLING_ROOKS,
                 ep_target: None,
                 halfmove_clock: 0,
                 fullmove_counter: 1,
+                history: Vec::new(),
             }
         )
     }
+
+    #[test]
+    fn round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+            "1rb1kb1r/p1p1P1pp/1q1p1p2/1p1nN1n1/2BP1B1N/1Q2p3/PPP1P1PP/R4RK1 w Qk e6 0 1",
+            "8/8/8/8/8/8/8/k6K b - - 49 120",
+            "4k3/8/8/8/8/8/8/4K2R b - - 0 300",
+            // Chess960, with an inner rook that needs its file spelled out
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9",
+            "rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1",
+        ] {
+            let position = parse(fen).unwrap().1;
+
+            assert_eq!(position.to_fen(), fen);
+            assert_eq!(parse(&position.to_fen()).unwrap().1, position);
+        }
+    }
+
+    #[test]
+    fn chess960_castling() {
+        let shredder = parse("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+            .unwrap()
+            .1;
+        let x_fen = parse("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9")
+            .unwrap()
+            .1;
+
+        assert_eq!(shredder, x_fen);
+        assert_eq!(shredder.castling, CastlingRights::all());
+        assert_eq!(shredder.castling_rooks, [[7, 5], [7, 5]]);
+
+        let inner = parse("rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1").unwrap().1;
+        assert_eq!(
+            inner.castling,
+            CastlingRights::WHITE_OOO | CastlingRights::BLACK_OOO
+        );
+        assert_eq!(inner.castling_rooks, [[7, 1], [7, 1]]);
+    }
 }
@@ -9,3 +9,48 @@ pub mod de;
 pub fn from_str(s: &str) -> Result<Position, nom::Err<nom::error::Error<&str>>> {
     de::parse(s).map(|r| r.1)
 }
+
+/// Rewrite a FEN string so that equivalent positions give the same string.
+///
+/// An en passant target nobody can capture on is dropped and the move clocks are reset, see
+/// [`Position::equivalent_to`].
+pub fn canonicalize(s: &str) -> Result<String, nom::Err<nom::error::Error<&str>>> {
+    let mut position = from_str(s)?;
+
+    position.ep_target = position.capturable_ep_target();
+    position.halfmove_clock = 0;
+    position.fullmove_counter = 1;
+
+    Ok(position.to_fen())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canonical_fens() {
+        for (fen, canonical) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+                "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            ),
+            (
+                "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+                "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 1",
+            ),
+            (
+                "8/8/8/8/8/8/8/k6K b - - 49 120",
+                "8/8/8/8/8/8/8/k6K b - - 0 1",
+            ),
+        ] {
+            assert_eq!(canonicalize(fen).unwrap(), canonical);
+        }
+
+        assert!(canonicalize("not a fen").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! Time controls and a chess clock to play them with.
+//!
+//! [`TimeControl`] reads and writes the PGN `TimeControl` tag, [`Clock`] keeps both players'
+//! remaining time through a game.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use sealion_board::Color;
+
+/// Time handed back on every move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bonus {
+    None,
+    /// Fischer increment, added after every move.
+    Increment(Duration),
+    /// Bronstein delay, the time used is given back up to the delay.
+    Bronstein(Duration),
+    /// Simple delay, the clock only starts running after the delay.
+    Delay(Duration),
+}
+
+/// One session of a time control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    /// Moves to play in this session, or `None` for the rest of the game (sudden death).
+    pub moves: Option<u32>,
+    /// Time added at the start of the session.
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+/// Parsed PGN `TimeControl` tag.
+///
+/// Sessions are separated by `:` and written as `moves/seconds`, `seconds` or `seconds+increment`.
+/// Standard PGN has no syntax for delays, so as an extension they are written as `300d5` (simple)
+/// and `300b5` (Bronstein).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeControl {
+    /// `?`
+    Unknown,
+    /// `-`
+    Untimed,
+    /// `*seconds`, a sandclock where the time used by one side is given to the other.
+    Sandclock(Duration),
+    Periods(Vec<Period>),
+}
+
+impl FromStr for TimeControl {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seconds = |s: &str| s.parse::<u64>().map(Duration::from_secs).map_err(|_| ());
+
+        match s {
+            "?" => return Ok(Self::Unknown),
+            "-" => return Ok(Self::Untimed),
+            _ => {}
+        }
+
+        if let Some(time) = s.strip_prefix('*') {
+            return Ok(Self::Sandclock(seconds(time)?));
+        }
+
+        let periods = s
+            .split(':')
+            .map(|period| {
+                let (moves, rest) = match period.split_once('/') {
+                    Some((moves, rest)) => (Some(moves.parse().map_err(|_| ())?), rest),
+                    None => (None, period),
+                };
+
+                let (time, bonus) = if let Some((time, inc)) = rest.split_once('+') {
+                    (time, Bonus::Increment(seconds(inc)?))
+                } else if let Some((time, delay)) = rest.split_once('d') {
+                    (time, Bonus::Delay(seconds(delay)?))
+                } else if let Some((time, delay)) = rest.split_once('b') {
+                    (time, Bonus::Bronstein(seconds(delay)?))
+                } else {
+                    (rest, Bonus::None)
+                };
+
+                Ok(Period {
+                    moves,
+                    time: seconds(time)?,
+                    bonus,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::Periods(periods))
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let periods = match self {
+            Self::Unknown => return write!(f, "?"),
+            Self::Untimed => return write!(f, "-"),
+            Self::Sandclock(time) => return write!(f, "*{}", time.as_secs()),
+            Self::Periods(periods) => periods,
+        };
+
+        for (i, period) in periods.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = period.moves {
+                write!(f, "{moves}/")?;
+            }
+
+            write!(f, "{}", period.time.as_secs())?;
+
+            match period.bonus {
+                Bonus::None => {}
+                Bonus::Increment(inc) => write!(f, "+{}", inc.as_secs())?,
+                Bonus::Delay(delay) => write!(f, "d{}", delay.as_secs())?,
+                Bonus::Bronstein(delay) => write!(f, "b{}", delay.as_secs())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A player ran out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flagged(pub Color);
+
+/// Chess clock for both players, driven by the time each move took.
+///
+/// The last session repeats once all of them are played, so `40/5400` is 90 minutes for every
+/// 40 moves.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    periods: Vec<Period>,
+    sides: [Side; 2],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Side {
+    remaining: Duration,
+    period: usize,
+    moves: u32,
+}
+
+impl Clock {
+    /// Start a clock for the given sessions, which can't be empty.
+    pub fn new(periods: Vec<Period>) -> Self {
+        let side = Side {
+            remaining: periods[0].time,
+            period: 0,
+            moves: 0,
+        };
+
+        Self {
+            periods,
+            sides: [side; 2],
+        }
+    }
+
+    /// Time left for a player.
+    #[inline]
+    pub fn remaining(&self, color: Color) -> Duration {
+        self.sides[color as usize].remaining
+    }
+
+    /// The session a player is currently in.
+    #[inline]
+    pub fn period(&self, color: Color) -> &Period {
+        &self.periods[self.sides[color as usize].period]
+    }
+
+    /// Moves a player still has to make before the next time control, if there is one.
+    #[inline]
+    pub fn moves_to_go(&self, color: Color) -> Option<u32> {
+        let side = &self.sides[color as usize];
+        self.period(color).moves.map(|moves| moves - side.moves)
+    }
+
+    /// Charge a completed move that took `elapsed` to `color`, returning the time left.
+    pub fn punch(&mut self, color: Color, elapsed: Duration) -> Result<Duration, Flagged> {
+        let period = *self.period(color);
+        let side = &mut self.sides[color as usize];
+
+        let charged = match period.bonus {
+            Bonus::Delay(delay) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        };
+
+        if charged > side.remaining {
+            side.remaining = Duration::ZERO;
+            return Err(Flagged(color));
+        }
+
+        side.remaining -= charged;
+        side.remaining += match period.bonus {
+            Bonus::Increment(inc) => inc,
+            Bonus::Bronstein(delay) => elapsed.min(delay),
+            Bonus::None | Bonus::Delay(_) => Duration::ZERO,
+        };
+
+        side.moves += 1;
+
+        if period.moves == Some(side.moves) {
+            side.period = (side.period + 1).min(self.periods.len() - 1);
+            side.moves = 0;
+            side.remaining += self.periods[side.period].time;
+        }
+
+        Ok(side.remaining)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn clock(tag: &str) -> Clock {
+        match tag.parse() {
+            Ok(TimeControl::Periods(periods)) => Clock::new(periods),
+            other => panic!("{tag}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_format() {
+        for tag in [
+            "?",
+            "-",
+            "*180",
+            "300",
+            "300+2",
+            "40/9000:300",
+            "40/5400+30:900+30",
+            "300d5",
+        ] {
+            let control = tag.parse::<TimeControl>().unwrap();
+            assert_eq!(control.to_string(), tag);
+        }
+
+        assert_eq!(
+            "40/9000:300".parse(),
+            Ok(TimeControl::Periods(vec![
+                Period {
+                    moves: Some(40),
+                    time: secs(9000),
+                    bonus: Bonus::None,
+                },
+                Period {
+                    moves: None,
+                    time: secs(300),
+                    bonus: Bonus::None,
+                },
+            ]))
+        );
+
+        for tag in ["", "40/", "abc", "300+", "*x"] {
+            assert_eq!(tag.parse::<TimeControl>(), Err(()), "{tag}");
+        }
+    }
+
+    #[test]
+    fn fischer() {
+        let mut clock = clock("60+2");
+
+        assert_eq!(clock.punch(Color::White, secs(10)), Ok(secs(52)));
+        assert_eq!(clock.punch(Color::Black, secs(1)), Ok(secs(61)));
+        assert_eq!(
+            clock.punch(Color::White, secs(53)),
+            Err(Flagged(Color::White))
+        );
+        assert_eq!(clock.remaining(Color::White), Duration::ZERO);
+    }
+
+    #[test]
+    fn delays() {
+        let mut bronstein = clock("60b5");
+        assert_eq!(bronstein.punch(Color::White, secs(3)), Ok(secs(60)));
+        assert_eq!(bronstein.punch(Color::White, secs(10)), Ok(secs(55)));
+
+        let mut simple = clock("60d5");
+        assert_eq!(simple.punch(Color::White, secs(3)), Ok(secs(60)));
+        assert_eq!(simple.punch(Color::White, secs(10)), Ok(secs(55)));
+        assert_eq!(simple.punch(Color::White, secs(60)), Ok(secs(0)));
+    }
+
+    #[test]
+    fn sessions() {
+        let mut clock = clock("2/100:50");
+
+        assert_eq!(clock.moves_to_go(Color::White), Some(2));
+        assert_eq!(clock.punch(Color::White, secs(30)), Ok(secs(70)));
+        assert_eq!(clock.moves_to_go(Color::White), Some(1));
+
+        // time control reached, the sudden death session is added
+        assert_eq!(clock.punch(Color::White, secs(30)), Ok(secs(90)));
+        assert_eq!(clock.moves_to_go(Color::White), None);
+        assert_eq!(clock.remaining(Color::Black), secs(100));
+
+        // the last session repeats
+        let mut clock = self::clock("1/10");
+        assert_eq!(clock.punch(Color::Black, secs(5)), Ok(secs(15)));
+        assert_eq!(clock.moves_to_go(Color::Black), Some(1));
+    }
+}
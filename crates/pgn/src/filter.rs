@@ -0,0 +1,189 @@
+//! Game filtering on tag pair values.
+
+use std::str::FromStr;
+
+use crate::RawGame;
+
+/// An inclusive range of ECO opening codes, such as `B20-B99`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcoRange {
+    pub from: String,
+    pub to: String,
+}
+
+impl EcoRange {
+    /// Check if an ECO code lies within this range.
+    #[inline]
+    pub fn contains(&self, eco: &str) -> bool {
+        self.from.as_str() <= eco && eco <= self.to.as_str()
+    }
+}
+
+impl FromStr for EcoRange {
+    type Err = ();
+
+    /// Parse a single code (`C42`) or a range (`B20-B99`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_code = |code: &str| {
+            code.len() == 3
+                && matches!(code.as_bytes()[0], b'A'..=b'E')
+                && code[1..].bytes().all(|c| c.is_ascii_digit())
+        };
+
+        let (from, to) = s.split_once('-').unwrap_or((s, s));
+
+        if !is_code(from) || !is_code(to) || from > to {
+            return Err(());
+        }
+
+        Ok(Self {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        })
+    }
+}
+
+/// Criteria a game has to meet. Unset criteria match every game.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Minimum rating of both players.
+    pub min_elo: Option<u16>,
+    /// Maximum rating of both players.
+    pub max_elo: Option<u16>,
+    /// Exact `TimeControl` tag value, e.g. `180+2`.
+    pub time_control: Option<String>,
+    /// Exact `Result` tag value, e.g. `1-0`.
+    pub result: Option<String>,
+    /// Range of `ECO` codes.
+    pub eco: Option<EcoRange>,
+    /// Case insensitive substring of either player's name.
+    pub player: Option<String>,
+}
+
+impl Filter {
+    /// Check if a game meets all criteria.
+    pub fn matches(&self, game: &RawGame) -> bool {
+        if self.min_elo.is_some() || self.max_elo.is_some() {
+            for tag in ["WhiteElo", "BlackElo"] {
+                let elo = match game.tag(tag).and_then(|elo| elo.parse::<u16>().ok()) {
+                    Some(elo) => elo,
+                    None => return false,
+                };
+
+                if self.min_elo.is_some_and(|min| elo < min)
+                    || self.max_elo.is_some_and(|max| elo > max)
+                {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(time_control) = &self.time_control {
+            if game.tag("TimeControl") != Some(time_control.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(result) = &self.result {
+            if game.tag("Result") != Some(result.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(eco) = &self.eco {
+            if !game.tag("ECO").is_some_and(|code| eco.contains(code)) {
+                return false;
+            }
+        }
+
+        if let Some(player) = &self.player {
+            let player = player.to_lowercase();
+            let plays = |tag| {
+                game.tag(tag)
+                    .is_some_and(|name| name.to_lowercase().contains(&player))
+            };
+
+            if !plays("White") && !plays("Black") {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn game(tags: &[(&str, &str)]) -> RawGame {
+        RawGame {
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            text: String::new(),
+        }
+    }
+
+    #[test]
+    fn eco_range() {
+        let range = EcoRange::from_str("B20-B99").unwrap();
+        assert!(range.contains("B20"));
+        assert!(range.contains("B54"));
+        assert!(!range.contains("C00"));
+        assert_eq!(
+            EcoRange::from_str("C42"),
+            Ok(EcoRange {
+                from: "C42".to_owned(),
+                to: "C42".to_owned()
+            })
+        );
+        assert!(EcoRange::from_str("F00").is_err());
+        assert!(EcoRange::from_str("B99-B20").is_err());
+    }
+
+    #[test]
+    fn filter() {
+        let g = game(&[
+            ("White", "Magnus Carlsen"),
+            ("Black", "Someone"),
+            ("WhiteElo", "2850"),
+            ("BlackElo", "2600"),
+            ("Result", "1-0"),
+            ("ECO", "B90"),
+            ("TimeControl", "180+2"),
+        ]);
+
+        assert!(Filter::default().matches(&g));
+
+        let filter = Filter {
+            min_elo: Some(2500),
+            max_elo: Some(2900),
+            time_control: Some("180+2".to_owned()),
+            result: Some("1-0".to_owned()),
+            eco: EcoRange::from_str("B20-B99").ok(),
+            player: Some("carlsen".to_owned()),
+        };
+        assert!(filter.matches(&g));
+
+        let filter = Filter {
+            min_elo: Some(2700),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&g));
+
+        let filter = Filter {
+            player: Some("nakamura".to_owned()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&g));
+
+        // missing rating tags never match a rating range
+        let filter = Filter {
+            max_elo: Some(3000),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&game(&[("White", "A")])));
+    }
+}
@@ -0,0 +1,413 @@
+//! Full game parsing, replaying the movetext on a [`Position`].
+//!
+//! Variations are skipped, only the main line is kept.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use sealion_board::{MoveExt, Position};
+use sealion_engine::san::{parse_san, SanError};
+use sealion_engine::state::PositionState;
+
+use crate::parse_tag;
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    /// `1-0`
+    WhiteWins,
+    /// `0-1`
+    BlackWins,
+    /// `1/2-1/2`
+    Draw,
+    /// `*`, still going or unknown.
+    Unknown,
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(Self::WhiteWins),
+            "0-1" => Ok(Self::BlackWins),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WhiteWins => write!(f, "1-0"),
+            Self::BlackWins => write!(f, "0-1"),
+            Self::Draw => write!(f, "1/2-1/2"),
+            Self::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+/// A main line move with its annotations.
+#[derive(Debug, Clone)]
+pub struct GameMove {
+    pub p_move: MoveExt,
+    /// The move as written in the movetext.
+    pub san: String,
+    /// Numeric annotation glyphs, e.g. `$1`.
+    pub nags: Vec<u8>,
+    /// Comments following the move.
+    pub comments: Vec<String>,
+}
+
+/// A parsed game.
+#[derive(Debug, Clone)]
+pub struct Game {
+    /// Tag pairs in the order they appear in.
+    pub tags: Vec<(String, String)>,
+    /// Position the game starts from, given by the `FEN` tag or the standard one.
+    pub start: Position,
+    /// Position after the last move, with the moves in its history so they can be unmade.
+    pub end: Position,
+    pub moves: Vec<GameMove>,
+    /// Comments before the first move.
+    pub comments: Vec<String>,
+    /// From the game termination marker, or the `Result` tag if there is none.
+    pub result: GameResult,
+}
+
+/// Why a game couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// The `FEN` tag isn't a valid position.
+    Fen(String),
+    /// A move couldn't be resolved, `ply` counts from 0 at the start of the game.
+    Move {
+        ply: usize,
+        san: String,
+        error: SanError,
+    },
+    /// Something in the movetext that isn't a move, comment or result.
+    Token(String),
+    /// A variation or comment is never closed.
+    Unterminated,
+    /// Parsing panicked, a bug rather than a bad game. Only [`crate::parse_parallel`] catches it.
+    Panicked(String),
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fen(fen) => write!(f, "invalid FEN tag `{fen}`"),
+            Self::Move { ply, san, error } => write!(f, "move {} `{san}`: {error}", ply / 2 + 1),
+            Self::Token(token) => write!(f, "unexpected `{token}`"),
+            Self::Unterminated => write!(f, "unterminated comment or variation"),
+            Self::Panicked(message) => write!(f, "parsing panicked: {message}"),
+        }
+    }
+}
+
+impl Game {
+    /// Parse a single game, tag section and movetext, e.g. [`crate::RawGame::text`].
+    pub fn parse(text: &str) -> Result<Self, GameError> {
+        let mut tags = vec![];
+        let mut movetext = text;
+
+        // the tag section ends with the first line that isn't a tag pair
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim();
+
+            if !trimmed.is_empty() && !trimmed.starts_with('[') {
+                break;
+            }
+            if let Ok((_, tag)) = parse_tag(trimmed) {
+                tags.push(tag);
+            }
+
+            movetext = &movetext[line.len()..];
+        }
+
+        let tag = |name: &str| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let start = match tag("FEN") {
+            Some(fen) => sealion_fen::from_str(fen)
+                .ok()
+                // a parsable FEN can still have no kings, or pawns on the back rank
+                .filter(|start| start.validate().is_ok())
+                .ok_or_else(|| GameError::Fen(fen.to_owned()))?,
+            None => Position::starting(),
+        };
+
+        let mut game = Game {
+            result: tag("Result")
+                .and_then(|result| result.parse().ok())
+                .unwrap_or(GameResult::Unknown),
+            tags,
+            end: start.clone(),
+            start,
+            moves: vec![],
+            comments: vec![],
+        };
+
+        for token in Tokens::new(movetext) {
+            match token? {
+                Token::Comment(comment) => {
+                    let comment = comment.trim().to_owned();
+
+                    match game.moves.last_mut() {
+                        Some(last) => last.comments.push(comment),
+                        None => game.comments.push(comment),
+                    }
+                }
+                Token::Nag(nag) => match game.moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => return Err(GameError::Token(format!("${nag}"))),
+                },
+                Token::Word(word) => {
+                    if let Ok(result) = word.parse() {
+                        game.result = result;
+                        break;
+                    }
+
+                    // move numbers, possibly glued to the move as in `1.e4`
+                    let san = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+                    if san.is_empty() || san == "e.p." {
+                        continue;
+                    }
+
+                    game.play(san)?;
+                }
+            }
+        }
+
+        Ok(game)
+    }
+
+    /// Value of the first tag pair with this name.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn play(&mut self, san: &str) -> Result<(), GameError> {
+        let state = PositionState::generate(&self.end);
+        let p_move = parse_san(&state, san).map_err(|error| GameError::Move {
+            ply: self.moves.len(),
+            san: san.to_owned(),
+            error,
+        })?;
+
+        self.end.make_move_ext(p_move);
+        self.moves.push(GameMove {
+            p_move,
+            san: san.to_owned(),
+            nags: vec![],
+            comments: vec![],
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Comment(&'a str),
+    Nag(u8),
+    Word(&'a str),
+}
+
+/// Main line movetext tokens, variations are skipped.
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(movetext: &'a str) -> Self {
+        Self { rest: movetext }
+    }
+
+    /// Split off everything up to the first `end`, dropping the `end` itself.
+    fn take_until(&mut self, end: char) -> Option<&'a str> {
+        let (taken, rest) = self.rest.split_once(end)?;
+        self.rest = rest;
+        Some(taken)
+    }
+
+    fn skip_variation(&mut self) -> Result<(), GameError> {
+        let mut depth = 1;
+
+        while depth > 0 {
+            let mut chars = self.rest.chars();
+            let c = chars.next().ok_or(GameError::Unterminated)?;
+            self.rest = chars.as_str();
+
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                '{' => {
+                    self.take_until('}').ok_or(GameError::Unterminated)?;
+                }
+                ';' => {
+                    self.take_until('\n');
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<Token<'a>, GameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // `%` at the start of a line escapes the whole line
+            while let Some(line) = self.rest.trim_start().strip_prefix('%') {
+                self.rest = line.split_once('\n').map_or("", |(_, rest)| rest);
+            }
+
+            self.rest = self.rest.trim_start();
+            let mut chars = self.rest.chars();
+            let c = chars.next()?;
+
+            match c {
+                '{' => {
+                    self.rest = chars.as_str();
+                    return Some(
+                        self.take_until('}')
+                            .map(Token::Comment)
+                            .ok_or(GameError::Unterminated),
+                    );
+                }
+                ';' => {
+                    self.rest = chars.as_str();
+                    let comment = self
+                        .take_until('\n')
+                        .unwrap_or_else(|| std::mem::take(&mut self.rest));
+                    return Some(Ok(Token::Comment(comment)));
+                }
+                '(' => {
+                    self.rest = chars.as_str();
+                    if let Err(err) = self.skip_variation() {
+                        return Some(Err(err));
+                    }
+                }
+                ')' | '}' => {
+                    self.rest = chars.as_str();
+                    return Some(Err(GameError::Token(c.to_string())));
+                }
+                '$' => {
+                    let digits = chars.as_str();
+                    let end = digits
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(digits.len());
+                    let (nag, rest) = digits.split_at(end);
+                    self.rest = rest;
+
+                    return Some(
+                        nag.parse()
+                            .map(Token::Nag)
+                            .map_err(|_| GameError::Token(format!("${nag}"))),
+                    );
+                }
+                _ => {
+                    let end = self
+                        .rest
+                        .find(|c: char| c.is_whitespace() || "{}();$".contains(c))
+                        .unwrap_or(self.rest.len());
+                    let (word, rest) = self.rest.split_at(end);
+                    self.rest = rest;
+
+                    return Some(Ok(Token::Word(word)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GAME: &str = r#"[Event "Test"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2.Nf3 $1 {develops} Nc6 (2... d6 {Philidor} (2... f5?))
+3. Bb5 a6 ; the Morphy defence
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7 1-0
+"#;
+
+    #[test]
+    fn parse_game() {
+        let game = Game::parse(GAME).unwrap();
+
+        assert_eq!(game.tag("Event"), Some("Test"));
+        assert_eq!(game.result, GameResult::WhiteWins);
+        assert_eq!(game.comments, ["Opening comment"]);
+        assert_eq!(game.moves.len(), 20);
+        assert_eq!(game.moves[2].san, "Nf3");
+        assert_eq!(game.moves[2].nags, [1]);
+        assert_eq!(game.moves[2].comments, ["develops"]);
+        assert_eq!(game.moves[5].comments, ["the Morphy defence"]);
+        assert_eq!(game.moves[19].p_move.to_move().to_string(), "b8d7");
+        assert_eq!(game.start, Position::starting());
+        assert_eq!(
+            game.end.to_fen(),
+            "r1bq1rk1/2pnbppp/p2p1n2/1p2p3/3PP3/1BP2N1P/PP3PP1/RNBQR1K1 w - - 1 11"
+        );
+        assert_eq!(game.end.history.len(), 20);
+    }
+
+    #[test]
+    fn fen_tag() {
+        let game = Game::parse(
+            "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n[SetUp \"1\"]\n\n1.e4 Kd7 2.e5 *",
+        )
+        .unwrap();
+
+        assert_eq!(game.result, GameResult::Unknown);
+        assert_eq!(game.start.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        assert_eq!(game.end.to_fen(), "8/3k4/8/4P3/8/8/8/4K3 b - - 0 2");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Game::parse("1. e4 e5 2. Ke3").unwrap_err(),
+            GameError::Move {
+                ply: 2,
+                san: "Ke3".to_owned(),
+                error: SanError::NoMatch,
+            }
+        );
+        assert_eq!(
+            Game::parse("1. e4 {unterminated").unwrap_err(),
+            GameError::Unterminated
+        );
+        assert_eq!(
+            Game::parse("1. e4 (1. d4").unwrap_err(),
+            GameError::Unterminated
+        );
+        assert!(matches!(
+            Game::parse("[FEN \"nope\"]\n\n1. e4"),
+            Err(GameError::Fen(_))
+        ));
+        assert_eq!(
+            Game::parse("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n1. e4 *").unwrap_err(),
+            GameError::Fen("8/8/8/8/8/8/8/8 w - - 0 1".to_owned())
+        );
+
+        // no termination marker, the result comes from the tag
+        let game = Game::parse("[Result \"1/2-1/2\"]\n\n1. e4").unwrap();
+        assert_eq!(game.result, GameResult::Draw);
+    }
+}
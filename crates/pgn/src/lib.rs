@@ -0,0 +1,15 @@
+//! PGN (Portable Game Notation) utilities.
+//!
+//! <https://www.chessprogramming.org/Portable_Game_Notation>
+
+pub mod clock;
+pub mod filter;
+pub mod game;
+pub mod parallel;
+pub mod reader;
+
+pub use clock::*;
+pub use filter::*;
+pub use game::*;
+pub use parallel::*;
+pub use reader::*;
@@ -0,0 +1,240 @@
+//! Parsing the games of a large PGN file on several threads.
+//!
+//! One thread splits the file into games, as [`GameReader`] does, and hands them out in batches
+//! to worker threads that resolve the moves. Games still come out in file order.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+
+use crate::{Game, GameError, GameReader, RawGame};
+
+/// Games handed to a worker at a time, parsing a single game is too quick to be worth a message.
+const BATCH_SIZE: usize = 64;
+
+/// A game as read and as parsed.
+pub type ParsedGame = (RawGame, Result<Game, GameError>);
+
+type Batch<T> = (usize, Vec<T>);
+
+/// Iterator over the parsed games of a PGN stream, see [`parse_parallel`].
+#[derive(Debug)]
+pub struct ParallelGames {
+    results: Receiver<Batch<io::Result<ParsedGame>>>,
+    /// Batches that arrived before the ones preceding them.
+    pending: BTreeMap<usize, Vec<io::Result<ParsedGame>>>,
+    /// Games of the current batch, reversed to pop from the back.
+    current: Vec<io::Result<ParsedGame>>,
+    next_batch: usize,
+}
+
+/// Parse all games of `reader` on `threads` worker threads, besides the one reading.
+///
+/// Reading stops at the first I/O error, which is the last item. A game whose parsing panics comes
+/// out as [`GameError::Panicked`], the others go on. Dropping the iterator early stops the threads
+/// once they notice.
+pub fn parse_parallel<R>(reader: GameReader<R>, threads: usize) -> ParallelGames
+where
+    R: BufRead + Send + 'static,
+{
+    let threads = threads.max(1);
+    let (jobs_tx, jobs_rx) = mpsc::sync_channel::<Batch<io::Result<RawGame>>>(threads * 2);
+    let (results_tx, results_rx) = mpsc::sync_channel(threads * 2);
+    let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+
+    std::thread::spawn(move || read_batches(reader, jobs_tx));
+
+    for _ in 0..threads {
+        let jobs_rx = jobs_rx.clone();
+        let results_tx = results_tx.clone();
+
+        std::thread::spawn(move || loop {
+            // the lock is only held while waiting for a job, not while parsing it
+            let job = jobs_rx.lock().unwrap().recv();
+            let Ok((index, games)) = job else {
+                break;
+            };
+
+            let parsed = games
+                .into_iter()
+                .map(|raw| {
+                    raw.map(|raw| {
+                        let game = catch(|| Game::parse(&raw.text));
+                        (raw, game)
+                    })
+                })
+                .collect();
+
+            if results_tx.send((index, parsed)).is_err() {
+                break;
+            }
+        });
+    }
+
+    ParallelGames {
+        results: results_rx,
+        pending: BTreeMap::new(),
+        current: vec![],
+        next_batch: 0,
+    }
+}
+
+/// The result of `parse`, or its panic as an error so one bad game doesn't lose a whole batch.
+fn catch(parse: impl FnOnce() -> Result<Game, GameError>) -> Result<Game, GameError> {
+    panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(GameError::Panicked(message))
+    })
+}
+
+fn read_batches<R: BufRead>(reader: GameReader<R>, jobs: SyncSender<Batch<io::Result<RawGame>>>) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut index = 0;
+
+    for game in reader {
+        let failed = game.is_err();
+        batch.push(game);
+
+        if batch.len() == BATCH_SIZE || failed {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+            if jobs.send((index, full)).is_err() {
+                return;
+            }
+            index += 1;
+        }
+
+        if failed {
+            return;
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = jobs.send((index, batch));
+    }
+}
+
+impl Iterator for ParallelGames {
+    type Item = io::Result<ParsedGame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(game) = self.current.pop() {
+                return Some(game);
+            }
+
+            let batch = match self.pending.remove(&self.next_batch) {
+                Some(batch) => batch,
+                None => match self.results.recv() {
+                    Ok((index, batch)) if index == self.next_batch => batch,
+                    Ok((index, batch)) => {
+                        self.pending.insert(index, batch);
+                        continue;
+                    }
+                    // every thread is done, batches still pending mean one died without sending
+                    // its own, say so rather than end quietly
+                    Err(_) => {
+                        let &index = self.pending.keys().next()?;
+                        let lost = (index - self.next_batch) * BATCH_SIZE;
+                        self.next_batch = index;
+                        return Some(Err(io::Error::other(format!(
+                            "up to {lost} games were lost to a failed worker"
+                        ))));
+                    }
+                },
+            };
+
+            self.current = batch;
+            self.current.reverse();
+            self.next_batch += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn same_as_serial() {
+        let mut pgn = String::new();
+        for i in 0..300 {
+            pgn += &format!("[Event \"{i}\"]\n\n");
+            pgn += match i % 3 {
+                0 => "1. e4 e5 2. Nf3 Nc6 1-0\n\n",
+                1 => "1. d4 {a comment} d5 2. c4 *\n\n",
+                _ => "1. e4 e4 0-1\n\n",
+            };
+        }
+
+        let serial = GameReader::new(Cursor::new(pgn.clone()))
+            .map(|raw| {
+                let raw = raw.unwrap();
+                let game = Game::parse(&raw.text);
+                (raw, game)
+            })
+            .collect::<Vec<_>>();
+
+        for threads in [1, 4] {
+            let parallel = parse_parallel(GameReader::new(Cursor::new(pgn.clone())), threads)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>();
+
+            assert_eq!(parallel.len(), 300);
+
+            for ((raw, game), (serial_raw, serial_game)) in parallel.iter().zip(&serial) {
+                assert_eq!(raw, serial_raw);
+
+                match (game, serial_game) {
+                    (Ok(game), Ok(serial_game)) => {
+                        assert_eq!(game.end, serial_game.end);
+                        assert_eq!(game.result, serial_game.result);
+                    }
+                    (Err(err), Err(serial_err)) => assert_eq!(err, serial_err),
+                    _ => panic!("{raw:?} parsed differently"),
+                }
+            }
+        }
+
+        assert_eq!(
+            parse_parallel(GameReader::new(Cursor::new(String::new())), 2).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn panics() {
+        assert_eq!(
+            catch(|| panic!("index out of bounds")).unwrap_err(),
+            GameError::Panicked("index out of bounds".to_owned())
+        );
+        assert_eq!(catch(|| Game::parse("1. e4 e5 *")).unwrap().moves.len(), 2);
+
+        // the batch of a worker that died without sending it
+        let (results_tx, results) = mpsc::sync_channel(2);
+        results_tx
+            .send((1, vec![Err(io::Error::other("last"))]))
+            .unwrap();
+        drop(results_tx);
+        let mut games = ParallelGames {
+            results,
+            pending: BTreeMap::new(),
+            current: vec![],
+            next_batch: 0,
+        };
+        let lost = games.next().unwrap().unwrap_err();
+        assert_eq!(
+            lost.to_string(),
+            format!("up to {BATCH_SIZE} games were lost to a failed worker")
+        );
+        assert_eq!(games.next().unwrap().unwrap_err().to_string(), "last");
+        assert!(games.next().is_none());
+    }
+}
@@ -0,0 +1,233 @@
+//! Streaming multi-game PGN reader.
+//!
+//! Splits a PGN file on game boundaries without loading it into memory as a whole. Only the tag
+//! pairs are parsed, the movetext is kept verbatim so games can be written back out unchanged.
+
+use std::io::{self, BufRead};
+
+use nom::branch::alt;
+use nom::bytes::complete::{escaped_transform, is_not, take_while1};
+use nom::character::complete::{char, space0};
+use nom::combinator::{opt, value};
+use nom::sequence::{delimited, Tuple};
+use nom::IResult;
+
+/// A single game as it appears in a PGN file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawGame {
+    /// Tag pairs in the order they appear in.
+    pub tags: Vec<(String, String)>,
+    /// Full text of the game (tag section and movetext) as read.
+    pub text: String,
+}
+
+impl RawGame {
+    /// Get the value of the first tag pair with this name.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Iterator over all games in a PGN stream.
+#[derive(Debug)]
+pub struct GameReader<R> {
+    inner: R,
+    /// First line of the next game, read while looking for the end of the previous one.
+    pending: Option<String>,
+}
+
+impl<R: BufRead> GameReader<R> {
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: None,
+        }
+    }
+
+    fn read_game(&mut self) -> io::Result<Option<RawGame>> {
+        let mut game = RawGame::default();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+        // a blank line after the tags, the movetext may still be missing
+        let mut tags_ended = false;
+        let mut buffer = vec![];
+
+        if let Some(line) = self.pending.take() {
+            push_line(&mut game, &line, &mut in_movetext, &mut in_comment);
+        }
+
+        loop {
+            buffer.clear();
+            if self.inner.read_until(b'\n', &mut buffer)? == 0 {
+                break;
+            }
+            // old databases are often Latin-1, their names shouldn't stop the whole file
+            let line = String::from_utf8_lossy(&buffer).into_owned();
+
+            // a tag pair after the movetext, or after the tags of a game without any, starts the
+            // next game
+            if (in_movetext || tags_ended) && !in_comment && line.trim_start().starts_with('[') {
+                self.pending = Some(line);
+                break;
+            }
+            if !in_movetext && !game.text.is_empty() && line.trim().is_empty() {
+                tags_ended = true;
+            }
+
+            push_line(&mut game, &line, &mut in_movetext, &mut in_comment);
+        }
+
+        if game.text.trim().is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(game))
+    }
+}
+
+impl<R: BufRead> Iterator for GameReader<R> {
+    type Item = io::Result<RawGame>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_game().transpose()
+    }
+}
+
+fn push_line(game: &mut RawGame, line: &str, in_movetext: &mut bool, in_comment: &mut bool) {
+    let trimmed = line.trim();
+
+    // skip blank lines before the game begins
+    if game.text.is_empty() && trimmed.is_empty() {
+        return;
+    }
+
+    game.text.push_str(line);
+
+    if !*in_comment && trimmed.starts_with('[') {
+        if let Ok((_, tag)) = parse_tag(trimmed) {
+            game.tags.push(tag);
+        }
+        return;
+    }
+
+    if trimmed.is_empty() {
+        return;
+    }
+
+    *in_movetext = true;
+
+    for c in trimmed.chars() {
+        match c {
+            '{' => *in_comment = true,
+            '}' => *in_comment = false,
+            // rest of line comment
+            ';' if !*in_comment => break,
+            _ => {}
+        }
+    }
+}
+
+/// Parse a single `[Name "Value"]` tag pair.
+pub fn parse_tag(input: &str) -> IResult<&str, (String, String)> {
+    let tag_value = opt(escaped_transform(
+        is_not("\\\""),
+        '\\',
+        alt((value("\\", char('\\')), value("\"", char('"')))),
+    ));
+
+    let (input, (_, _, name, _, value, _, _)) = (
+        char('['),
+        space0,
+        take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+        space0,
+        delimited(char('"'), tag_value, char('"')),
+        space0,
+        char(']'),
+    )
+        .parse(input)?;
+
+    Ok((input, (name.to_owned(), value.unwrap_or_default())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GAMES: &str = r#"
+[Event "One"]
+[White "Alice"]
+[Black "Bob \"The Builder\""]
+
+1. e4 e5 {a comment
+[not a tag]} 2. Nf3 1-0
+
+[Event "Two"]
+[White "Carol"]
+
+1. d4 d5 0-1
+"#;
+
+    #[test]
+    fn tag() {
+        assert_eq!(
+            parse_tag(r#"[Black "Bob \"The Builder\""]"#).unwrap().1,
+            ("Black".to_owned(), "Bob \"The Builder\"".to_owned())
+        );
+        assert_eq!(
+            parse_tag(r#"[Site ""]"#).unwrap().1,
+            ("Site".to_owned(), String::new())
+        );
+        assert!(parse_tag("1. e4").is_err());
+    }
+
+    #[test]
+    fn split_games() {
+        let games = GameReader::new(GAMES.as_bytes())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("One"));
+        assert_eq!(games[0].tag("Black"), Some("Bob \"The Builder\""));
+        assert_eq!(games[0].tags.len(), 3);
+        assert!(games[0].text.contains("[not a tag]"));
+        assert_eq!(games[1].tag("White"), Some("Carol"));
+        assert!(games[1].text.ends_with("0-1\n"));
+    }
+
+    #[test]
+    fn missing_movetext() {
+        let pgn =
+            "[Event \"One\"]\n\n[Event \"Two\"]\n[Round \"2\"]\n\n1. e4 *\n\n[Event \"Three\"]\n";
+        let games = GameReader::new(pgn.as_bytes())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].tags, [("Event".to_owned(), "One".to_owned())]);
+        assert_eq!(games[0].text.trim(), "[Event \"One\"]");
+        assert_eq!(games[1].tag("Round"), Some("2"));
+        assert!(games[1].text.ends_with("1. e4 *\n\n"));
+        assert_eq!(games[2].tag("Event"), Some("Three"));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        // "Müller" in Latin-1
+        let mut pgn = b"[White \"M".to_vec();
+        pgn.push(0xfc);
+        pgn.extend_from_slice(b"ller\"]\n\n1. e4 1-0\n\n[White \"Smith\"]\n\n1. d4 0-1\n");
+        let games = GameReader::new(pgn.as_slice())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("White"), Some("M\u{fffd}ller"));
+        assert_eq!(games[1].tag("White"), Some("Smith"));
+    }
+}
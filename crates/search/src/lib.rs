@@ -0,0 +1,735 @@
+//! Alpha-beta search over the legal move generator.
+//!
+//! A negamax with iterative deepening: every iteration searches one ply deeper than the last and
+//! the last one to complete gives the result, so a search can be stopped at any time. Results are
+//! kept in a [`TranspositionTable`], which orders the moves of the next iteration and cuts off
+//! positions reached again. Moves are tried in the order of [`ordering`], and a null move prunes
+//! positions so good that even passing would fail high. At the horizon a quiescence search
+//! resolves captures first, so the evaluation isn't taken in the middle of an exchange. With a
+//! clock, a [`TimeManager`] decides when to stop.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sealion_board::{MoveExt, Piece, PieceKind, Position};
+use sealion_engine::movegen::{Generator, MoveList};
+use sealion_engine::state::PositionState;
+use sealion_eval::evaluate;
+
+mod ordering;
+pub mod time;
+pub mod tree;
+pub mod tt;
+
+use ordering::{is_quiet, mvv_lva, MoveOrdering};
+use time::{GoClocks, TimeManager, MOVE_OVERHEAD};
+use tt::{from_table, to_table, Bound, Entry, TranspositionTable, DEFAULT_HASH};
+
+/// Deepest a search goes, in plies from the root.
+pub const MAX_PLY: usize = 128;
+/// Score of being checkmated, a mate `n` plies away scores `MATE - n` for the winning side.
+pub const MATE: i32 = 30_000;
+/// Scores at least this far from zero are mates.
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+const INFINITY: i32 = MATE + 1;
+
+/// The stop flag is only read every so many nodes.
+const STOP_CHECK_INTERVAL: u64 = 1024;
+
+/// First iteration searched with a window around the score of the one before, the earlier ones
+/// are too quick to be worth it and their scores swing too much.
+const ASPIRATION_DEPTH: u8 = 4;
+/// Half the width of the first aspiration window, doubled every time the score falls outside.
+const ASPIRATION_WINDOW: i32 = 25;
+
+/// Shallowest depth to try a null move at.
+const NULL_MOVE_DEPTH: u8 = 3;
+
+/// When to stop searching, besides the stop flag. No limits searches until stopped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Deepest iteration, capped at [`MAX_PLY`].
+    pub depth: Option<u8>,
+    /// Nodes to search before stopping.
+    pub nodes: Option<u64>,
+    /// Think for exactly this long.
+    pub move_time: Option<Duration>,
+    /// Think for as long as the clock allows, unless there is a `move_time`.
+    pub clock: Option<GoClocks>,
+    /// Kept back from the time limits for lag, [`MOVE_OVERHEAD`] if not given.
+    pub overhead: Option<Duration>,
+}
+
+impl Limits {
+    /// Whether only the stop flag ends the search, as for UCI's `go infinite`.
+    pub fn is_infinite(&self) -> bool {
+        self.depth.is_none()
+            && self.nodes.is_none()
+            && self.move_time.is_none()
+            && self.clock.is_none()
+    }
+}
+
+/// The result of the last completed iteration.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub depth: u8,
+    /// From the side to move's point of view, see [`MATE`].
+    pub score: i32,
+    /// Principal variation, empty if the side to move has no moves.
+    pub pv: Vec<MoveExt>,
+    /// Nodes searched over all iterations.
+    pub nodes: u64,
+}
+
+impl SearchResult {
+    #[inline]
+    pub fn best_move(&self) -> Option<MoveExt> {
+        self.pv.first().copied()
+    }
+
+    /// Moves until mate, negative when the side to move gets mated. `None` if the score isn't
+    /// a mate.
+    #[inline]
+    pub const fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+/// Moves until mate for a score, negative when the side to move gets mated.
+#[inline]
+pub const fn mate_in(score: i32) -> Option<i32> {
+    if score.abs() < MATE_BOUND {
+        return None;
+    }
+
+    let moves = (MATE - score.abs() + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+/// Searches positions, one at a time.
+#[derive(Debug)]
+pub struct Searcher {
+    limits: Limits,
+    table: Arc<TranspositionTable>,
+    ordering: MoveOrdering,
+    stop: Arc<AtomicBool>,
+    /// Deadlines of the running search, if it has any.
+    time: Option<TimeManager>,
+    nodes: u64,
+    /// Ply of the null move made last, there are never two in a row.
+    null_ply: Option<usize>,
+    /// Set once the search ran out, the iteration it happened in doesn't count.
+    aborted: bool,
+}
+
+impl Searcher {
+    /// A searcher with a table of its own, [`DEFAULT_HASH`] MiB large.
+    pub fn new(limits: Limits) -> Self {
+        Self::with_table(limits, Arc::new(TranspositionTable::new(DEFAULT_HASH)))
+    }
+
+    /// A searcher using a table that outlives it, e.g. over the moves of a game.
+    pub fn with_table(limits: Limits, table: Arc<TranspositionTable>) -> Self {
+        Self {
+            limits,
+            table,
+            ordering: MoveOrdering::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            time: None,
+            nodes: 0,
+            null_ply: None,
+            aborted: false,
+        }
+    }
+
+    /// Set this from another thread to stop a running search.
+    #[inline]
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Search `position` until a limit is hit or the stop flag is set, calling `report` after
+    /// every completed iteration.
+    ///
+    /// The position is left as it was. If even the first iteration doesn't finish, the best move
+    /// is just the first legal one.
+    pub fn search(
+        &mut self,
+        position: &mut Position,
+        mut report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.nodes = 0;
+        self.aborted = false;
+        self.table.new_search();
+        self.ordering.new_search();
+        let overhead = self.limits.overhead.unwrap_or(MOVE_OVERHEAD);
+        self.time = match (self.limits.move_time, &self.limits.clock) {
+            (Some(time), _) => Some(TimeManager::fixed(time, overhead)),
+            (None, Some(clock)) => Some(TimeManager::new(clock, position.active_color, overhead)),
+            (None, None) => None,
+        };
+
+        let max_depth = self.limits.depth.unwrap_or(u8::MAX).clamp(1, MAX_PLY as u8);
+        let mut best = SearchResult {
+            depth: 0,
+            score: 0,
+            pv: vec![],
+            nodes: 0,
+        };
+
+        for depth in 1..=max_depth {
+            let mut pv = vec![];
+            let score = match depth >= ASPIRATION_DEPTH {
+                true => self.aspiration(position, depth, best.score, &mut pv),
+                false => self.negamax(position, depth, 0, -INFINITY, INFINITY, &mut pv),
+            };
+
+            if self.aborted {
+                break;
+            }
+
+            best = SearchResult {
+                depth,
+                score,
+                pv,
+                nodes: self.nodes,
+            };
+            report(&best);
+
+            // nothing to search, or the shortest mate is found already, which an infinite search
+            // keeps confirming until stopped
+            if best.pv.is_empty() || best.mate_in().is_some() && !self.limits.is_infinite() {
+                break;
+            }
+            if let Some(time) = &mut self.time {
+                if !time.iteration(best.pv[0].to_move(), best.score) {
+                    break;
+                }
+            }
+        }
+
+        if best.pv.is_empty() && best.depth == 0 {
+            let state = PositionState::generate(position);
+            if let MoveList::Moves(moves) = MoveList::generate(&state) {
+                best.pv.push(moves[0]);
+            }
+        }
+
+        best.nodes = self.nodes;
+        best
+    }
+
+    /// Search the root with a narrow window around `previous`, widening the side the score falls
+    /// out of until it lands inside.
+    fn aspiration(
+        &mut self,
+        position: &mut Position,
+        depth: u8,
+        previous: i32,
+        pv: &mut Vec<MoveExt>,
+    ) -> i32 {
+        let mut delta = ASPIRATION_WINDOW;
+        let mut alpha = (previous - delta).max(-INFINITY);
+        let mut beta = (previous + delta).min(INFINITY);
+
+        loop {
+            let score = self.negamax(position, depth, 0, alpha, beta, pv);
+
+            // a bound is only trusted from a full window
+            if self.aborted || alpha < score && score < beta {
+                return score;
+            }
+
+            delta *= 2;
+            if score <= alpha {
+                alpha = (score - delta).max(-INFINITY);
+            } else {
+                beta = (score + delta).min(INFINITY);
+            }
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        position: &mut Position,
+        depth: u8,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<MoveExt>,
+    ) -> i32 {
+        pv.clear();
+        self.nodes += 1;
+
+        if self.should_stop() {
+            self.aborted = true;
+            return 0;
+        }
+
+        if ply > 0 && is_draw(position) {
+            return 0;
+        }
+
+        let key = position.zobrist_key();
+        let entry = self.table.probe(key);
+
+        // the root always searches, it has to come up with a move
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiesce(position, ply, alpha, beta);
+        }
+
+        // if passing still fails high, a real move would too, as there is nearly always one that
+        // does better than nothing
+        if ply > 0
+            && depth >= NULL_MOVE_DEPTH
+            && self.null_ply != Some(ply - 1)
+            && beta.abs() < MATE_BOUND
+            && can_pass(position)
+            && evaluate(position) >= beta
+        {
+            let reduction = if depth > 6 { 3 } else { 2 };
+            let previous = self.null_ply.replace(ply);
+
+            let undo = position.make_null();
+            let score = -self.negamax(
+                position,
+                depth - 1 - reduction,
+                ply + 1,
+                -beta,
+                -beta + 1,
+                &mut vec![],
+            );
+            position.unmake_null(undo);
+            self.null_ply = previous;
+
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                // a mate found after a pass isn't a real one
+                return score.min(MATE_BOUND - 1);
+            }
+        }
+
+        let state = PositionState::generate(position);
+        let mut moves = match MoveList::generate(&state) {
+            MoveList::Moves(moves) => moves,
+            MoveList::Checkmate => return -MATE + ply as i32,
+            MoveList::Stalemate => return 0,
+        };
+
+        let hash_move = entry.and_then(|entry| entry.best_move);
+        self.ordering.order(position, &mut moves, hash_move, ply);
+
+        let alpha_start = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = vec![];
+        let mut quiets_tried = vec![];
+
+        for p_move in moves {
+            position.make_move_ext(p_move);
+            let score = -self.negamax(position, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            position.unmake_move();
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(p_move.to_move());
+
+                    pv.clear();
+                    pv.push(p_move);
+                    pv.extend_from_slice(&child_pv);
+
+                    if alpha >= beta {
+                        self.ordering.cutoff(
+                            p_move,
+                            &quiets_tried,
+                            ply,
+                            depth,
+                            position.active_color,
+                        );
+                        break;
+                    }
+                }
+            }
+
+            if is_quiet(p_move) {
+                quiets_tried.push(p_move);
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > alpha_start {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(
+            key,
+            Entry {
+                depth,
+                score: to_table(best, ply),
+                bound,
+                best_move,
+            },
+        );
+
+        best
+    }
+
+    /// Captures and queen promotions only, until the position is quiet.
+    ///
+    /// The side to move can stand pat, take the static evaluation instead of capturing, unless it
+    /// is in check. Then every evasion is searched and mates are found.
+    fn quiesce(&mut self, position: &mut Position, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+
+        if self.should_stop() {
+            self.aborted = true;
+            return 0;
+        }
+
+        let state = PositionState::generate(position);
+        let mut best = -INFINITY;
+
+        // every evasion in check, else only captures and queen promotions on top of standing pat
+        let mut moves = if state.in_check() {
+            match MoveList::generate(&state) {
+                MoveList::Moves(moves) => moves,
+                _ => return -MATE + ply as i32,
+            }
+        } else {
+            if state.is_stalemate() {
+                return 0;
+            }
+
+            best = evaluate(position);
+            if best >= beta || ply >= MAX_PLY {
+                return best;
+            }
+            alpha = alpha.max(best);
+
+            let mut moves = Generator::new(&state).generate_captures();
+            moves.retain(|m| m.capture.is_some() || m.promotion == Some(PieceKind::Queen));
+            moves
+        };
+        moves.sort_by_key(|&m| -mvv_lva(m));
+
+        for p_move in moves {
+            position.make_move_ext(p_move);
+            let score = -self.quiesce(position, ply + 1, -beta, -alpha);
+            position.unmake_move();
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+
+                if score > alpha {
+                    alpha = score;
+
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    #[inline]
+    fn should_stop(&self) -> bool {
+        self.limits.nodes.is_some_and(|nodes| self.nodes > nodes)
+            // from the first node on, a search stopped before it starts stops right away
+            || self.nodes % STOP_CHECK_INTERVAL == 1
+                && (self.stop.load(Ordering::Relaxed)
+                    || self.time.as_ref().is_some_and(TimeManager::out_of_time))
+    }
+}
+
+/// Draws the search scores as 0 without looking further.
+///
+/// A repetition is as good as a draw, the side that could avoid it didn't.
+fn is_draw(position: &Position) -> bool {
+    position.is_repetition(2)
+        || position.is_fifty_move_draw()
+        || position.is_insufficient_material()
+}
+
+/// Whether a null move is safe to try: not in check, where passing is illegal, and with a piece
+/// besides pawns, as pawn endings are full of zugzwang where passing would be best.
+fn can_pass(position: &Position) -> bool {
+    let color = position.active_color;
+    let pieces = [
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+    ];
+
+    !position.in_check()
+        && pieces.into_iter().any(|kind| {
+            !position
+                .board
+                .get_piece_bb(Piece { color, kind })
+                .is_empty()
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn search(fen: &str, depth: u8) -> SearchResult {
+        let mut position = sealion_fen::from_str(fen).unwrap();
+        let before = position.clone();
+
+        let result = Searcher::new(Limits {
+            depth: Some(depth),
+            ..Limits::default()
+        })
+        .search(&mut position, |_| {});
+
+        assert_eq!(position, before);
+        result
+    }
+
+    #[test]
+    fn mates() {
+        let back_rank = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 4);
+        assert_eq!(back_rank.best_move().unwrap().to_string(), "Ra1a8");
+        assert_eq!(back_rank.mate_in(), Some(1));
+        // stops as soon as the mate is found
+        assert_eq!(back_rank.depth, 1);
+
+        let two_moves = search("k7/8/2K5/8/8/8/8/7R w - - 0 1", 5);
+        assert_eq!(two_moves.mate_in(), Some(2));
+
+        // Kb8 is forced, then Rh8
+        let mated = search("k7/8/1K6/8/8/8/8/7R b - - 0 1", 4);
+        assert_eq!(mated.mate_in(), Some(-1));
+    }
+
+    #[test]
+    fn infinite() {
+        let mut position = sealion_fen::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut searcher = Searcher::new(Limits::default());
+        let stop = searcher.stop_flag();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        // goes on past the mate until stopped
+        let result = searcher.search(&mut position, |_| {});
+        stopper.join().unwrap();
+        assert_eq!(result.mate_in(), Some(1));
+        assert!(result.depth > 1);
+
+        assert!(Limits::default().is_infinite());
+        let overhead = Limits {
+            overhead: Some(Duration::ZERO),
+            ..Limits::default()
+        };
+        assert!(overhead.is_infinite());
+        let depth = Limits {
+            depth: Some(1),
+            ..Limits::default()
+        };
+        assert!(!depth.is_infinite());
+    }
+
+    #[test]
+    fn no_moves() {
+        let stalemate = search("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(stalemate.score, 0);
+        assert!(stalemate.best_move().is_none());
+
+        let checkmate = search("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(checkmate.score, -MATE);
+        assert!(checkmate.best_move().is_none());
+    }
+
+    #[test]
+    fn material() {
+        let hanging = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 3);
+        assert_eq!(hanging.best_move().unwrap().to_string(), "Rd1xd5");
+        assert!(hanging.score > 0);
+
+        // taking the pawn loses the queen to the rook
+        let poisoned = search("3rk3/8/8/8/3p4/8/8/3QK3 w - - 0 1", 2);
+        assert_ne!(poisoned.best_move().unwrap().to_string(), "Qd1xd4");
+    }
+
+    #[test]
+    fn quiescence() {
+        // at depth 1 the recapture is past the horizon
+        let poisoned = search("3rk3/8/8/8/3p4/8/8/3QK3 w - - 0 1", 1);
+        assert_ne!(poisoned.best_move().unwrap().to_string(), "Qd1xd4");
+
+        // the exchange on d5 wins a knight for nothing, whatever black recaptures with
+        let exchange = search("3rk3/8/8/3n4/8/2N5/8/3RK3 w - - 0 1", 1);
+        assert_eq!(exchange.best_move().unwrap().to_string(), "Nc3xd5");
+        assert!(exchange.score > 200);
+
+        // no standing pat in check, so a mate at the horizon is seen
+        let mut mated = sealion_fen::from_str("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let score = Searcher::new(Limits::default()).quiesce(&mut mated, 3, -INFINITY, INFINITY);
+        assert_eq!(score, -MATE + 3);
+
+        // nor when there's no move at all
+        let mut stalemate = sealion_fen::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let score =
+            Searcher::new(Limits::default()).quiesce(&mut stalemate, 3, -INFINITY, INFINITY);
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn null_move() {
+        let can = |fen| can_pass(&sealion_fen::from_str(fen).unwrap());
+
+        assert!(can("4k3/8/8/8/8/8/4P3/1N2K3 w - - 0 1"));
+        // only pawns, or only the other side has a piece
+        assert!(!can("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert!(!can("1n2k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        // in check
+        assert!(!can("4k3/8/8/8/8/5n2/8/1N2K3 w - - 0 1"));
+
+        // mates are still found with a piece left to pass with
+        let mate = search("k7/8/2K5/8/8/8/8/7R w - - 0 1", 5);
+        assert_eq!(mate.mate_in(), Some(2));
+    }
+
+    /// Iterative deepening without aspiration windows, to compare with.
+    fn full_window(fen: &str, depth: u8) -> i32 {
+        let mut position = sealion_fen::from_str(fen).unwrap();
+        let mut searcher = Searcher::new(Limits::default());
+        searcher.table.new_search();
+
+        (1..=depth)
+            .map(|depth| {
+                searcher.negamax(&mut position, depth, 0, -INFINITY, INFINITY, &mut vec![])
+            })
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn aspiration_windows() {
+        for fen in [
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            // scores swing between iterations as pieces hang
+            "3rk3/8/8/3n4/8/2N5/8/3RK3 w - - 0 1",
+            "4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1",
+        ] {
+            let result = search(fen, 4);
+            assert_eq!(result.score, full_window(fen, 4), "{fen}");
+            assert!(!result.pv.is_empty());
+        }
+
+        // a rook up at depth 4, mate at depth 5: the window fails high again and again
+        let fen = "6k1/8/8/5K2/8/8/8/R7 w - - 0 1";
+        let mate = search(fen, 5);
+        assert_eq!(mate.mate_in(), Some(3));
+        assert_eq!(mate.score, full_window(fen, 5));
+    }
+
+    #[test]
+    fn limits() {
+        let mut position = Position::starting();
+        let mut depths = vec![];
+
+        let result = Searcher::new(Limits {
+            nodes: Some(2000),
+            ..Limits::default()
+        })
+        .search(&mut position, |result| depths.push(result.depth));
+
+        assert!(result.best_move().is_some());
+        assert!(result.nodes <= 2001);
+        assert_eq!(depths, (1..=result.depth).collect::<Vec<_>>());
+
+        // stopped before it starts, a legal move is still given
+        let mut searcher = Searcher::new(Limits::default());
+        searcher.stop_flag().store(true, Ordering::Relaxed);
+        let result = searcher.search(&mut position, |_| {});
+
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move().is_some());
+    }
+
+    #[test]
+    fn time_limits() {
+        let mut position = Position::starting();
+
+        for limits in [
+            Limits {
+                move_time: Some(Duration::from_millis(100)),
+                ..Limits::default()
+            },
+            Limits {
+                clock: Some(GoClocks {
+                    time: [Duration::from_secs(3), Duration::ZERO],
+                    ..GoClocks::default()
+                }),
+                overhead: Some(Duration::from_millis(10)),
+                ..Limits::default()
+            },
+        ] {
+            let start = std::time::Instant::now();
+            let result = Searcher::new(limits).search(&mut position, |_| {});
+
+            assert!(result.best_move().is_some());
+            assert!(start.elapsed() < Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn shared_table() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let table = Arc::new(TranspositionTable::new(1));
+        let limits = Limits {
+            depth: Some(3),
+            ..Limits::default()
+        };
+
+        let search = || {
+            let mut position = sealion_fen::from_str(fen).unwrap();
+            Searcher::with_table(limits.clone(), table.clone()).search(&mut position, |_| {})
+        };
+
+        let first = search();
+        let second = search();
+
+        // the second search is answered mostly from the table
+        assert!(second.nodes * 4 < first.nodes);
+        assert_eq!(second.score, first.score);
+        assert_eq!(
+            second.best_move().unwrap().to_move(),
+            first.best_move().unwrap().to_move()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Move ordering: the sooner the best move is searched, the more alpha-beta cuts off.
+//!
+//! Moves are tried in stages: the best move stored in the transposition table, captures and
+//! promotions by MVV-LVA, the killer moves of the ply, then the other quiet moves by their
+//! history score, those that leave the moved piece to be taken last.
+
+use sealion_board::{Capture, Color, Move, MoveExt, PieceKind, Position};
+use sealion_engine::threats::is_unsafe_quiet;
+
+use crate::MAX_PLY;
+
+const HASH_MOVE: i32 = i32::MAX;
+const CAPTURE: i32 = 1 << 24;
+/// The first and the second killer of a ply.
+const KILLERS: [i32; 2] = [CAPTURE - 1, CAPTURE - 2];
+/// History scores stay within this, below the killers.
+const HISTORY_MAX: i32 = 1 << 16;
+/// Taken off the history score of an unsafe quiet move, below every safe one.
+const UNSAFE_QUIET: i32 = 2 * HISTORY_MAX + 1;
+
+/// Killer moves and history scores, learned from cutoffs.
+#[derive(Debug, Clone)]
+pub(crate) struct MoveOrdering {
+    /// Quiet moves that cut off last at a ply, most recent first.
+    killers: Vec<[Option<Move>; 2]>,
+    /// How well quiet moves did, by side, from and to square.
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            history: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    /// Forget the killers, which belong to positions of the last search, and fade the history.
+    pub fn new_search(&mut self) {
+        self.killers.fill([None; 2]);
+        for score in self.history.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    /// Sort the moves of `position` at `ply` from the most to the least promising.
+    pub fn order(
+        &self,
+        position: &Position,
+        moves: &mut [MoveExt],
+        hash_move: Option<Move>,
+        ply: usize,
+    ) {
+        moves.sort_by_cached_key(|&p_move| -self.score(position, p_move, hash_move, ply));
+    }
+
+    fn score(
+        &self,
+        position: &Position,
+        p_move: MoveExt,
+        hash_move: Option<Move>,
+        ply: usize,
+    ) -> i32 {
+        let color = position.active_color;
+        if Some(p_move.to_move()) == hash_move {
+            return HASH_MOVE;
+        }
+        if !is_quiet(p_move) {
+            return CAPTURE + mvv_lva(p_move);
+        }
+
+        let killer = self.killers[ply]
+            .iter()
+            .position(|&killer| killer == Some(p_move.to_move()));
+        match killer {
+            Some(index) => KILLERS[index],
+            None if is_unsafe_quiet(position, p_move) => self.history(p_move, color) - UNSAFE_QUIET,
+            None => self.history(p_move, color),
+        }
+    }
+
+    /// Learn from `p_move` causing a beta cutoff `depth` plies from the horizon, after the quiet
+    /// moves `tried` failed to.
+    ///
+    /// Only quiet moves are learned from, captures are ordered well enough already.
+    pub fn cutoff(
+        &mut self,
+        p_move: MoveExt,
+        tried: &[MoveExt],
+        ply: usize,
+        depth: u8,
+        color: Color,
+    ) {
+        if !is_quiet(p_move) {
+            return;
+        }
+
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(p_move.to_move()) {
+            killers[1] = killers[0];
+            killers[0] = Some(p_move.to_move());
+        }
+
+        let bonus = (depth as i32 * depth as i32).min(HISTORY_MAX);
+        self.update_history(p_move, color, bonus);
+        for &tried in tried {
+            self.update_history(tried, color, -bonus);
+        }
+    }
+
+    #[inline]
+    fn history(&self, p_move: MoveExt, color: Color) -> i32 {
+        self.history[color as usize][p_move.from.raw_index() as usize]
+            [p_move.to.raw_index() as usize]
+    }
+
+    /// Moves the score towards the bound of the bonus' sign, slower the closer it gets, so it
+    /// never leaves `-HISTORY_MAX..=HISTORY_MAX`.
+    fn update_history(&mut self, p_move: MoveExt, color: Color, bonus: i32) {
+        let score = &mut self.history[color as usize][p_move.from.raw_index() as usize]
+            [p_move.to.raw_index() as usize];
+        // the product doesn't fit in an i32 for deep cutoffs
+        *score += bonus - (*score as i64 * bonus.abs() as i64 / HISTORY_MAX as i64) as i32;
+    }
+}
+
+/// Neither a capture nor a promotion.
+#[inline]
+pub(crate) fn is_quiet(p_move: MoveExt) -> bool {
+    p_move.capture.is_none() && p_move.promotion.is_none()
+}
+
+/// Most valuable victim, then least valuable attacker: the order to try captures in.
+pub(crate) fn mvv_lva(p_move: MoveExt) -> i32 {
+    let victim = match p_move.capture {
+        Some(Capture::Regular(kind)) => kind.score(),
+        Some(Capture::EnPassant) => PieceKind::Pawn.score(),
+        None => 0,
+    };
+    let promotion = p_move.promotion.map_or(0, |kind| kind.score());
+
+    (victim + promotion) as i32 * 8 - p_move.piece_kind as i32
+}
+
+#[cfg(test)]
+mod test {
+    use sealion_engine::movegen::MoveList;
+    use sealion_engine::state::PositionState;
+
+    use super::*;
+
+    fn moves(fen: &str) -> Vec<MoveExt> {
+        let position = sealion_fen::from_str(fen).unwrap();
+        let MoveList::Moves(moves) = MoveList::generate(&PositionState::generate(&position)) else {
+            panic!("no moves in {fen}");
+        };
+
+        moves
+    }
+
+    fn find(moves: &[MoveExt], uci: &str) -> MoveExt {
+        *moves
+            .iter()
+            .find(|m| m.to_move().to_string() == uci)
+            .unwrap()
+    }
+
+    fn uci(moves: &[MoveExt]) -> Vec<String> {
+        moves.iter().map(|m| m.to_move().to_string()).collect()
+    }
+
+    // a pawn and a queen can take the queen on d5, the queen can also take the rook on a5
+    const CAPTURES: &str = "4k3/8/8/r2q4/4P3/8/3Q4/4K3 w - - 0 1";
+
+    #[test]
+    fn mvv_lva_order() {
+        let moves = moves(CAPTURES);
+        let pawn_takes_queen = mvv_lva(find(&moves, "e4d5"));
+        let queen_takes_queen = mvv_lva(find(&moves, "d2d5"));
+        let queen_takes_rook = mvv_lva(find(&moves, "d2a5"));
+
+        assert!(pawn_takes_queen > queen_takes_queen);
+        assert!(queen_takes_queen > queen_takes_rook);
+        assert!(queen_takes_rook > mvv_lva(find(&moves, "d2d3")));
+
+        let promotions = self::moves("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(mvv_lva(find(&promotions, "b7b8q")) > mvv_lva(find(&promotions, "b7b8n")));
+    }
+
+    #[test]
+    fn stages() {
+        let mut moves = moves(CAPTURES);
+        let mut ordering = MoveOrdering::new();
+        let quiet = find(&moves, "d2c3");
+        let killer = find(&moves, "d2b4");
+        let tried = find(&moves, "e1f1");
+
+        ordering.cutoff(quiet, &[tried], 3, 4, Color::White);
+        ordering.cutoff(killer, &[], 2, 4, Color::White);
+
+        let mut position = sealion_fen::from_str(CAPTURES).unwrap();
+        let hash_move = "d2d3".parse().ok();
+        ordering.order(&position, &mut moves, hash_move, 2);
+        let order = uci(&moves);
+
+        assert_eq!(
+            &order[..5],
+            ["d2d3", "e4d5", "d2d5", "d2a5", "d2b4"].map(str::to_owned)
+        );
+        // killers only count at their own ply, history everywhere
+        assert_eq!(order[5], "d2c3");
+        // the unsafe quiets come after even a move with a bad history
+        let position_of = |uci: &str| order.iter().position(|m| m == uci).unwrap();
+        assert!(position_of("e1f1") < position_of("d2d4"));
+        assert!(position_of("e1f1") < position_of("d2a2"));
+
+        // history is kept by side, killers by ply only
+        assert_eq!(ordering.history(quiet, Color::Black), 0);
+        position.active_color = Color::Black;
+        ordering.order(&position, &mut moves, None, 2);
+        assert_eq!(uci(&moves)[3], "d2b4");
+    }
+
+    #[test]
+    fn learning() {
+        let moves = moves(CAPTURES);
+        let mut ordering = MoveOrdering::new();
+        let first = find(&moves, "d2c3");
+        let second = find(&moves, "d2b4");
+
+        ordering.cutoff(first, &[], 0, 2, Color::White);
+        ordering.cutoff(first, &[], 0, 2, Color::White);
+        assert_eq!(ordering.killers[0], [Some(first.to_move()), None]);
+
+        ordering.cutoff(second, &[first], 0, 2, Color::White);
+        assert_eq!(
+            ordering.killers[0],
+            [Some(second.to_move()), Some(first.to_move())]
+        );
+        assert!(ordering.history(second, Color::White) > 0);
+
+        // captures are left alone
+        ordering.cutoff(find(&moves, "e4d5"), &[], 0, 2, Color::White);
+        assert_eq!(ordering.killers[0][0], Some(second.to_move()));
+
+        // deep cutoffs saturate instead of overflowing
+        for _ in 0..1000 {
+            ordering.cutoff(first, &[second], 1, 255, Color::White);
+        }
+        assert!(ordering.history(first, Color::White) <= HISTORY_MAX);
+        assert!(ordering.history(second, Color::White) >= -HISTORY_MAX);
+
+        ordering.new_search();
+        assert_eq!(ordering.killers[0], [None, None]);
+        assert!(ordering.history(first, Color::White) <= HISTORY_MAX / 2);
+    }
+
+    #[test]
+    fn unsafe_quiets() {
+        let position = sealion_fen::from_str(CAPTURES).unwrap();
+        let mut moves = moves(CAPTURES);
+        MoveOrdering::new().order(&position, &mut moves, None, 0);
+        let order = uci(&moves);
+
+        // the queen next to the enemy queen or in front of the rook, safe squares first
+        let first_unsafe = moves
+            .iter()
+            .position(|&m| is_quiet(m) && is_unsafe_quiet(&position, m))
+            .unwrap();
+        assert!(moves[first_unsafe..]
+            .iter()
+            .all(|&m| is_unsafe_quiet(&position, m)));
+        for uci in ["d2d4", "d2a2"] {
+            assert!(order[first_unsafe..].contains(&uci.to_owned()), "{uci}");
+        }
+        for uci in ["d2c3", "e1f1"] {
+            assert!(order[..first_unsafe].contains(&uci.to_owned()), "{uci}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Time management: how long to think about a move with a clock running.
+//!
+//! A [`TimeManager`] splits the time left into a soft deadline, after which no new iteration is
+//! started, and a hard one, at which the search stops mid-iteration. The soft deadline stretches
+//! while the best move keeps changing or the score drops, and shrinks once the best move has been
+//! the same for a while.
+
+use std::time::{Duration, Instant};
+
+use sealion_board::{Color, Move};
+
+/// Kept back from every deadline for the time it takes the move to reach the GUI, unless
+/// [`crate::Limits::overhead`] says otherwise.
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+/// Moves the time left is assumed to last for without a `movestogo`.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// A score drop of at least this much between iterations counts as trouble.
+const SCORE_DROP: i32 = 30;
+
+/// The clocks as UCI's `go` gives them, `wtime`, `btime`, `winc`, `binc` and `movestogo`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GoClocks {
+    /// Time left by color.
+    pub time: [Duration; 2],
+    /// Increment per move by color.
+    pub increment: [Duration; 2],
+    /// Moves until the next time control, if there is one.
+    pub moves_to_go: Option<u32>,
+}
+
+/// Deadlines of one search, and how stable its result has been.
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    start: Instant,
+    soft: Duration,
+    hard: Duration,
+    /// Best move and score of the last iteration.
+    last: Option<(Move, i32)>,
+    /// Iterations in a row the best move stayed the same.
+    stable: u32,
+    /// How much of the soft deadline the next iteration may start within.
+    scale: f64,
+}
+
+impl TimeManager {
+    /// Deadlines for `color` to move with `clock`, starting now, `overhead` kept back.
+    pub fn new(clock: &GoClocks, color: Color, overhead: Duration) -> Self {
+        let time = clock.time[color as usize];
+        let increment = clock.increment[color as usize];
+        let moves = clock.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        let available = time.saturating_sub(overhead);
+        let base = available / moves + increment * 3 / 4;
+        // never more than most of what's left, however few moves there are
+        let hard = (base * 3).min(available * 4 / 5);
+
+        Self::with_deadlines(base.min(hard), hard)
+    }
+
+    /// Exactly `time` per move, as for UCI's `movetime`, `overhead` kept back.
+    pub fn fixed(time: Duration, overhead: Duration) -> Self {
+        let time = time.saturating_sub(overhead);
+        Self::with_deadlines(time, time)
+    }
+
+    fn with_deadlines(soft: Duration, hard: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            soft,
+            hard,
+            last: None,
+            stable: 0,
+            scale: 1.0,
+        }
+    }
+
+    #[inline]
+    pub fn soft(&self) -> Duration {
+        self.soft
+    }
+
+    #[inline]
+    pub fn hard(&self) -> Duration {
+        self.hard
+    }
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Whether the search has to stop right away.
+    #[inline]
+    pub fn out_of_time(&self) -> bool {
+        self.elapsed() >= self.hard
+    }
+
+    /// Take in the result of a completed iteration, and tell whether to start another one.
+    pub fn iteration(&mut self, best_move: Move, score: i32) -> bool {
+        let mut scale = match self.last {
+            Some((last, _)) if last == best_move => {
+                self.stable += 1;
+                match self.stable {
+                    1 => 1.0,
+                    2..=3 => 0.8,
+                    _ => 0.6,
+                }
+            }
+            // a new best move needs confirming
+            Some(_) => {
+                self.stable = 0;
+                1.6
+            }
+            None => 1.0,
+        };
+        if self
+            .last
+            .is_some_and(|(_, last)| score <= last - SCORE_DROP)
+        {
+            scale *= 1.4;
+        }
+
+        self.last = Some((best_move, score));
+        self.scale = scale;
+        self.elapsed() < self.soft.mul_f64(scale).min(self.hard)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn clock(time: u64, increment: u64, moves_to_go: Option<u32>) -> GoClocks {
+        GoClocks {
+            time: [Duration::from_millis(time), Duration::from_secs(1)],
+            increment: [Duration::from_millis(increment), Duration::ZERO],
+            moves_to_go,
+        }
+    }
+
+    #[test]
+    fn deadlines() {
+        let sudden_death = TimeManager::new(&clock(60_000, 0, None), Color::White, MOVE_OVERHEAD);
+        assert!(sudden_death.soft() < Duration::from_secs(3));
+        assert!(sudden_death.soft() > Duration::from_secs(1));
+        assert!(sudden_death.hard() > sudden_death.soft());
+
+        // the increment is spent too, more so with fewer moves to go
+        let increment = TimeManager::new(&clock(60_000, 2_000, None), Color::White, MOVE_OVERHEAD);
+        assert!(increment.soft() > sudden_death.soft());
+        let last_move = TimeManager::new(&clock(60_000, 0, Some(1)), Color::White, MOVE_OVERHEAD);
+        assert!(last_move.soft() > increment.soft());
+        assert!(last_move.hard() < Duration::from_secs(60));
+
+        // black's clock for black
+        let black = TimeManager::new(&clock(60_000, 0, None), Color::Black, MOVE_OVERHEAD);
+        assert!(black.hard() < Duration::from_secs(1));
+
+        // nothing left, stop at once
+        let flagging = TimeManager::new(&clock(10, 0, None), Color::White, MOVE_OVERHEAD);
+        assert_eq!(flagging.hard(), Duration::ZERO);
+        assert!(flagging.out_of_time());
+
+        let fixed = TimeManager::fixed(Duration::from_secs(1), MOVE_OVERHEAD);
+        assert_eq!(fixed.soft(), fixed.hard());
+        assert_eq!(fixed.hard(), Duration::from_secs(1) - MOVE_OVERHEAD);
+
+        // a laggier connection keeps more back
+        let lag = Duration::from_millis(500);
+        assert_eq!(TimeManager::fixed(Duration::from_secs(1), lag).hard(), lag);
+        let laggy = TimeManager::new(&clock(10_000, 0, Some(1)), Color::White, lag);
+        let fast = TimeManager::new(&clock(10_000, 0, Some(1)), Color::White, Duration::ZERO);
+        assert!(laggy.hard() < fast.hard());
+    }
+
+    #[test]
+    fn stability() {
+        let e2e4 = "e2e4".parse().unwrap();
+        let d2d4 = "d2d4".parse().unwrap();
+        let mut time = TimeManager::fixed(Duration::from_secs(60), MOVE_OVERHEAD);
+
+        assert!(time.iteration(e2e4, 20));
+        assert_eq!(time.scale, 1.0);
+        time.iteration(d2d4, 20);
+        assert_eq!(time.scale, 1.6);
+        for _ in 0..4 {
+            time.iteration(d2d4, 20);
+        }
+        assert_eq!(time.scale, 0.6);
+        time.iteration(d2d4, -40);
+        assert!(time.scale > 0.8);
+
+        let mut time = TimeManager::fixed(Duration::ZERO, MOVE_OVERHEAD);
+        assert!(!time.iteration(e2e4, 0));
+    }
+}
@@ -0,0 +1,426 @@
+//! Analysis trees that grow over many sessions, for correspondence chess.
+//!
+//! Instead of one deep principal variation, the tree keeps a search score for every move it
+//! expanded and backs them up by minimax. Each expansion follows the critical moves, those close
+//! to the best one, to the least visited of them and searches every reply there. The tree is
+//! saved as text, so analysis can be picked up where it was left, and exported as PGN.
+
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use sealion_board::{Color, Move, MoveExt, Position};
+use sealion_engine::legality::check_move;
+use sealion_engine::movegen::MoveList;
+use sealion_engine::san::to_san;
+use sealion_engine::state::PositionState;
+
+use crate::tt::{TranspositionTable, DEFAULT_HASH};
+use crate::{is_draw, mate_in, Limits, Searcher, MATE_BOUND};
+
+/// How much worse than the best move a move may be and still be explored.
+pub const CRITICAL_MARGIN: i32 = 30;
+
+/// A position in the tree, reached by the moves leading to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Search score from the side to move's point of view, see [`crate::MATE`].
+    pub score: i32,
+    pub depth: u8,
+    /// Expansions that went through this node.
+    pub visits: u32,
+    pub children: Vec<(Move, Node)>,
+}
+
+impl Node {
+    const fn leaf(score: i32, depth: u8) -> Self {
+        Self {
+            score,
+            depth,
+            visits: 0,
+            children: vec![],
+        }
+    }
+
+    /// The best child's value once expanded, the search score before.
+    pub fn value(&self) -> i32 {
+        self.children
+            .iter()
+            .map(|(_, child)| backed_up(child.value()))
+            .max()
+            .unwrap_or(self.score)
+    }
+
+    /// Children within [`CRITICAL_MARGIN`] of the best one, best first.
+    pub fn critical(&self) -> Vec<&(Move, Node)> {
+        let mut children = self.children.iter().collect::<Vec<_>>();
+        children.sort_by_key(|(_, child)| -backed_up(child.value()));
+
+        let best = self.value();
+        children.retain(|(_, child)| backed_up(child.value()) >= best - CRITICAL_MARGIN);
+        children
+    }
+
+    fn write(&self, out: &mut String, path: &mut Vec<Move>) {
+        let _ = write!(out, "node {} {} {}", self.score, self.depth, self.visits);
+        for p_move in path.iter() {
+            let _ = write!(out, " {p_move}");
+        }
+        out.push('\n');
+
+        for (p_move, child) in &self.children {
+            path.push(*p_move);
+            child.write(out, path);
+            path.pop();
+        }
+    }
+}
+
+/// A child's value from the parent's point of view, a mate one move further away.
+const fn backed_up(value: i32) -> i32 {
+    let value = -value;
+
+    if value >= MATE_BOUND {
+        value - 1
+    } else if value <= -MATE_BOUND {
+        value + 1
+    } else {
+        value
+    }
+}
+
+/// An analysis tree of one position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisTree {
+    pub position: Position,
+    pub root: Node,
+}
+
+impl AnalysisTree {
+    /// A tree with nothing analysed yet.
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            root: Node::leaf(0, 0),
+        }
+    }
+
+    /// Search every move of the least visited leaf along the critical lines, `depth` plies deep.
+    ///
+    /// Returns the moves leading to the expanded node, `None` once every critical line ends in
+    /// mate or a draw.
+    pub fn expand(&mut self, depth: u8) -> Option<Vec<Move>> {
+        let mut position = self.position.clone();
+        let mut path = vec![];
+        // sibling positions share a lot, so their searches share a table
+        let table = Arc::new(TranspositionTable::new(DEFAULT_HASH));
+
+        expand(&mut self.root, &mut position, depth, &table, &mut path).then_some(path)
+    }
+
+    /// The tree as PGN, with the best moves as the main line and the other critical moves as
+    /// variations.
+    ///
+    /// Every move is commented with its value from white's point of view, leaves also with the
+    /// depth of their search.
+    pub fn to_pgn(&self) -> String {
+        let mut out = String::new();
+
+        if self.position != Position::starting() {
+            let _ = writeln!(out, "[FEN \"{}\"]", self.position.to_fen());
+            out += "[SetUp \"1\"]\n";
+        }
+        out += "[Result \"*\"]\n\n";
+
+        let mut position = self.position.clone();
+        let mut movetext = String::new();
+        write_line(&mut movetext, &mut position, &self.root);
+
+        out += &movetext;
+        out += "*\n";
+        out
+    }
+}
+
+fn expand(
+    node: &mut Node,
+    position: &mut Position,
+    depth: u8,
+    table: &Arc<TranspositionTable>,
+    path: &mut Vec<Move>,
+) -> bool {
+    if node.children.is_empty() {
+        if is_draw(position) {
+            return false;
+        }
+
+        let state = PositionState::generate(position);
+        let MoveList::Moves(moves) = MoveList::generate(&state) else {
+            return false;
+        };
+
+        for p_move in moves {
+            position.make_move_ext(p_move);
+
+            let child = match is_draw(position) {
+                true => Node::leaf(0, 0),
+                false => {
+                    let limits = Limits {
+                        depth: Some(depth),
+                        ..Limits::default()
+                    };
+                    let result =
+                        Searcher::with_table(limits, table.clone()).search(position, |_| {});
+
+                    Node::leaf(result.score, result.depth)
+                }
+            };
+
+            position.unmake_move();
+            node.children.push((p_move.to_move(), child));
+        }
+
+        node.visits += 1;
+        return true;
+    }
+
+    // least visited first, the better move of equally visited ones
+    let mut candidates = node
+        .critical()
+        .into_iter()
+        .map(|(p_move, child)| (*p_move, child.visits))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|&(_, visits)| visits);
+
+    for (p_move, _) in candidates {
+        let index = node
+            .children
+            .iter()
+            .position(|(child_move, _)| *child_move == p_move)
+            .unwrap();
+        let p_move_ext = legal_move(position, p_move).unwrap();
+
+        position.make_move_ext(p_move_ext);
+        path.push(p_move);
+        let expanded = expand(&mut node.children[index].1, position, depth, table, path);
+        position.unmake_move();
+
+        if expanded {
+            node.visits += 1;
+            return true;
+        }
+        path.pop();
+    }
+
+    false
+}
+
+fn legal_move(position: &Position, p_move: Move) -> Option<MoveExt> {
+    check_move(&PositionState::generate(position), p_move).ok()
+}
+
+fn write_line(out: &mut String, position: &mut Position, node: &Node) {
+    let critical = node.critical();
+    let Some((main, alternatives)) = critical.split_first() else {
+        return;
+    };
+
+    write_move(out, position, main);
+
+    for alternative in alternatives {
+        out.push_str("( ");
+        write_move(out, position, alternative);
+
+        position.make_move_ext(legal_move(position, alternative.0).unwrap());
+        write_line(out, position, &alternative.1);
+        position.unmake_move();
+
+        out.push_str(") ");
+    }
+
+    position.make_move_ext(legal_move(position, main.0).unwrap());
+    write_line(out, position, &main.1);
+    position.unmake_move();
+}
+
+/// A numbered move with its value as a comment.
+fn write_move(out: &mut String, position: &Position, (p_move, node): &(Move, Node)) {
+    let state = PositionState::generate(position);
+    let san = to_san(&state, legal_move(position, *p_move).unwrap());
+
+    let number = position.fullmove_counter;
+    let value = match position.active_color {
+        Color::White => backed_up(node.value()),
+        Color::Black => -backed_up(node.value()),
+    };
+    let value = match mate_in(value) {
+        Some(moves) => format!("#{moves}"),
+        None => format!("{:+.2}", value as f64 / 100.0),
+    };
+
+    let _ = match position.active_color {
+        Color::White => write!(out, "{number}. {san} "),
+        Color::Black => write!(out, "{number}... {san} "),
+    };
+    let _ = match node.children.is_empty() {
+        true => write!(out, "{{{value}/{}}} ", node.depth),
+        false => write!(out, "{{{value}}} "),
+    };
+}
+
+/// One `fen` line, then a `node <score> <depth> <visits> <moves>` line per node with the moves
+/// from the root, parents before children.
+impl Display for AnalysisTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.root.write(&mut out, &mut vec![]);
+
+        writeln!(f, "fen {}", self.position.to_fen())?;
+        write!(f, "{out}")
+    }
+}
+
+impl FromStr for AnalysisTree {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+
+        let fen = lines.next().and_then(|line| line.strip_prefix("fen "));
+        let position = sealion_fen::from_str(fen.ok_or(())?.trim()).map_err(|_| ())?;
+        let mut root = None;
+
+        for line in lines {
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("node") {
+                return Err(());
+            }
+
+            let mut number = || fields.next().ok_or(());
+            let node = Node {
+                score: number()?.parse().map_err(|_| ())?,
+                depth: number()?.parse().map_err(|_| ())?,
+                visits: number()?.parse().map_err(|_| ())?,
+                children: vec![],
+            };
+            let path = fields
+                .map(Move::from_str)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ())?;
+
+            let Some((last, parents)) = path.split_last() else {
+                if root.is_some() {
+                    return Err(());
+                }
+                root = Some(node);
+                continue;
+            };
+
+            let mut parent = root.as_mut().ok_or(())?;
+            let mut replay = position.clone();
+            for p_move in parents {
+                replay.make_move_ext(legal_move(&replay, *p_move).ok_or(())?);
+                parent = parent
+                    .children
+                    .iter_mut()
+                    .find(|(child_move, _)| child_move == p_move)
+                    .map(|(_, child)| child)
+                    .ok_or(())?;
+            }
+
+            legal_move(&replay, *last).ok_or(())?;
+            if parent
+                .children
+                .iter()
+                .any(|(child_move, _)| child_move == last)
+            {
+                return Err(());
+            }
+            parent.children.push((*last, node));
+        }
+
+        Ok(Self {
+            position,
+            root: root.ok_or(())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grows() {
+        // white mates with Ra8 or loses the rook to nothing
+        let position = sealion_fen::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mut tree = AnalysisTree::new(position);
+
+        assert_eq!(tree.expand(1), Some(vec![]));
+        assert_eq!(tree.root.visits, 1);
+        assert_eq!(tree.root.value(), crate::MATE - 1);
+
+        // the mate is the only critical move, and there is nothing left after it
+        let critical = tree.root.critical();
+        assert_eq!(critical.len(), 1);
+        assert_eq!(critical[0].0.to_string(), "a1a8");
+        assert_eq!(tree.expand(1), None);
+
+        let pgn = tree.to_pgn();
+        assert!(pgn.starts_with("[FEN \"6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\"]\n"));
+        assert!(pgn.ends_with("\n\n1. Ra8# {#1/1} *\n"), "{pgn}");
+    }
+
+    #[test]
+    fn critical_lines() {
+        let mut tree = AnalysisTree::new(Position::starting());
+
+        tree.expand(1).unwrap();
+        assert_eq!(tree.root.children.len(), 20);
+
+        // goes down the best move first, then its equally good alternatives
+        let first = tree.expand(1).unwrap();
+        let second = tree.expand(1).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_ne!(first, second);
+        assert_eq!(tree.root.visits, 3);
+
+        let critical = tree.root.critical();
+        assert_eq!(first[0], critical[0].0);
+        assert!(critical
+            .iter()
+            .all(|(_, child)| backed_up(child.value()) >= tree.root.value() - CRITICAL_MARGIN));
+
+        let pgn = tree.to_pgn();
+        assert!(pgn.starts_with("[Result \"*\"]\n\n1. "));
+        assert!(pgn.contains("( 1. "));
+        assert!(pgn.contains("1... "));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut tree = AnalysisTree::new(Position::starting());
+        for _ in 0..3 {
+            tree.expand(1).unwrap();
+        }
+
+        let text = tree.to_string();
+        assert!(text.starts_with("fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n"));
+        assert_eq!(text.parse::<AnalysisTree>(), Ok(tree.clone()));
+
+        // a child before its parent, an illegal move, a second root
+        let fen = "fen 4k3/8/8/8/8/8/8/4K3 w - - 0 1\n";
+        assert!(format!("{fen}node 0 1 1 e1e2\n")
+            .parse::<AnalysisTree>()
+            .is_err());
+        assert!(format!("{fen}node 0 1 1\nnode 0 1 0 e1e3\n")
+            .parse::<AnalysisTree>()
+            .is_err());
+        assert!(format!("{fen}node 0 1 1\nnode 0 1 1\n")
+            .parse::<AnalysisTree>()
+            .is_err());
+        assert!(format!("{fen}node 0 1 1\nnode 0 1 0 e1e2\n")
+            .parse::<AnalysisTree>()
+            .is_ok());
+    }
+}
@@ -0,0 +1,351 @@
+//! Transposition table: results of positions searched before, by Zobrist key.
+//!
+//! A key can only go into one bucket of [`BUCKET_SIZE`] slots. Each slot stores its key XORed
+//! with its data, so a write torn by another thread reads as a miss rather than as someone else's
+//! entry, and a table can be shared between searches without locking.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use sealion_board::{Move, PieceKind, Square};
+
+use crate::MATE_BOUND;
+
+/// Table size in MiB when none is given.
+pub const DEFAULT_HASH: usize = 16;
+const BUCKET_SIZE: usize = 4;
+
+/// How the stored score relates to the real one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, the score is at least this.
+    Lower,
+    /// The search failed low, the score is at most this.
+    Upper,
+}
+
+/// What a search found out about a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub depth: u8,
+    /// Mates are counted from the position itself, not from the root, see [`to_table`].
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+impl Entry {
+    /// Set in every stored entry, so an empty slot is all zeros.
+    const PRESENT: u64 = 1 << 63;
+
+    /// Score in bits 0..16, depth in 16..24, bound in 24..26, generation in 26..32, move in 32..48.
+    fn pack(&self, generation: u8) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let best_move = self.best_move.map_or(0, |p_move| {
+            let promotion = p_move.promotion.map_or(0, |kind| {
+                PieceKind::PROMOTABLE
+                    .iter()
+                    .position(|&k| k == kind)
+                    .unwrap() as u64
+                    + 1
+            });
+
+            1 << 15
+                | p_move.from.raw_index() as u64
+                | (p_move.to.raw_index() as u64) << 6
+                | promotion << 12
+        });
+
+        Self::PRESENT
+            | self.score as i16 as u16 as u64
+            | (self.depth as u64) << 16
+            | bound << 24
+            | ((generation & 0x3f) as u64) << 26
+            | best_move << 32
+    }
+
+    fn unpack(data: u64) -> (Self, u8) {
+        let best_move = (data >> 32) as u16;
+        let best_move = (best_move & 1 << 15 != 0).then(|| Move {
+            from: Square::from_index_unchecked((best_move & 0x3f) as u8),
+            to: Square::from_index_unchecked((best_move >> 6 & 0x3f) as u8),
+            promotion: match best_move >> 12 & 0x7 {
+                0 => None,
+                index => Some(PieceKind::PROMOTABLE[index as usize - 1]),
+            },
+        });
+
+        let entry = Self {
+            depth: (data >> 16) as u8,
+            score: data as u16 as i16 as i32,
+            bound: match data >> 24 & 0x3 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            best_move,
+        };
+
+        (entry, (data >> 26) as u8 & 0x3f)
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+type Bucket = [Slot; BUCKET_SIZE];
+
+/// A fixed size hash table of [`Entry`]s, shared by reference.
+pub struct TranspositionTable {
+    buckets: Box<[Bucket]>,
+    /// Bumped every search, entries of older searches are replaced first.
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// A table taking up about `megabytes` MiB, at least one bucket.
+    pub fn new(megabytes: usize) -> Self {
+        Self::try_new(megabytes)
+            .unwrap_or_else(|| panic!("failed to allocate a {megabytes} MiB hash table"))
+    }
+
+    /// Like [`TranspositionTable::new`], `None` if the memory isn't available.
+    pub fn try_new(megabytes: usize) -> Option<Self> {
+        let count = (megabytes.checked_mul(1024 * 1024)? / size_of::<Bucket>()).max(1);
+        let mut buckets = Vec::new();
+        buckets.try_reserve_exact(count).ok()?;
+        buckets.resize_with(count, Bucket::default);
+
+        Some(Self {
+            buckets: buckets.into_boxed_slice(),
+            generation: AtomicU8::new(0),
+        })
+    }
+
+    /// Size in MiB, rounded down.
+    #[inline]
+    pub fn megabytes(&self) -> usize {
+        self.buckets.len() * size_of::<Bucket>() / (1024 * 1024)
+    }
+
+    /// Forget everything, e.g. for a new game.
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flatten() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Mark the start of a search, entries stored before become older.
+    #[inline]
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.bucket(key).iter().find_map(|slot| {
+            let data = slot.data.load(Ordering::Relaxed);
+            let stored = slot.key.load(Ordering::Relaxed) ^ data;
+
+            (data != 0 && stored == key).then(|| Entry::unpack(data).0)
+        })
+    }
+
+    /// Store an entry, over the one for the same key or else the least useful in its bucket.
+    ///
+    /// An entry without a best move keeps the one stored for the same key.
+    pub fn store(&self, key: u64, mut entry: Entry) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let bucket = self.bucket(key);
+
+        let loaded = bucket.each_ref().map(|slot| {
+            let data = slot.data.load(Ordering::Relaxed);
+            (slot.key.load(Ordering::Relaxed) ^ data, data)
+        });
+
+        let same = loaded
+            .iter()
+            .position(|&(stored, data)| data != 0 && stored == key);
+        let index = same.unwrap_or_else(|| {
+            // empty slots first, then shallow entries of old searches
+            let worth = |&(_, data): &(u64, u64)| match data {
+                0 => i32::MIN,
+                _ => {
+                    let (stored, stored_generation) = Entry::unpack(data);
+                    let age = generation.wrapping_sub(stored_generation) & 0x3f;
+                    stored.depth as i32 - 8 * age as i32
+                }
+            };
+
+            (0..BUCKET_SIZE)
+                .min_by_key(|&index| worth(&loaded[index]))
+                .unwrap()
+        });
+
+        if let Some(same) = same {
+            entry.best_move = entry
+                .best_move
+                .or(Entry::unpack(loaded[same].1).0.best_move);
+        }
+
+        let data = entry.pack(generation);
+        bucket[index].key.store(key ^ data, Ordering::Relaxed);
+        bucket[index].data.store(data, Ordering::Relaxed);
+    }
+
+    /// Per mille of the first thousand slots used by the current search, as UCI reports it.
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed) & 0x3f;
+        let slots = self.buckets.iter().flatten().take(1000);
+        let total = slots.clone().count() as u32;
+
+        let used = slots
+            .filter(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                data != 0 && Entry::unpack(data).1 == generation
+            })
+            .count() as u32;
+
+        used * 1000 / total
+    }
+
+    #[inline]
+    fn bucket(&self, key: u64) -> &Bucket {
+        let index = (key as u128 * self.buckets.len() as u128) >> 64;
+        &self.buckets[index as usize]
+    }
+}
+
+impl Debug for TranspositionTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("buckets", &self.buckets.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// A score `ply` plies from the root as stored: mates counted from the position, not the root.
+#[inline]
+pub(crate) const fn to_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// The inverse of [`to_table`].
+#[inline]
+pub(crate) const fn from_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MATE;
+
+    fn entry(depth: u8, score: i32, best_move: Option<&str>) -> Entry {
+        Entry {
+            depth,
+            score,
+            bound: Bound::Lower,
+            best_move: best_move.map(|uci| uci.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn packing() {
+        for entry in [
+            entry(0, 0, None),
+            entry(12, -345, Some("e2e4")),
+            entry(255, MATE, Some("h7h8n")),
+            Entry {
+                bound: Bound::Upper,
+                ..entry(3, -MATE + 4, Some("a7b8q"))
+            },
+        ] {
+            assert_eq!(Entry::unpack(entry.pack(70)), (entry, 70 & 0x3f));
+            assert_ne!(entry.pack(0), 0);
+        }
+    }
+
+    #[test]
+    fn store_and_probe() {
+        let table = TranspositionTable::new(1);
+        assert_eq!(table.megabytes(), 1);
+        assert_eq!(table.probe(42), None);
+
+        table.store(42, entry(5, 17, Some("g1f3")));
+        assert_eq!(table.probe(42), Some(entry(5, 17, Some("g1f3"))));
+        assert_eq!(table.probe(43), None);
+
+        // the move survives an entry without one
+        table.store(42, entry(6, 20, None));
+        assert_eq!(table.probe(42), Some(entry(6, 20, Some("g1f3"))));
+
+        assert_eq!(table.hashfull(), 1);
+        table.new_search();
+        assert_eq!(table.hashfull(), 0);
+
+        table.clear();
+        assert_eq!(table.probe(42), None);
+    }
+
+    #[test]
+    fn allocation() {
+        assert_eq!(TranspositionTable::try_new(2).unwrap().megabytes(), 2);
+        assert!(TranspositionTable::try_new(usize::MAX).is_none());
+        assert!(TranspositionTable::try_new(usize::MAX / (1024 * 1024)).is_none());
+    }
+
+    #[test]
+    fn replacement() {
+        // a single bucket, every key lands in it
+        let table = TranspositionTable::new(0);
+        for key in 1..=BUCKET_SIZE as u64 {
+            table.store(key, entry(key as u8, 0, None));
+        }
+
+        // the shallowest goes
+        table.store(100, entry(9, 0, None));
+        assert_eq!(table.probe(1), None);
+        assert!((2..=BUCKET_SIZE as u64).all(|key| table.probe(key).is_some()));
+
+        // deep entries of an old search go before shallow ones of this one
+        table.new_search();
+        table.store(101, entry(1, 0, None));
+        table.store(102, entry(1, 0, None));
+        assert!(table.probe(101).is_some());
+        assert!(table.probe(102).is_some());
+        assert!(table.probe(100).is_some());
+    }
+
+    #[test]
+    fn mate_scores() {
+        // mate in 3 plies from the root, 1 ply from a node 2 plies deep
+        let stored = to_table(MATE - 3, 2);
+        assert_eq!(stored, MATE - 1);
+        assert_eq!(from_table(stored, 4), MATE - 5);
+
+        assert_eq!(from_table(to_table(-MATE + 6, 3), 3), -MATE + 6);
+        assert_eq!(to_table(150, 10), 150);
+    }
+}
@@ -0,0 +1,122 @@
+//! `sealion analyze-tree <tree> [--fen <fen>] [--steps <n>] [--depth <n>] [--pgn <out>]`: long
+//! running analysis of one position, for correspondence games.
+//!
+//! The tree file is created on the first run, from `--fen` or the starting position, and grows
+//! by `--steps` expansions every run after that. It is saved after each expansion, so stopping
+//! the command loses at most one. `--pgn` exports the tree as annotated PGN, with `--steps 0`
+//! without analysing further.
+
+use std::path::Path;
+
+use sealion::search::{mate_in, AnalysisTree};
+use sealion::Position;
+
+use crate::crash;
+
+const USAGE: &str =
+    "usage: sealion analyze-tree <tree> [--fen <fen>] [--steps <n>] [--depth <n>] [--pgn <out>]";
+
+/// Run the analyze-tree command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    crash::install();
+
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut fen = None;
+    let mut steps = 10;
+    let mut depth = 4;
+    let mut pgn = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        match arg.as_str() {
+            "--fen" => fen = Some(value()?),
+            "--steps" => steps = value()?.parse().map_err(|_| USAGE)?,
+            "--depth" => depth = value()?.parse().map_err(|_| USAGE)?,
+            "--pgn" => pgn = Some(value()?),
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let path = path.ok_or_else(|| USAGE.to_owned())?;
+    let mut tree = if Path::new(&path).exists() {
+        if fen.is_some() {
+            return Err(format!(
+                "`{path}` already exists, leave out --fen to continue it"
+            ));
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read `{path}`: {err}"))?;
+        text.parse::<AnalysisTree>()
+            .map_err(|_| format!("`{path}` is not an analysis tree"))?
+    } else {
+        let position = match fen {
+            Some(fen) => {
+                let position = sealion::fen::from_str(&fen)
+                    .map_err(|err| format!("invalid fen `{fen}`: {err}"))?;
+                position
+                    .validate()
+                    .map_err(|err| format!("invalid position `{fen}`: {err}"))?;
+                position
+            }
+            None => Position::starting(),
+        };
+
+        let tree = AnalysisTree::new(position);
+        save(&tree, &path)?;
+        tree
+    };
+
+    for step in 1..=steps {
+        let Some(line) = tree.expand(depth) else {
+            println!("every critical line ends in mate or a draw");
+            break;
+        };
+        save(&tree, &path)?;
+
+        let line = line.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        println!(
+            "step {step}: expanded {}, value {}, {} expansions in total",
+            match line.is_empty() {
+                true => "the root".to_owned(),
+                false => line.join(" "),
+            },
+            value(tree.root.value()),
+            tree.root.visits
+        );
+    }
+
+    if let Some(pgn) = pgn {
+        std::fs::write(&pgn, tree.to_pgn())
+            .map_err(|err| format!("failed to write `{pgn}`: {err}"))?;
+    }
+
+    Ok(())
+}
+
+/// A score in pawns or as a mate, for the side to move at the root.
+fn value(score: i32) -> String {
+    match mate_in(score) {
+        Some(moves) => format!("#{moves}"),
+        None => format!("{:+.2}", score as f64 / 100.0),
+    }
+}
+
+/// Write the tree next to its file and move it over, so an interrupted save keeps the old one.
+fn save(tree: &AnalysisTree, path: &str) -> Result<(), String> {
+    let temporary = format!("{path}.tmp");
+
+    std::fs::write(&temporary, tree.to_string())
+        .and_then(|()| std::fs::rename(&temporary, path))
+        .map_err(|err| format!("failed to write `{path}`: {err}"))
+}
@@ -0,0 +1,129 @@
+//! `sealion benchsuite [--depth <n>] [--millis <n>]`: time each subsystem on a fixed set of
+//! positions and print a report that can be compared between builds.
+//!
+//! Sections: perft throughput, make/unmake and evaluation. Search will be added once there is
+//! one to measure.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use sealion::movegen::{perft, MoveList, PositionState};
+use sealion::Position;
+
+const USAGE: &str = "usage: sealion benchsuite [--depth <n>] [--millis <n>]";
+
+/// Standard positions, shared by every section.
+const POSITIONS: [(&str, &str); 3] = [
+    (
+        "start_pos",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ),
+    (
+        "pos_5",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ),
+];
+
+/// Run the benchsuite command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let (mut depth, mut millis) = (3, 1000);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| USAGE.to_owned())?;
+
+        match arg.as_str() {
+            "--depth" => depth = value as usize,
+            "--millis" => millis = value,
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let mut positions = POSITIONS
+        .iter()
+        .map(|(name, fen)| {
+            sealion::fen::from_str(fen)
+                .map(|position| (*name, position))
+                .map_err(|err| format!("invalid fen `{fen}`: {err}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let budget = Duration::from_millis(millis);
+
+    println!(
+        "{:<10} {:<12} {:>12} {:>10} {:>14}",
+        "section", "position", "count", "ms", "per sec"
+    );
+
+    for (name, position) in &mut positions {
+        let start = Instant::now();
+        let nodes = perft(position, depth);
+        report("perft", name, nodes, start.elapsed());
+    }
+
+    for (name, position) in &mut positions {
+        let (count, elapsed) = repeat(budget, || make_moves(position));
+        report("make", name, count, elapsed);
+    }
+
+    for (name, position) in &positions {
+        let (count, elapsed) = repeat(budget, || {
+            black_box(PositionState::generate(black_box(position)).score);
+            1
+        });
+        report("eval", name, count, elapsed);
+    }
+
+    Ok(())
+}
+
+fn report(section: &str, position: &str, count: u64, elapsed: Duration) {
+    let per_sec = count as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+
+    println!(
+        "{section:<10} {position:<12} {count:>12} {:>10} {per_sec:>14.0}",
+        elapsed.as_millis()
+    );
+}
+
+/// Call `f` until `budget` runs out, summing the counts it returns.
+fn repeat(budget: Duration, mut f: impl FnMut() -> u64) -> (u64, Duration) {
+    let start = Instant::now();
+    let mut count = 0;
+
+    while start.elapsed() < budget {
+        count += f();
+    }
+
+    (count, start.elapsed())
+}
+
+/// Make and unmake every legal move of a position once, returning how many were made.
+fn make_moves(position: &mut Position) -> u64 {
+    let state = PositionState::generate(position);
+    let moves = match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves,
+        _ => return 0,
+    };
+
+    for &p_move in &moves {
+        position.make_move_ext(black_box(p_move));
+        black_box(&position);
+        position.unmake_move();
+    }
+
+    moves.len() as u64
+}
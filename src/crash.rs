@@ -0,0 +1,150 @@
+//! Crash dumps: a panic anywhere writes what the engine was doing to
+//! `sealion-crash-<time>-<pid>.txt` in the working directory and exits.
+//!
+//! Only the commands that search install the hook. The one-shot ones keep the default panic,
+//! which is what e.g. a broken pipe in `sealion show ... | head` should give.
+//!
+//! Modes keep the context up to date as they go: the last commands, the position and the state of
+//! their subsystems. Exiting, also for a panic on a search thread, makes the GUI notice the crash
+//! instead of waiting for a `bestmove` that never comes.
+
+use std::backtrace::Backtrace;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::panic::PanicHookInfo;
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sealion::Position;
+
+/// Commands kept for the dump, older ones are dropped.
+const COMMANDS_KEPT: usize = 32;
+
+/// Exit code after a crash, the same as for an uncaught panic.
+const EXIT_CODE: i32 = 101;
+
+struct Context {
+    commands: VecDeque<String>,
+    /// The position as FEN and as a diagram, and the command that set it up, which has the
+    /// moves leading to it.
+    position: Option<(String, String, String)>,
+    /// Free form state by subsystem name, e.g. the options or the running search.
+    state: BTreeMap<&'static str, String>,
+}
+
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    commands: VecDeque::new(),
+    position: None,
+    state: BTreeMap::new(),
+});
+
+/// Write a dump and exit on any panic, after the usual message.
+pub fn install() {
+    let default = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        default(info);
+
+        let dump = dump(info);
+        match write(&dump) {
+            Ok(path) => eprintln!("crash report written to `{path}`"),
+            Err(err) => eprintln!("failed to write a crash report: {err}\n{dump}"),
+        }
+
+        std::process::exit(EXIT_CODE);
+    }));
+}
+
+/// Remember an input line, e.g. a UCI command.
+pub fn record_command(line: &str) {
+    let mut context = context();
+    if context.commands.len() == COMMANDS_KEPT {
+        context.commands.pop_front();
+    }
+    context.commands.push_back(line.to_owned());
+}
+
+/// Remember the position, and the `command` that set it up.
+pub fn set_position(position: &Position, command: &str) {
+    // written out now, the board may be broken by the time of a crash
+    let fen = position.to_fen();
+    let diagram = position.diagram().to_string();
+    context().position = Some((fen, diagram, command.to_owned()));
+}
+
+pub fn set_state(subsystem: &'static str, state: impl Display) {
+    context().state.insert(subsystem, state.to_string());
+}
+
+/// The context, also after a panic while it was locked.
+fn context() -> MutexGuard<'static, Context> {
+    CONTEXT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn dump(info: &PanicHookInfo<'_>) -> String {
+    let mut out = String::new();
+    let thread = std::thread::current();
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("(no message)");
+
+    // writing to a string can't fail
+    let _ = writeln!(
+        out,
+        "sealion {} crashed at {} seconds since the epoch",
+        env!("CARGO_PKG_VERSION"),
+        now()
+    );
+    let _ = writeln!(
+        out,
+        "thread `{}` panicked at {}:\n{message}\n",
+        thread.name().unwrap_or("<unnamed>"),
+        info.location()
+            .map_or("an unknown location".to_owned(), |location| location
+                .to_string())
+    );
+
+    // the panic may have happened while the context was being updated, on this thread it's
+    // still locked
+    let context = match CONTEXT.try_lock() {
+        Ok(context) => Some(context),
+        Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    };
+    match context {
+        Some(context) => {
+            if let Some((fen, diagram, command)) = &context.position {
+                let _ = writeln!(out, "position: {fen}\n{diagram}\nset up by: {command}\n");
+            }
+
+            out += "state:\n";
+            for (subsystem, state) in &context.state {
+                let _ = writeln!(out, "  {subsystem}: {state}");
+            }
+
+            out += "\nlast commands:\n";
+            for command in &context.commands {
+                let _ = writeln!(out, "  {command}");
+            }
+        }
+        None => out += "context unavailable, it was locked\n",
+    }
+
+    let _ = write!(out, "\nbacktrace:\n{}", Backtrace::force_capture());
+    out
+}
+
+fn write(dump: &str) -> std::io::Result<String> {
+    let path = format!("sealion-crash-{}-{}.txt", now(), std::process::id());
+    std::fs::write(&path, dump)?;
+    Ok(path)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
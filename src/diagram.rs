@@ -0,0 +1,64 @@
+//! `sealion diagram <fen> [-o <out.svg>] [options]`: draw a position as an SVG picture.
+
+use sealion::diagram::{svg, SvgOptions};
+use sealion::Move;
+
+const USAGE: &str = "usage: sealion diagram <fen> [-o <out.svg>] [--flip] [--no-coordinates] \
+[--last-move <uci>] [--arrow <uci>]... [--size <px>] [--light <color>] [--dark <color>]";
+
+/// Run the diagram command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut fen = vec![];
+    let mut output = None;
+    let mut options = SvgOptions::default();
+
+    let uci = |value: String| value.parse::<Move>().map_err(|_| USAGE.to_owned());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        // a lone `-` is an empty FEN field, not a flag
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value()?),
+            "--flip" => options.flipped = true,
+            "--no-coordinates" => options.coordinates = false,
+            "--last-move" => options.last_move = Some(uci(value()?)?),
+            "--arrow" => {
+                let Move { from, to, .. } = uci(value()?)?;
+                options.arrows.push((from, to));
+            }
+            "--size" => options.square_size = value()?.parse().map_err(|_| USAGE)?,
+            "--light" => options.light = value()?,
+            "--dark" => options.dark = value()?,
+            _ if arg == "-" || !arg.starts_with('-') => fen.push(arg.as_str()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    if fen.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+
+    let fen = fen.join(" ");
+    let position =
+        sealion::fen::from_str(&fen).map_err(|err| format!("invalid fen `{fen}`: {err}"))?;
+
+    let svg = svg(&position.board, &options);
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, svg).map_err(|err| format!("failed to write `{path}`: {err}"))?
+        }
+        None => print!("{svg}"),
+    }
+
+    Ok(())
+}
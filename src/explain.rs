@@ -0,0 +1,218 @@
+//! `sealion explain <fen>`: a quick "what does the engine see" report for a single position.
+
+use sealion::attacks::AttackMap;
+use sealion::eval::evaluate;
+use sealion::eval::king::{self, KingFeatures};
+use sealion::eval::pawns::{self, PawnFeatures};
+use sealion::movegen::{AttackTable, MoveList, PositionState};
+use sealion::psqt::{self, MAX_PHASE};
+use sealion::{BitBoard, Board, Color, IntoEnumIterator, MoveExt, Piece, PieceKind, Position};
+
+use PieceKind::*;
+
+/// Run the explain command with the remaining command line arguments (the fen).
+pub fn run(args: &[String]) {
+    let fen = args.join(" ");
+
+    let position = match sealion::fen::from_str(&fen) {
+        Ok(position) => position,
+        Err(err) => {
+            eprintln!("invalid fen `{fen}`: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(err) = position.validate() {
+        eprintln!("invalid position `{fen}`: {err}");
+        std::process::exit(1);
+    }
+
+    print!("{}", explain(&position));
+}
+
+/// Build the full textual report for a position.
+pub fn explain(position: &Position) -> String {
+    let mut out = String::new();
+    let state = PositionState::generate(position);
+
+    out += &format!("{}\n", position.diagram());
+
+    // -- moves
+    match MoveList::generate(&state) {
+        MoveList::Checkmate => out += "Checkmate\n",
+        MoveList::Stalemate => out += "Stalemate\n",
+        MoveList::Moves(moves) => {
+            let (mut captures, mut checks, mut quiets) = (vec![], vec![], vec![]);
+
+            for p_move in moves {
+                if p_move.capture.is_some() {
+                    captures.push(p_move);
+                } else if gives_check(position, p_move) {
+                    checks.push(p_move);
+                } else {
+                    quiets.push(p_move);
+                }
+            }
+
+            out += &move_line("Captures", &captures);
+            out += &move_line("Checks", &checks);
+            out += &move_line("Quiets", &quiets);
+        }
+    }
+    if position.is_insufficient_material() {
+        out += "Insufficient material\n";
+    }
+
+    // -- pieces
+    out += "\nHanging pieces:\n";
+    for color in Color::iter() {
+        out += &square_line(
+            color,
+            &position.board,
+            hanging_pieces(&position.board, color),
+        );
+    }
+
+    out += "\nPinned pieces:\n";
+    for color in Color::iter() {
+        out += &square_line(color, &position.board, position.pinned_pieces(color));
+    }
+
+    out += "\nPassed pawns:\n";
+    for color in Color::iter() {
+        let passed = PawnFeatures::new(&position.board, color).passed;
+        out += &square_line(color, &position.board, passed);
+    }
+
+    // -- king safety
+    out += "\nKing safety:\n";
+    let attack_map = AttackMap::new(&position.board);
+    for color in Color::iter() {
+        let king_bb = position.board.get_piece_bb(Piece { color, kind: King });
+
+        if king_bb.is_empty() {
+            out += &format!("  {}: no king\n", color_name(color));
+            continue;
+        }
+
+        let king_sq = king_bb.to_square_unchecked();
+        let features = KingFeatures::new(&position.board, &attack_map, color);
+        let enemy_attacks = attack_map.attacked_by(!color);
+        let zone = king::zone(king_sq, color);
+
+        out += &format!(
+            "  {}: king {}, shield missing {}, advanced {}, semi-open files {}, open files {}, \
+             attackers {} ({} units), attacked zone squares {}/{}, in check: {}\n",
+            color_name(color),
+            king_sq,
+            features.shield_missing,
+            features.shield_advanced,
+            features.semi_open_files,
+            features.open_files,
+            features.attackers,
+            features.attack_units,
+            (zone & enemy_attacks).count(),
+            zone.count(),
+            if king_bb & enemy_attacks != 0 {
+                "yes"
+            } else {
+                "no"
+            },
+        );
+    }
+
+    // -- eval
+    let board = &position.board;
+    let phase = psqt::phase(board);
+    out += &format!("\nEval (white's view, mg/eg, phase {phase}/{MAX_PHASE}):\n");
+    for (name, score) in [
+        ("material", board.material()),
+        ("piece-square", board.psqt()),
+        ("pawns", pawns::evaluate_pawns(board)),
+        (
+            "king safety",
+            king::evaluate_king_safety(board, &attack_map),
+        ),
+    ] {
+        out += &format!(
+            "  {name}: {}/{} = {}\n",
+            score.mg,
+            score.eg,
+            score.taper(phase)
+        );
+    }
+    out += &format!("  total (side to move): {}\n", evaluate(position));
+
+    out
+}
+
+#[inline]
+pub(crate) fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+fn move_line(name: &str, moves: &[MoveExt]) -> String {
+    let moves = moves.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+    format!("{name} ({}): {}\n", moves.len(), moves.join(" "))
+}
+
+fn square_line(color: Color, board: &Board, squares: BitBoard) -> String {
+    let pieces = squares
+        .set_iter()
+        .filter_map(|sq| {
+            board
+                .get(sq)
+                .map(|piece| format!("{}{sq}", piece.as_char()))
+        })
+        .collect::<Vec<_>>();
+
+    if pieces.is_empty() {
+        format!("  {}: none\n", color_name(color))
+    } else {
+        format!("  {}: {}\n", color_name(color), pieces.join(" "))
+    }
+}
+
+/// Check if playing this move puts the opponent in check.
+fn gives_check(position: &Position, p_move: MoveExt) -> bool {
+    let king_bb = position.board.get_piece_bb(Piece {
+        color: !position.active_color,
+        kind: King,
+    });
+
+    p_move
+        .to_move()
+        .resulting_attacks(position)
+        .intersects(king_bb)
+}
+
+/// All squares attacked by the pieces of one side.
+pub(crate) fn attacks_by(board: &Board, color: Color) -> BitBoard {
+    AttackMap::new(board).attacked_by(color)
+}
+
+/// Pieces (other than the king) attacked by the opponent and either not defended or attacked by
+/// a cheaper piece.
+pub(crate) fn hanging_pieces(board: &Board, color: Color) -> BitBoard {
+    let king_bb = board.get_piece_bb(Piece { color, kind: King });
+    let table = AttackTable::generate(board);
+    let mut hanging = BitBoard::ZERO;
+
+    for square in (board.get_color_bb(color) & !king_bb).set_iter() {
+        let Some(attacker) = table.least_valuable(square, !color) else {
+            continue;
+        };
+        let Some(kind) = board.get_piece_kind(square) else {
+            continue;
+        };
+
+        if table.attackers(square, color) == 0 || attacker.score() < kind.score() {
+            hanging |= BitBoard::from_square(square);
+        }
+    }
+
+    hanging
+}
@@ -0,0 +1,241 @@
+//! `sealion fuzz-movegen [--games <n>] [--plies <n>] [--seed <n>]`: play random legal games and
+//! check move generation invariants on every ply.
+//!
+//! Each ply asserts that the board masks are consistent, that the position passes
+//! [`Position::validate`], that filtering pseudo-legal moves by king safety gives exactly the legal
+//! generator's output and that unmaking the move restores the position.
+
+use sealion::movegen::{Generator, MoveList, PositionState, Rng};
+use sealion::{CastlingRights, Color, MoveExt, Piece, PieceKind, Position, Square};
+
+use crate::explain::attacks_by;
+
+const USAGE: &str = "usage: sealion fuzz-movegen [--games <n>] [--plies <n>] [--seed <n>]";
+
+/// Run the fuzz-movegen command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let (mut games, mut plies, mut seed) = (1000, 200, 0x5EA1_1011);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| USAGE.to_owned())?;
+
+        match arg.as_str() {
+            "--games" => games = value,
+            "--plies" => plies = value,
+            "--seed" => seed = value,
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut total_plies = 0;
+
+    for game in 0..games {
+        let mut position = Position::starting();
+        let mut history = vec![];
+
+        for _ in 0..plies {
+            if let Err(err) = check_invariants(&position) {
+                let history = history.iter().map(ToString::to_string).collect::<Vec<_>>();
+                return Err(format!(
+                    "game {game}: {err}\nmoves: {}\n{}",
+                    history.join(" "),
+                    position.diagram()
+                ));
+            }
+
+            let state = PositionState::generate(&position);
+            let moves = match MoveList::generate(&state) {
+                MoveList::Moves(moves) => moves,
+                _ => break,
+            };
+
+            let p_move = moves[rng.below(moves.len())];
+            let before = position.clone();
+            position.make_move_ext(p_move);
+            history.push(p_move.to_move());
+
+            if let Err(err) = check_unmake(&before, &mut position) {
+                return Err(format!(
+                    "game {game}: {err} after {p_move}\n{}",
+                    before.diagram()
+                ));
+            }
+            total_plies += 1;
+        }
+    }
+
+    println!("{games} games, {total_plies} plies: all invariants held");
+    Ok(())
+}
+
+/// Unmaking the last move has to give back the exact position and key, then redo it.
+fn check_unmake(before: &Position, position: &mut Position) -> Result<(), String> {
+    let p_move = position.unmake_move().ok_or("nothing to unmake")?;
+
+    if position != before || position.zobrist_key() != before.zobrist_key() {
+        return Err(format!("unmake gave {}", position.to_fen()));
+    }
+    if position.zobrist_key() != position.compute_zobrist_key() {
+        return Err("incremental zobrist key differs from scratch".to_owned());
+    }
+
+    position.make_move_ext(p_move);
+    Ok(())
+}
+
+fn check_invariants(position: &Position) -> Result<(), String> {
+    let board = &position.board;
+
+    if !board.is_consistent() {
+        return Err("board masks are inconsistent".to_owned());
+    }
+
+    position.validate().map_err(|err| err.to_string())?;
+
+    let state = PositionState::generate(position);
+    let mut legal = match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves.iter().map(move_key).collect(),
+        _ => vec![],
+    };
+    let mut reference = reference_moves(&state);
+
+    legal.sort_unstable();
+    reference.sort_unstable();
+
+    if legal != reference {
+        return Err(format!(
+            "legal generator disagrees with filtered pseudo-legal moves\n  only legal: {}\n  only pseudo-legal: {}",
+            difference(&legal, &reference),
+            difference(&reference, &legal),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Moves in `moves` without a counterpart in `other`, counting duplicates.
+fn difference(moves: &[(u8, u8, u8)], other: &[(u8, u8, u8)]) -> String {
+    let mut other = other.to_vec();
+    let mut only = vec![];
+
+    for m in moves {
+        match other.iter().position(|o| o == m) {
+            Some(i) => {
+                other.swap_remove(i);
+            }
+            None => only.push(format!(
+                "{}{}",
+                Square::from_index_unchecked(m.0),
+                Square::from_index_unchecked(m.1)
+            )),
+        }
+    }
+
+    only.join(" ")
+}
+
+#[inline]
+fn move_key(p_move: &MoveExt) -> (u8, u8, u8) {
+    (
+        p_move.from.raw_index(),
+        p_move.to.raw_index(),
+        p_move.promotion.map_or(0, |kind| kind as u8 + 1),
+    )
+}
+
+/// Pseudo-legal moves filtered by whether they leave the mover's king attacked.
+fn reference_moves(state: &PositionState) -> Vec<(u8, u8, u8)> {
+    let position = state.position;
+    let color = position.active_color;
+    let generator = Generator::new(state);
+    let mut candidates = vec![];
+
+    for from in position.board.get_color_bb(color).set_iter() {
+        let kind = position.board.get_piece_kind(from).unwrap();
+
+        for to in generator.pseudo_moves(from, kind).set_iter() {
+            let p_move = MoveExt {
+                piece_kind: kind,
+                from,
+                to,
+                promotion: None,
+                capture: if kind == PieceKind::Pawn {
+                    state.resolve_capture(to)
+                } else {
+                    state.resolve_capture_only(to)
+                },
+            };
+
+            if kind == PieceKind::Pawn && (to.rank() == 0 || to.rank() == 7) {
+                for promotion in PieceKind::PROMOTABLE {
+                    candidates.push(MoveExt {
+                        promotion: Some(promotion),
+                        ..p_move
+                    });
+                }
+            } else {
+                candidates.push(p_move);
+            }
+        }
+    }
+
+    // castling: king not in check, path not attacked, squares between empty and rook in place
+    let enemy_attacks = attacks_by(&position.board, !color);
+    let (rank, oo, ooo) = match color {
+        Color::White => (0, CastlingRights::WHITE_OO, CastlingRights::WHITE_OOO),
+        Color::Black => (7, CastlingRights::BLACK_OO, CastlingRights::BLACK_OOO),
+    };
+    let at = |file| Square::at(rank, file).unwrap();
+    let rook = Some(Piece {
+        color,
+        kind: PieceKind::Rook,
+    });
+
+    for (right, rook_file, between, path) in [
+        (oo, 7, &[5, 6][..], &[4, 5, 6][..]),
+        (ooo, 0, &[1, 2, 3][..], &[2, 3, 4][..]),
+    ] {
+        if position.castling.contains(right)
+            && position.board.get(at(rook_file)) == rook
+            && between
+                .iter()
+                .all(|&file| position.board.get(at(file)).is_none())
+            && path.iter().all(|&file| !enemy_attacks.get(at(file)))
+        {
+            candidates.push(MoveExt {
+                piece_kind: PieceKind::King,
+                from: at(4),
+                to: at(if rook_file == 7 { 6 } else { 2 }),
+                promotion: None,
+                capture: None,
+            });
+        }
+    }
+
+    candidates
+        .iter()
+        .filter(|p_move| {
+            let mut next = position.clone();
+            next.apply_move_unchecked(**p_move);
+
+            let king = next.board.get_piece_bb(Piece {
+                color,
+                kind: PieceKind::King,
+            });
+            king & attacks_by(&next.board, !color) == 0
+        })
+        .map(move_key)
+        .collect()
+}
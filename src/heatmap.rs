@@ -0,0 +1,139 @@
+//! `sealion heatmap [--placement] [--format <json|svg>] [-o <out>] <fen>`: what each piece adds to
+//! the static evaluation.
+//!
+//! Values are from white's point of view, blended by game phase like the evaluation, and add up to
+//! the material and placement part of it, so a black piece is negative. `--placement` leaves out material to show only how well pieces stand.
+//! The SVG tints squares green where white gains and red where black does.
+
+use sealion::psqt;
+
+const USAGE: &str = "usage: sealion heatmap [--placement] [--format <json|svg>] [-o <out>] <fen>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    #[cfg(feature = "diagram")]
+    Svg,
+}
+
+/// Run the heatmap command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut placement = false;
+    let mut format = Format::Json;
+    let mut output = None;
+    let mut fen = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        // a lone `-` is an empty FEN field, not a flag
+        match arg.as_str() {
+            "--placement" => placement = true,
+            "--format" => {
+                format = match value()?.as_str() {
+                    "json" => Format::Json,
+                    #[cfg(feature = "diagram")]
+                    "svg" => Format::Svg,
+                    _ => return Err(USAGE.to_owned()),
+                }
+            }
+            "-o" | "--output" => output = Some(value()?),
+            _ if arg == "-" || !arg.starts_with('-') => fen.push(arg.as_str()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    if fen.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+
+    let fen = fen.join(" ");
+    let position =
+        sealion::fen::from_str(&fen).map_err(|err| format!("invalid fen `{fen}`: {err}"))?;
+
+    let phase = psqt::phase(&position.board);
+    let values = psqt::by_square(&position.board).map(|(material, piece_square)| match placement {
+        true => piece_square.taper(phase),
+        false => (material + piece_square).taper(phase),
+    });
+
+    let out = match format {
+        Format::Json => json(&fen, &values),
+        #[cfg(feature = "diagram")]
+        Format::Svg => svg(&position.board, &values),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, out).map_err(|err| format!("failed to write `{path}`: {err}"))?
+        }
+        None => print!("{out}"),
+    }
+
+    Ok(())
+}
+
+/// `{"fen":..,"total":..,"squares":[..]}` on a single line, with 64 values from a1, b1 to h8.
+fn json(fen: &str, values: &[i32; 64]) -> String {
+    let squares = values.map(|value| value.to_string());
+
+    format!(
+        "{{\"fen\":\"{fen}\",\"total\":{},\"squares\":[{}]}}\n",
+        values.iter().sum::<i32>(),
+        squares.join(",")
+    )
+}
+
+#[cfg(feature = "diagram")]
+fn svg(board: &sealion::Board, values: &[i32; 64]) -> String {
+    use sealion::diagram::SvgOptions;
+
+    let max = values
+        .iter()
+        .map(|value| value.abs())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let fills = board
+        .get_full_bb()
+        .into_iter()
+        .map(|square| {
+            let value = values[square.raw_index() as usize];
+            let target = match value >= 0 {
+                true => (0x2e, 0x8b, 0x57),
+                false => (0xc0, 0x39, 0x2b),
+            };
+
+            (square, tint(target, value.abs() as f64 / max as f64))
+        })
+        .collect();
+
+    sealion::diagram::svg(
+        board,
+        &SvgOptions {
+            fills,
+            ..SvgOptions::default()
+        },
+    )
+}
+
+/// A color between white and `target`, `strength` from 0 to 1.
+#[cfg(feature = "diagram")]
+fn tint(target: (u8, u8, u8), strength: f64) -> String {
+    let mix = |channel: u8| (255.0 - (255.0 - channel as f64) * strength).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(target.0),
+        mix(target.1),
+        mix(target.2)
+    )
+}
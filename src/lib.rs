@@ -0,0 +1,64 @@
+//! Sealion chess engine.
+//!
+//! Stable entry point to the engine's public API. Everything is re-exported from the internal
+//! crates under fixed paths, so downstream users don't depend on how those crates are laid out.
+
+#[cfg(feature = "diagram")]
+pub use sealion_board::diagram;
+pub use sealion_board::{attacks, psqt, zobrist};
+pub use sealion_board::{
+    BitBoard, Board, Capture, Castle, CastlingRights, Color, Diagram, EnumCount, FenPlacement,
+    IntoEnumIterator, InvalidPosition, Move, MoveExt, NullUndo, Overlay, OverlayView, Piece,
+    PieceKind, Position, Square, Tint, Undo,
+};
+
+/// Bitboard helpers.
+pub mod bitboard {
+    pub use sealion_board::batch;
+    pub use sealion_board::bitboard::{constants, SetIter};
+}
+
+/// FEN parsing.
+pub mod fen {
+    pub use sealion_fen::de::parse;
+    pub use sealion_fen::{canonicalize, from_str};
+}
+
+/// Legal move generation.
+pub mod movegen {
+    pub use sealion_engine::compress::{decode as decode_moves, encode as encode_moves};
+    pub use sealion_engine::legality::{check_move, Illegal};
+    pub use sealion_engine::movegen::{merge_bb, Generator, MoveList};
+    pub use sealion_engine::perft::{perft, perft_divide};
+    pub use sealion_engine::random::{random_position, Rng};
+    pub use sealion_engine::san::{parse_san, to_san, SanError};
+    pub use sealion_engine::state::PositionState;
+    pub use sealion_engine::status::{GameStatus, Termination};
+    pub use sealion_engine::threats::{is_unsafe_quiet, AttackTable};
+}
+
+/// Static evaluation.
+pub mod eval {
+    pub use sealion_eval::{evaluate, king, params, pawns};
+}
+
+/// Alpha-beta search.
+pub mod search {
+    pub use sealion_search::time::{GoClocks, TimeManager, MOVE_OVERHEAD};
+    pub use sealion_search::tree::{AnalysisTree, Node, CRITICAL_MARGIN};
+    pub use sealion_search::tt::{Bound, Entry, TranspositionTable, DEFAULT_HASH};
+    pub use sealion_search::{mate_in, Limits, SearchResult, Searcher, MATE, MATE_BOUND, MAX_PLY};
+}
+
+/// Scores as shown to users.
+pub mod score {
+    pub use sealion_engine::score::Calibration;
+}
+
+/// PGN reading, parsing and filtering, and time controls.
+pub mod pgn {
+    pub use sealion_pgn::{
+        parse_parallel, parse_tag, Bonus, Clock, EcoRange, Filter, Flagged, Game, GameError,
+        GameMove, GameReader, GameResult, ParallelGames, ParsedGame, Period, RawGame, TimeControl,
+    };
+}
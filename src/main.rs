@@ -1,24 +1,37 @@
-use std::io::stdin;
-
-use sealion_engine::movegen::MoveList;
-use sealion_engine::state::PositionState;
+mod analyze_tree;
+mod benchsuite;
+mod crash;
+#[cfg(feature = "diagram")]
+mod diagram;
+mod explain;
+mod fuzz;
+mod heatmap;
+mod options;
+mod perft;
+mod pgn_filter;
+mod proxy;
+mod show;
+mod train;
+mod tune;
+mod uci;
 
 fn main() {
-    println!("Position fen: ");
-    let mut fen = String::new();
-    stdin().read_line(&mut fen).unwrap();
-
-    let position = sealion_fen::from_str(&fen).unwrap();
-    let state = PositionState::generate(&position);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
 
-    match MoveList::generate(&state) {
-        MoveList::Checkmate => println!("Checkmate"),
-        MoveList::Stalemate => println!("Stalemate"),
-        MoveList::Moves(moves) => {
-            for p_move in &moves {
-                println!("{p_move}")
-            }
-            println!("{} legal moves", moves.len());
-        }
+    match args.first().map(String::as_str) {
+        Some("analyze-tree") => analyze_tree::run(&args[1..]),
+        Some("benchsuite") => benchsuite::run(&args[1..]),
+        #[cfg(feature = "diagram")]
+        Some("diagram") => diagram::run(&args[1..]),
+        Some("explain") => explain::run(&args[1..]),
+        Some("fuzz-movegen") => fuzz::run(&args[1..]),
+        Some("heatmap") => heatmap::run(&args[1..]),
+        Some("perft") => perft::run(&args[1..]),
+        Some("pgn-filter") => pgn_filter::run(&args[1..]),
+        Some("proxy") => proxy::run(&args[1..]),
+        Some("show") => show::run(&args[1..]),
+        Some("train") => train::run(&args[1..]),
+        Some("tune") => tune::run(&args[1..]),
+        _ => uci::run(&args),
     }
 }
@@ -0,0 +1,102 @@
+//! UCI options and named presets of them, picked with `sealion --profile <name>`.
+//!
+//! A profile only changes the starting values, `setoption` still overrides any of them. `Ponder`
+//! and `MultiPV` aren't declared until the search supports them, a GUI would rely on them.
+
+/// Names accepted by `--profile`.
+pub const PROFILES: [&str; 3] = ["play", "analysis", "bullet"];
+
+/// Largest `Hash` in MiB, so a typo can't take all of the machine's memory.
+pub const MAX_HASH: u32 = 4096;
+
+/// Values of the options advertised on `uci`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Castling is written as king takes rook.
+    pub chess960: bool,
+    /// Hash table size in MiB.
+    pub hash: u32,
+    /// Report win/draw/loss estimates along with the score.
+    pub show_wdl: bool,
+    /// Milliseconds kept in reserve per move for communication lag.
+    pub move_overhead: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            chess960: false,
+            hash: 16,
+            show_wdl: false,
+            move_overhead: 30,
+        }
+    }
+}
+
+impl Options {
+    /// The options of a named profile, see [`PROFILES`].
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "play" => Some(Self::default()),
+            "analysis" => Some(Self {
+                hash: 256,
+                show_wdl: true,
+                ..Self::default()
+            }),
+            "bullet" => Some(Self {
+                move_overhead: 10,
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The `option` lines of the `uci` reply, with the current values as defaults.
+    pub fn declarations(&self) -> Vec<String> {
+        vec![
+            format!(
+                "option name Hash type spin default {} min 1 max {MAX_HASH}",
+                self.hash
+            ),
+            format!(
+                "option name UCI_ShowWDL type check default {}",
+                self.show_wdl
+            ),
+            format!(
+                "option name Move Overhead type spin default {} min 0 max 5000",
+                self.move_overhead
+            ),
+            format!(
+                "option name UCI_Chess960 type check default {}",
+                self.chess960
+            ),
+        ]
+    }
+
+    /// Apply a `setoption`, names are matched case insensitively as the protocol asks.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let check = || match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(format!(
+                "option `{name}` expects true or false, got `{value}`"
+            )),
+        };
+        let spin = |min: u32, max: u32| match value.parse::<u32>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!(
+                "option `{name}` expects a number from {min} to {max}, got `{value}`"
+            )),
+        };
+
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => self.hash = spin(1, MAX_HASH)?,
+            "uci_showwdl" => self.show_wdl = check()?,
+            "move overhead" => self.move_overhead = spin(0, 5000)?,
+            "uci_chess960" => self.chess960 = check()?,
+            _ => return Err(format!("unknown option `{name}`")),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! `sealion perft [--format <text|tsv|json>] <depth> <fen>`: count move paths and print the divide
+//! per root move.
+//!
+//! The `tsv` and `json` formats are for diffing against other engines from scripts: one
+//! `move<TAB>nodes` line per root move, or an object with the total and the divide keyed by move.
+//! Neither includes timings, so the output is the same on every run.
+
+use std::time::Instant;
+
+use sealion::movegen::perft_divide;
+use sealion::MoveExt;
+
+const USAGE: &str = "usage: sealion perft [--format <text|tsv|json>] <depth> <fen>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Tsv,
+    Json,
+}
+
+/// Run the perft command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let (format, args) = match args {
+        [flag, format, rest @ ..] if flag == "--format" => {
+            let format = match format.as_str() {
+                "text" => Format::Text,
+                "tsv" => Format::Tsv,
+                "json" => Format::Json,
+                _ => return Err(USAGE.to_owned()),
+            };
+            (format, rest)
+        }
+        _ => (Format::Text, args),
+    };
+
+    let (depth, fen) = args.split_first().ok_or_else(|| USAGE.to_owned())?;
+    let depth = depth.parse::<usize>().map_err(|_| USAGE.to_owned())?;
+
+    let fen = fen.join(" ");
+    let mut position =
+        sealion::fen::from_str(&fen).map_err(|err| format!("invalid fen `{fen}`: {err}"))?;
+    position
+        .validate()
+        .map_err(|err| format!("invalid position `{fen}`: {err}"))?;
+
+    let start = Instant::now();
+    let divide = perft_divide(&mut position, depth);
+    let elapsed = start.elapsed();
+
+    let nodes = match depth {
+        0 => 1,
+        _ => divide.iter().map(|(_, nodes)| nodes).sum(),
+    };
+
+    match format {
+        Format::Text => {
+            for (p_move, nodes) in &divide {
+                println!("{}: {nodes}", p_move.to_move());
+            }
+
+            println!();
+            println!("Nodes searched: {nodes}");
+            println!(
+                "Time: {} ms ({:.0} nps)",
+                elapsed.as_millis(),
+                nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+            );
+        }
+        Format::Tsv => {
+            for (p_move, nodes) in &divide {
+                println!("{}\t{nodes}", p_move.to_move());
+            }
+        }
+        Format::Json => println!("{}", json(&fen, depth, nodes, &divide)),
+    }
+
+    Ok(())
+}
+
+/// A single line object, UCI moves and FENs never need escaping.
+fn json(fen: &str, depth: usize, nodes: u64, divide: &[(MoveExt, u64)]) -> String {
+    let divide = divide
+        .iter()
+        .map(|(p_move, nodes)| format!("\"{}\":{nodes}", p_move.to_move()))
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"fen\":\"{fen}\",\"depth\":{depth},\"nodes\":{nodes},\"divide\":{{{}}}}}",
+        divide.join(",")
+    )
+}
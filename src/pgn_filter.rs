@@ -0,0 +1,69 @@
+//! `sealion pgn-filter <input.pgn> [options]`: stream a multi-game PGN and write out matching games.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::str::FromStr;
+
+use sealion::pgn::{EcoRange, Filter, GameReader};
+
+const USAGE: &str = "usage: sealion pgn-filter <input.pgn> [-o <output.pgn>] [--min-elo <n>] \
+[--max-elo <n>] [--time-control <tc>] [--result <result>] [--eco <A00-E99>] [--player <name>]";
+
+/// Run the pgn-filter command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut input = None;
+    let mut output = None;
+    let mut filter = Filter::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value()?),
+            "--min-elo" => filter.min_elo = Some(value()?.parse().map_err(|_| USAGE)?),
+            "--max-elo" => filter.max_elo = Some(value()?.parse().map_err(|_| USAGE)?),
+            "--time-control" => filter.time_control = Some(value()?),
+            "--result" => filter.result = Some(value()?),
+            "--eco" => filter.eco = Some(EcoRange::from_str(&value()?).map_err(|_| USAGE)?),
+            "--player" => filter.player = Some(value()?),
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg.clone()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let input = input.ok_or_else(|| USAGE.to_owned())?;
+    let reader = File::open(&input).map_err(|err| format!("failed to open `{input}`: {err}"))?;
+
+    let mut writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|err| format!("failed to create `{path}`: {err}"))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let (mut total, mut matched) = (0, 0);
+
+    for game in GameReader::new(BufReader::new(reader)) {
+        let game = game.map_err(|err| format!("failed to read `{input}`: {err}"))?;
+        total += 1;
+
+        if filter.matches(&game) {
+            matched += 1;
+            write!(writer, "{}", game.text.trim_end()).map_err(|err| err.to_string())?;
+            writeln!(writer, "\n").map_err(|err| err.to_string())?;
+        }
+    }
+
+    writer.flush().map_err(|err| err.to_string())?;
+    eprintln!("{matched}/{total} games matched");
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+//! `sealion proxy --engine <path>`: sit between a GUI and another engine, relaying and logging
+//! both directions of the conversation.
+//!
+//! Lines are relayed untouched, but are also read with the parsers of the UCI loop so that the
+//! log points out what the engine would have choked on: malformed `position`, `go` and
+//! `setoption` commands, illegal `bestmove`s, and how long each `go` took to answer.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sealion::Position;
+
+use crate::uci;
+
+const USAGE: &str = "usage: sealion proxy --engine <path> [--log <file>] [--latency <ms>] \
+[-- <engine args>...]";
+
+/// Shared, timestamped log of both directions.
+#[derive(Clone)]
+struct Log {
+    start: Instant,
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl Log {
+    fn line(&self, direction: &str, line: &str) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let mut out = self.out.lock().unwrap();
+        // logging failures should never break the relay
+        let _ = writeln!(out, "[{elapsed:>10.3}] {direction} {line}");
+        let _ = out.flush();
+    }
+}
+
+/// What the GUI has asked for so far, to make sense of the engine's replies.
+struct Session {
+    position: Position,
+    go: Option<Instant>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Session {
+            position: Position::starting(),
+            go: None,
+        }
+    }
+
+    /// Note about a GUI command, if it is worth one.
+    fn command(&mut self, line: &str) -> Option<String> {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next()? {
+            "position" => match uci::parse_position(tokens) {
+                Ok(position) => self.position = position,
+                Err(err) => return Some(format!("bad position: {err}")),
+            },
+            "go" => {
+                if let Err(err) = uci::parse_go(tokens) {
+                    return Some(format!("bad go: {err}"));
+                }
+                self.go = Some(Instant::now());
+            }
+            "setoption" if uci::parse_option(tokens).is_none() => {
+                return Some("bad setoption: expected `name`".to_owned());
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Note about an engine reply, if it is worth one.
+    fn reply(&mut self, line: &str) -> Option<String> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next()? != "bestmove" {
+            return None;
+        }
+
+        let answer = match self.go.take() {
+            Some(go) => format!("bestmove after {} ms", go.elapsed().as_millis()),
+            None => "bestmove without a go".to_owned(),
+        };
+
+        match tokens.next() {
+            // no legal move to play
+            Some("0000" | "(none)") => Some(answer),
+            Some(token) => match uci::parse_moves(self.position.clone(), std::iter::once(token)) {
+                Ok(_) => Some(answer),
+                Err(err) => Some(format!("{answer}, {err}")),
+            },
+            None => Some(format!("{answer}, missing move")),
+        }
+    }
+}
+
+/// Run the proxy command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut engine = None;
+    let mut log_path = None;
+    let mut latency = Duration::ZERO;
+    let mut engine_args = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        match arg.as_str() {
+            "--engine" => engine = Some(value()?),
+            "--log" => log_path = Some(value()?),
+            "--latency" => latency = Duration::from_millis(value()?.parse().map_err(|_| USAGE)?),
+            "--" => {
+                engine_args.extend(args.by_ref().cloned());
+                break;
+            }
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let engine = engine.ok_or_else(|| USAGE.to_owned())?;
+
+    let out: Box<dyn Write + Send> = match &log_path {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|err| format!("failed to create `{path}`: {err}"))?,
+        )),
+        None => Box::new(io::stderr()),
+    };
+    let log = Log {
+        start: Instant::now(),
+        out: Arc::new(Mutex::new(out)),
+    };
+
+    let mut child = Command::new(&engine)
+        .args(&engine_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to start `{engine}`: {err}"))?;
+
+    let mut engine_in = child.stdin.take().unwrap();
+    let engine_out = child.stdout.take().unwrap();
+    let session = Arc::new(Mutex::new(Session::new()));
+
+    // GUI -> engine
+    let gui_log = log.clone();
+    let gui_session = session.clone();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            gui_log.line(">", &line);
+            thread::sleep(latency);
+
+            // noted once sent, so that `go` timings leave the injected latency out
+            let note = gui_session.lock().unwrap().command(&line);
+            if writeln!(engine_in, "{line}")
+                .and_then(|_| engine_in.flush())
+                .is_err()
+            {
+                break;
+            }
+            if let Some(note) = note {
+                gui_log.line("#", &note);
+            }
+        }
+        // dropping stdin lets the engine see eof
+    });
+
+    // engine -> GUI
+    let mut stdout = io::stdout().lock();
+    for line in BufReader::new(engine_out).lines() {
+        let line = line.map_err(|err| format!("failed to read from engine: {err}"))?;
+        log.line("<", &line);
+        if let Some(note) = session.lock().unwrap().reply(&line) {
+            log.line("#", &note);
+        }
+        thread::sleep(latency);
+
+        writeln!(stdout, "{line}")
+            .and_then(|_| stdout.flush())
+            .map_err(|err| format!("failed to write to gui: {err}"))?;
+    }
+
+    let status = child.wait().map_err(|err| err.to_string())?;
+    log.line("#", &format!("engine exited with {status}"));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let mut session = Session::new();
+
+        assert_eq!(session.command("isready"), None);
+        assert_eq!(session.command("setoption name Hash value 16"), None);
+        assert!(session.command("setoption Hash 16").is_some());
+
+        assert_eq!(session.command("position startpos moves e2e4"), None);
+        assert!(session.command("position startpos moves e2e5").is_some());
+        // a bad position leaves the last good one
+        assert_eq!(session.position.to_fen().split(' ').nth(1), Some("b"));
+
+        assert!(session.command("go depth x").is_some());
+        assert!(session.go.is_none());
+        assert_eq!(session.command("go depth 3"), None);
+        assert!(session.go.is_some());
+    }
+
+    #[test]
+    fn replies() {
+        let mut session = Session::new();
+
+        assert_eq!(session.reply("info depth 1"), None);
+        assert_eq!(
+            session.reply("bestmove e2e4").as_deref(),
+            Some("bestmove without a go")
+        );
+
+        session.command("position startpos moves e2e4");
+        session.command("go movetime 10");
+        let note = session.reply("bestmove e2e4 ponder d7d5").unwrap();
+        assert!(note.starts_with("bestmove after"), "{note}");
+        assert!(note.ends_with("illegal move `e2e4`"), "{note}");
+
+        session.command("go movetime 10");
+        let note = session.reply("bestmove e7e5").unwrap();
+        assert!(note.ends_with(" ms"), "{note}");
+        assert!(session.go.is_none());
+    }
+}
@@ -0,0 +1,102 @@
+//! `sealion show <fen> [<layer> <color>]...`: render a position with bitboard overlays.
+//!
+//! Layers are `attacks`, `hanging`, `pins`, `passed` and `mobility`, each for `white` or `black`.
+
+use std::io::IsTerminal;
+
+use sealion::eval::pawns::PawnFeatures;
+use sealion::movegen::{MoveList, PositionState};
+use sealion::{BitBoard, Color, Overlay, Position, Tint};
+
+use crate::explain::{attacks_by, color_name, hanging_pieces};
+
+const USAGE: &str = "usage: sealion show <fen> [<attacks|hanging|pins|passed|mobility> \
+<white|black>]...";
+
+const LAYERS: [&str; 5] = ["attacks", "hanging", "pins", "passed", "mobility"];
+const MARKERS: [(char, Tint); 6] = [
+    ('*', Tint::Red),
+    ('+', Tint::Green),
+    ('x', Tint::Yellow),
+    ('o', Tint::Blue),
+    ('#', Tint::Magenta),
+    ('@', Tint::Cyan),
+];
+
+/// Run the show command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let fen_len = args
+        .iter()
+        .position(|arg| LAYERS.contains(&arg.as_str()))
+        .unwrap_or(args.len());
+    let fen = args[..fen_len].join(" ");
+
+    let position = sealion::fen::from_str(&fen).map_err(|err| format!("invalid fen: {err}"))?;
+    position
+        .validate()
+        .map_err(|err| format!("invalid position `{fen}`: {err}"))?;
+    let tinted = std::io::stdout().is_terminal();
+
+    let mut overlays = vec![];
+    let mut legend = vec![];
+
+    for (i, layer) in args[fen_len..].chunks(2).enumerate() {
+        let [name, color] = layer else {
+            return Err(USAGE.to_owned());
+        };
+        let color = match color.as_str() {
+            "white" | "w" => Color::White,
+            "black" | "b" => Color::Black,
+            _ => return Err(USAGE.to_owned()),
+        };
+
+        let squares = match name.as_str() {
+            "attacks" => attacks_by(&position.board, color),
+            "hanging" => hanging_pieces(&position.board, color),
+            "pins" => position.pinned_pieces(color),
+            "passed" => PawnFeatures::new(&position.board, color).passed,
+            "mobility" => mobility(&position, color),
+            _ => return Err(USAGE.to_owned()),
+        };
+
+        let (marker, tint) = MARKERS[i % MARKERS.len()];
+        overlays.push(Overlay {
+            squares,
+            marker,
+            tint: tinted.then_some(tint),
+        });
+        legend.push(format!("{marker} {name} {}", color_name(color)));
+    }
+
+    print!("{}", position.board.with_overlays(&overlays));
+    for line in legend {
+        println!("{line}");
+    }
+
+    Ok(())
+}
+
+/// Destination squares of all legal moves for one side.
+fn mobility(position: &Position, color: Color) -> BitBoard {
+    let mut position = position.clone();
+    // an en passant target only belongs to the side that was to move
+    if position.active_color != color {
+        position.active_color = color;
+        position.ep_target = None;
+    }
+    let state = PositionState::generate(&position);
+
+    match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves.iter().fold(BitBoard::ZERO, |bb, p_move| {
+            bb | BitBoard::from_square(p_move.to)
+        }),
+        _ => BitBoard::ZERO,
+    }
+}
@@ -0,0 +1,182 @@
+//! `sealion train <coordinates|blindfold> ...`: small trainers for club players.
+//!
+//! `coordinates` marks a square on an unlabelled board and asks for its name. `blindfold` reads
+//! out the moves of a game one at a time without showing the board, asking about a square shows
+//! what stands on it and `board` gives up and shows the position.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sealion::movegen::Rng;
+use sealion::pgn::{Game, GameReader};
+use sealion::{BitBoard, Board, Color, Overlay, Position, Square};
+
+use crate::explain::color_name;
+
+const USAGE: &str = "usage: sealion train coordinates [--rounds <n>] [--seed <n>]\n       \
+sealion train blindfold <game.pgn> [--game <n>]";
+
+/// Run the train command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        Some((trainer, args)) if trainer == "coordinates" => coordinates(args),
+        Some((trainer, args)) if trainer == "blindfold" => blindfold(args),
+        _ => Err(USAGE.to_owned()),
+    }
+}
+
+fn coordinates(args: &[String]) -> Result<(), String> {
+    let mut rounds = 10;
+    let mut seed = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| USAGE.to_owned())?;
+
+        match arg.as_str() {
+            "--rounds" => rounds = value.parse().map_err(|_| USAGE)?,
+            "--seed" => seed = Some(value.parse().map_err(|_| USAGE)?),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let mut rng = Rng::new(seed);
+    let mut lines = io::stdin().lock().lines();
+    let mut correct = 0;
+
+    for round in 1..=rounds {
+        let square = Square::from_index_unchecked(rng.below(64) as u8);
+        let overlay = [Overlay {
+            squares: BitBoard::from_square(square),
+            marker: '?',
+            tint: None,
+        }];
+
+        // the file letters would give half the answer away
+        let board = Board::default();
+        let diagram = board.with_overlays(&overlay).to_string();
+        for line in diagram.lines().skip(1) {
+            println!("{line}");
+        }
+
+        let Some(answer) = prompt(&mut lines, &format!("{round}/{rounds} which square? "))? else {
+            break;
+        };
+
+        if answer.parse::<Square>() == Ok(square) {
+            correct += 1;
+            println!("correct\n");
+        } else {
+            println!("no, that's {square}\n");
+        }
+    }
+
+    println!("{correct}/{rounds} correct");
+
+    Ok(())
+}
+
+fn blindfold(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut number = 1;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--game" => {
+                let value = args.next().ok_or_else(|| USAGE.to_owned())?;
+                number = value
+                    .parse()
+                    .ok()
+                    .filter(|&number| number > 0)
+                    .ok_or_else(|| format!("`--game` counts from 1, got `{value}`"))?;
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let path = path.ok_or_else(|| USAGE.to_owned())?;
+    let reader = File::open(&path).map_err(|err| format!("failed to open `{path}`: {err}"))?;
+
+    let raw = GameReader::new(BufReader::new(reader))
+        .nth(number - 1)
+        .ok_or_else(|| format!("`{path}` has no game {number}"))?
+        .map_err(|err| format!("failed to read `{path}`: {err}"))?;
+    let game = Game::parse(&raw.text).map_err(|err| format!("invalid game {number}: {err}"))?;
+
+    println!(
+        "{} - {}",
+        game.tag("White").unwrap_or("?"),
+        game.tag("Black").unwrap_or("?")
+    );
+    if game.start != Position::starting() {
+        println!("starting from {}", game.start.to_fen());
+    }
+    println!("enter for the next move, a square to see what's on it, `board` to look, `quit`\n");
+
+    let mut lines = io::stdin().lock().lines();
+    let mut position = game.start.clone();
+
+    for game_move in &game.moves {
+        let number = position.fullmove_counter;
+        let dots = match position.active_color {
+            Color::White => ".",
+            Color::Black => "...",
+        };
+        println!("{number}{dots} {}", game_move.san);
+
+        position.make_move_ext(game_move.p_move);
+
+        loop {
+            let Some(answer) = prompt(&mut lines, "> ")? else {
+                return Ok(());
+            };
+
+            match answer.as_str() {
+                "" => break,
+                "quit" => return Ok(()),
+                "board" => print!("{}", position.board),
+                _ => match answer.parse::<Square>() {
+                    Ok(square) => match position.board.get(square) {
+                        Some(piece) => println!(
+                            "{square}: {} {}",
+                            color_name(piece.color),
+                            piece.kind.name()
+                        ),
+                        None => println!("{square}: empty"),
+                    },
+                    Err(()) => println!("not a square, try e.g. e4"),
+                },
+            }
+        }
+    }
+
+    println!("{}", game.result);
+
+    Ok(())
+}
+
+/// Print a prompt and read a trimmed line, `None` once input ends.
+fn prompt<B: BufRead>(lines: &mut io::Lines<B>, prompt: &str) -> Result<Option<String>, String> {
+    print!("{prompt}");
+    io::stdout().flush().map_err(|err| err.to_string())?;
+
+    match lines.next() {
+        Some(line) => Ok(Some(line.map_err(|err| err.to_string())?.trim().to_owned())),
+        None => Ok(None),
+    }
+}
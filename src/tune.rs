@@ -0,0 +1,389 @@
+//! `sealion tune <dataset> [--epochs <n>] [--rate <r>] [--out <file>]`: Texel tuning of the
+//! evaluation weights.
+//!
+//! The dataset holds a quiet position and the result of its game per line, from white's side:
+//! `<fen> [1.0]`, `<fen> 1-0`, `<fen> | 1/2-1/2` or an EPD line with `c9 "0-1";`. A bare number
+//! after the FEN isn't taken for a result, it could as well be the fullmove counter. Material is
+//! a single value per piece for both phases, everything else is tuned apart. The weights are fitted
+//! so that a sigmoid of the evaluation predicts the results, by gradient descent from the current
+//! ones, and written out as the Rust items they come from. Progress goes to stderr.
+
+use std::io::{BufRead, BufReader};
+
+use sealion::eval::params::{Trace, Weights, MATERIAL, PSQT};
+use sealion::psqt::{Score, MAX_PHASE};
+use sealion::Board;
+
+const USAGE: &str = "usage: sealion tune <dataset> [--epochs <n>] [--rate <r>] [--out <file>]";
+
+/// Adam's decay rates of the gradient's mean and variance.
+const BETA: (f64, f64) = (0.9, 0.999);
+/// Last index of the piece-square tables.
+const PSQT_END: usize = PSQT + 6 * 64 - 1;
+
+/// Run the tune command with the remaining command line arguments.
+pub fn run(args: &[String]) {
+    if let Err(err) = run_impl(args) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run_impl(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut epochs = 200;
+    let mut rate = 1.0;
+    let mut out = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_owned());
+
+        match arg.as_str() {
+            "--epochs" => epochs = value()?.parse().map_err(|_| USAGE)?,
+            "--rate" => rate = value()?.parse().map_err(|_| USAGE)?,
+            "--out" => out = Some(value()?),
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg.clone()),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let path = path.ok_or_else(|| USAGE.to_owned())?;
+    let dataset = load(&path)?;
+    if dataset.is_empty() {
+        return Err(format!("`{path}` has no positions"));
+    }
+    eprintln!("loaded {} positions", dataset.len());
+
+    let mut weights = Weights::current()
+        .0
+        .iter()
+        .map(|score| [score.mg as f64, score.eg as f64])
+        .collect::<Vec<_>>();
+    let k = fit_scale(&dataset, &weights);
+    eprintln!("scale {k:.3}, error {:.6}", error(&dataset, &weights, k));
+
+    let mut mean = vec![[0.0; 2]; weights.len()];
+    let mut variance = vec![[0.0; 2]; weights.len()];
+    for epoch in 1..=epochs {
+        let gradient = gradient(&dataset, &weights, k);
+
+        for (index, gradient) in gradient.iter().enumerate() {
+            for phase in 0..2 {
+                let (m, v) = (&mut mean[index][phase], &mut variance[index][phase]);
+                *m = BETA.0 * *m + (1.0 - BETA.0) * gradient[phase];
+                *v = BETA.1 * *v + (1.0 - BETA.1) * gradient[phase] * gradient[phase];
+
+                let m = *m / (1.0 - BETA.0.powi(epoch));
+                let v = *v / (1.0 - BETA.1.powi(epoch));
+                weights[index][phase] -= rate * m / (v.sqrt() + 1e-8);
+            }
+        }
+
+        if epoch % 10 == 0 || epoch == epochs {
+            eprintln!("epoch {epoch}: error {:.6}", error(&dataset, &weights, k));
+        }
+    }
+
+    let weights = Weights(
+        weights
+            .iter()
+            .enumerate()
+            .map(|(index, &[mg, eg])| {
+                let round = |value: f64| match index {
+                    // the tables are stored as i8
+                    PSQT..=PSQT_END => value.round().clamp(i8::MIN as f64, i8::MAX as f64) as i32,
+                    _ => value.round() as i32,
+                };
+                Score::new(round(mg), round(eg))
+            })
+            .collect(),
+    );
+
+    match out {
+        Some(out) => std::fs::write(&out, weights.to_string())
+            .map_err(|err| format!("failed to write `{out}`: {err}")),
+        None => {
+            print!("{weights}");
+            Ok(())
+        }
+    }
+}
+
+/// The trace and result of every position, skipping blank lines and `#` comments.
+fn load(path: &str) -> Result<Vec<(Trace, f64)>, String> {
+    let file =
+        std::fs::File::open(path).map_err(|err| format!("failed to open `{path}`: {err}"))?;
+    let mut dataset = vec![];
+
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| format!("failed to read `{path}`: {err}"))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (board, result) = parse_line(line)
+            .ok_or_else(|| format!("line {}: expected a FEN and a result", number + 1))?;
+        dataset.push((Trace::new(&board), result));
+    }
+
+    Ok(dataset)
+}
+
+/// The board and result of a dataset line.
+fn parse_line(line: &str) -> Option<(Board, f64)> {
+    let (fen, result) = if let Some((fen, result)) = line.split_once('|') {
+        (fen, result.trim())
+    } else if let Some((fen, opcodes)) = line.split_once(" c9 ") {
+        let result = opcodes.split(';').next()?.trim().trim_matches('"');
+        (fen, result)
+    } else {
+        let (fen, last) = line.trim_end().rsplit_once(char::is_whitespace)?;
+        let result = match last.strip_prefix('[') {
+            Some(result) => result.strip_suffix(']')?,
+            None if last.contains('-') => last,
+            None => return None,
+        };
+        (fen, result)
+    };
+
+    let result = self::result(result)?;
+    // only the placement is evaluated, which also makes EPD without clocks fine
+    let board = fen
+        .split_whitespace()
+        .next()
+        .and_then(Board::from_fen_placement)?;
+
+    Some((board, result))
+}
+
+/// A result as white's score.
+fn result(token: &str) -> Option<f64> {
+    match token {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        _ => token
+            .parse()
+            .ok()
+            .filter(|result| [0.0, 0.5, 1.0].contains(result)),
+    }
+}
+
+/// Expected score for white of an evaluation, `k` scaling centipawns to winning chances.
+#[inline]
+fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+fn evaluate(trace: &Trace, weights: &[[f64; 2]]) -> f64 {
+    let phase = trace.phase as f64 / MAX_PHASE as f64;
+
+    trace
+        .terms
+        .iter()
+        .map(|&(index, count)| {
+            let [mg, eg] = weights[index as usize];
+            count as f64 * (mg * phase + eg * (1.0 - phase))
+        })
+        .sum()
+}
+
+/// Mean squared error of the predicted results.
+fn error(dataset: &[(Trace, f64)], weights: &[[f64; 2]], k: f64) -> f64 {
+    dataset
+        .iter()
+        .map(|(trace, result)| (result - sigmoid(evaluate(trace, weights), k)).powi(2))
+        .sum::<f64>()
+        / dataset.len() as f64
+}
+
+/// The error's gradient by the middlegame and endgame value of every weight. Material has a
+/// single value, so both of its halves get the gradient of the two together.
+fn gradient(dataset: &[(Trace, f64)], weights: &[[f64; 2]], k: f64) -> Vec<[f64; 2]> {
+    let mut gradient = vec![[0.0; 2]; weights.len()];
+
+    for (trace, result) in dataset {
+        let predicted = sigmoid(evaluate(trace, weights), k);
+        // derivative of the squared error by the evaluation
+        let slope = -2.0
+            * (result - predicted)
+            * predicted
+            * (1.0 - predicted)
+            * k
+            * std::f64::consts::LN_10
+            / 400.0;
+        let phase = trace.phase as f64 / MAX_PHASE as f64;
+
+        for &(index, count) in &trace.terms {
+            let gradient = &mut gradient[index as usize];
+            gradient[0] += slope * count as f64 * phase;
+            gradient[1] += slope * count as f64 * (1.0 - phase);
+        }
+    }
+
+    for gradient in &mut gradient {
+        gradient[0] /= dataset.len() as f64;
+        gradient[1] /= dataset.len() as f64;
+    }
+    for gradient in &mut gradient[MATERIAL..PSQT] {
+        *gradient = [gradient[0] + gradient[1]; 2];
+    }
+
+    gradient
+}
+
+/// The scale that fits the current weights best, so tuning changes the weights rather than
+/// stretching them all.
+fn fit_scale(dataset: &[(Trace, f64)], weights: &[[f64; 2]]) -> f64 {
+    let mut best = 1.0;
+    let mut step = 1.0;
+
+    // narrow down around the best scale so far, a tenth at a time
+    for _ in 0..4 {
+        best = (-10..=10)
+            .map(|offset| best + offset as f64 * step / 10.0)
+            .filter(|&k| k > 0.0)
+            .min_by(|&a, &b| error(dataset, weights, a).total_cmp(&error(dataset, weights, b)))
+            .unwrap();
+        step /= 10.0;
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    /// White is a knight up.
+    const KNIGHT_UP: &str = "4k3/8/8/8/8/8/8/3NK3 w - - 0 1";
+
+    fn current() -> Vec<[f64; 2]> {
+        Weights::current()
+            .0
+            .iter()
+            .map(|score| [score.mg as f64, score.eg as f64])
+            .collect()
+    }
+
+    fn trace(fen: &str) -> Trace {
+        Trace::new(&Board::from_fen_placement(fen.split(' ').next().unwrap()).unwrap())
+    }
+
+    #[test]
+    fn results() {
+        for (line, expected) in [
+            (format!("{START} [1.0]"), 1.0),
+            (format!("{START} [0.5]"), 0.5),
+            (format!("{START} 0-1"), 0.0),
+            (format!("{START} | 1/2-1/2"), 0.5),
+            (format!("{START} | 1"), 1.0),
+            ("8/8/8/8/8/8/8/K6k w - - c9 \"1-0\";".to_owned(), 1.0),
+            (
+                "8/8/8/8/8/8/8/K6k w - - c0 \"x\"; c9 \"0-1\";".to_owned(),
+                0.0,
+            ),
+        ] {
+            assert_eq!(
+                parse_line(&line).map(|(_, result)| result),
+                Some(expected),
+                "{line}"
+            );
+        }
+
+        for line in [
+            // the fullmove counter is no result
+            START,
+            "8/8/8/8/8/8/8/K6k w - -",
+            &format!("{START} [2]"),
+            &format!("{START} [1.0"),
+            &format!("{START} 1-1"),
+            "not/a/fen [1.0]",
+            "[1.0]",
+        ] {
+            assert!(parse_line(line).is_none(), "{line}");
+        }
+    }
+
+    #[test]
+    fn loading() {
+        let path = std::env::temp_dir().join(format!("sealion-tune-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        std::fs::write(
+            path,
+            format!("# comment\n\n{START} [0.5]\n{KNIGHT_UP} 1-0\n"),
+        )
+        .unwrap();
+        let dataset = load(path).unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset[1], (trace(KNIGHT_UP), 1.0));
+
+        std::fs::write(path, format!("{START} [0.5]\n{START}\n")).unwrap();
+        assert_eq!(
+            load(path).unwrap_err(),
+            "line 2: expected a FEN and a result"
+        );
+
+        std::fs::remove_file(path).unwrap();
+        assert!(load(path).is_err());
+    }
+
+    #[test]
+    fn scale() {
+        assert_eq!(sigmoid(0.0, 1.0), 0.5);
+        assert!((sigmoid(400.0, 1.0) - 10.0 / 11.0).abs() < 1e-12);
+        assert!((sigmoid(-400.0, 1.0) - 1.0 / 11.0).abs() < 1e-12);
+        assert!(sigmoid(100.0, 2.0) > sigmoid(100.0, 1.0));
+
+        // won once and drawn once, the best prediction is 0.75
+        let weights = current();
+        let dataset = [(trace(KNIGHT_UP), 1.0), (trace(KNIGHT_UP), 0.5)];
+        let score = evaluate(&dataset[0].0, &weights);
+        let k = fit_scale(&dataset, &weights);
+        assert!((sigmoid(score, k) - 0.75).abs() < 1e-3, "{k}");
+        assert!((k - 400.0 * 3f64.log10() / score).abs() < 1e-3, "{k}");
+    }
+
+    #[test]
+    fn descent() {
+        let mut weights = current();
+        let dataset = [
+            (trace(START), 0.5),
+            (trace(KNIGHT_UP), 0.5),
+            (trace("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1"), 1.0),
+        ];
+        let k = 1.0;
+        let gradient = gradient(&dataset, &weights, k);
+
+        // the same as the error's slope, material moving as a whole
+        let h = 1e-3;
+        let knight = MATERIAL + 1;
+        let slope = |index: usize, phases: &[usize]| {
+            let mut weights = weights.clone();
+            phases.iter().for_each(|&phase| weights[index][phase] += h);
+            let up = error(&dataset, &weights, k);
+            phases
+                .iter()
+                .for_each(|&phase| weights[index][phase] -= 2.0 * h);
+            (up - error(&dataset, &weights, k)) / (2.0 * h)
+        };
+        assert_eq!(gradient[knight][0], gradient[knight][1]);
+        assert!((gradient[knight][0] - slope(knight, &[0, 1])).abs() < 1e-9);
+        assert!(gradient[knight][0] > 0.0);
+        let knight_d1 = PSQT + 64 + 59;
+        assert!(gradient[knight_d1][1] != 0.0);
+        assert!((gradient[knight_d1][1] - slope(knight_d1, &[1])).abs() < 1e-9);
+
+        // a small step against the gradient lowers the error
+        let before = error(&dataset, &weights, k);
+        for (weight, gradient) in weights.iter_mut().zip(&gradient) {
+            weight[0] -= 1e3 * gradient[0];
+            weight[1] -= 1e3 * gradient[1];
+        }
+        assert!(error(&dataset, &weights, k) < before);
+    }
+}
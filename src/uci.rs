@@ -0,0 +1,483 @@
+//! `sealion [--profile <name>]`: UCI protocol loop, the default mode of the binary.
+//!
+//! <https://www.shredderchess.com/chess-features/uci-universal-chess-interface.html>
+
+use std::io::{stdin, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use sealion::movegen::{GameStatus, MoveList, PositionState};
+use sealion::score::Calibration;
+use sealion::search::{mate_in, GoClocks, Limits, Searcher, TranspositionTable};
+use sealion::{Color, Move, MoveExt, Position};
+
+use crate::crash;
+use crate::options::{Options, PROFILES};
+
+/// Depth of a `go` without limits.
+const DEFAULT_DEPTH: u8 = 5;
+
+/// A running `go`, stopped through the shared flag.
+struct Search {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Search {
+    fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // an infinite search may be done already and waiting to be stopped
+        self.handle.thread().unpark();
+        let _ = self.handle.join();
+    }
+}
+
+/// Read UCI commands from stdin until `quit` or end of input.
+pub fn run(args: &[String]) {
+    crash::install();
+
+    match parse_args(args) {
+        Ok(options) => run_loop(options),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let usage = format!("usage: sealion [--profile <{}>]", PROFILES.join("|"));
+
+    match args {
+        [] => Ok(Options::default()),
+        [flag, name] if flag == "--profile" => {
+            Options::profile(name).ok_or_else(|| format!("unknown profile `{name}`\n{usage}"))
+        }
+        _ => Err(usage),
+    }
+}
+
+fn run_loop(mut options: Options) {
+    let mut position = Position::starting();
+    let mut search: Option<Search> = None;
+    // kept from one `go` to the next, replaced when the size changes
+    let mut table = Arc::new(TranspositionTable::new(options.hash as usize));
+    crash::set_position(&position, "position startpos");
+    crash::set_state("options", format!("{options:?}"));
+
+    for line in stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let mut tokens = line.split_whitespace();
+        crash::record_command(&line);
+
+        match tokens.next() {
+            Some("uci") => {
+                println!("id name sealion {}", env!("CARGO_PKG_VERSION"));
+                println!("id author {}", env!("CARGO_PKG_AUTHORS"));
+                for option in options.declarations() {
+                    println!("{option}");
+                }
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            // not part of the protocol, but what most engines print the board for
+            Some("d") => println!("{}", position.diagram()),
+            Some("setoption") => match parse_option(tokens) {
+                Some((name, value)) => {
+                    if let Err(err) = options.set(&name, &value) {
+                        println!("info string {err}");
+                    }
+                    if table.megabytes() != options.hash as usize {
+                        match TranspositionTable::try_new(options.hash as usize) {
+                            Some(new_table) => table = Arc::new(new_table),
+                            None => {
+                                println!(
+                                    "info string failed to allocate {} MiB of hash, keeping {} MiB",
+                                    options.hash,
+                                    table.megabytes()
+                                );
+                                options.hash = table.megabytes() as u32;
+                            }
+                        }
+                    }
+                    crash::set_state("options", format!("{options:?}"));
+                }
+                None => println!("info string malformed option `{line}`"),
+            },
+            Some("ucinewgame") => {
+                if let Some(search) = search.take() {
+                    search.stop();
+                }
+                table.clear();
+                position = Position::starting();
+                crash::set_position(&position, "position startpos");
+            }
+            Some("position") => match parse_position(tokens) {
+                Ok(new_position) => {
+                    position = new_position;
+                    crash::set_position(&position, &line);
+                }
+                Err(err) => println!("info string {err}"),
+            },
+            Some("go") => {
+                if let Some(search) = search.take() {
+                    search.stop();
+                }
+                match parse_go(tokens) {
+                    Ok(limits) => {
+                        search = Some(go(position.clone(), limits, options.clone(), table.clone()))
+                    }
+                    Err(err) => println!("info string {err}"),
+                }
+            }
+            Some("stop") => {
+                if let Some(search) = search.take() {
+                    search.stop();
+                }
+            }
+            Some("quit") => break,
+            // unknown commands are ignored, as the protocol asks
+            _ => {}
+        }
+    }
+
+    if let Some(search) = search {
+        search.stop();
+    }
+}
+
+/// `setoption name <name> [value <value>]`, names and values can contain spaces.
+pub(crate) fn parse_option<'a>(tokens: impl Iterator<Item = &'a str>) -> Option<(String, String)> {
+    let option = tokens.collect::<Vec<_>>().join(" ");
+    let option = option.strip_prefix("name ")?;
+
+    Some(match option.split_once(" value ") {
+        Some((name, value)) => (name.to_owned(), value.to_owned()),
+        None => (option.to_owned(), String::new()),
+    })
+}
+
+/// `position [startpos | fen <fen>] [moves <move>...]`
+pub(crate) fn parse_position<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+) -> Result<Position, String> {
+    let position = match tokens.next() {
+        Some("startpos") => Position::starting(),
+        Some("fen") => {
+            let fen = tokens
+                .by_ref()
+                .take_while(|&token| token != "moves")
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let position = sealion::fen::from_str(&fen)
+                .map_err(|err| format!("invalid fen `{fen}`: {err}"))?;
+            position
+                .validate()
+                .map_err(|err| format!("invalid position `{fen}`: {err}"))?;
+
+            return parse_moves(position, tokens);
+        }
+        _ => return Err("expected startpos or fen".to_owned()),
+    };
+
+    match tokens.next() {
+        Some("moves") | None => {}
+        Some(token) => return Err(format!("unexpected `{token}`")),
+    }
+
+    parse_moves(position, tokens)
+}
+
+pub(crate) fn parse_moves<'a>(
+    mut position: Position,
+    tokens: impl Iterator<Item = &'a str>,
+) -> Result<Position, String> {
+    for token in tokens {
+        let mut wanted = token
+            .parse::<Move>()
+            .map_err(|_| format!("invalid move `{token}`"))?;
+
+        // Chess960 castling, king takes rook
+        if let Some(castle) = position.castle_of(wanted) {
+            wanted.to = castle.move_to();
+        }
+
+        let state = PositionState::generate(&position);
+        let moves = match MoveList::generate(&state) {
+            MoveList::Moves(moves) => moves,
+            _ => vec![],
+        };
+
+        let p_move = moves
+            .into_iter()
+            .find(|p_move| p_move.to_move() == wanted)
+            .ok_or_else(|| format!("illegal move `{token}`"))?;
+
+        position.make_move_ext(p_move);
+    }
+
+    Ok(position)
+}
+
+/// `go [depth <n>] [nodes <n>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>]
+/// [binc <ms>] [movestogo <n>] [infinite]`, other limits are ignored.
+pub(crate) fn parse_go<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Limits, String> {
+    let mut limits = Limits::default();
+    let mut infinite = false;
+
+    while let Some(token) = tokens.next() {
+        let mut value = || {
+            tokens
+                .next()
+                .and_then(|value| value.parse::<i64>().ok())
+                .ok_or_else(|| format!("expected a number after `{token}`"))
+        };
+        // some GUIs send a negative time left once the flag is about to fall
+        let mut millis = || value().map(|ms| Duration::from_millis(ms.max(0) as u64));
+
+        match token {
+            "depth" => limits.depth = Some(value()?.clamp(0, u8::MAX as i64) as u8),
+            "nodes" => limits.nodes = Some(value()?.max(0) as u64),
+            "movetime" => limits.move_time = Some(millis()?),
+            "wtime" | "btime" | "winc" | "binc" => {
+                let time = millis()?;
+                let clock = limits.clock.get_or_insert_with(GoClocks::default);
+                let color = if token.starts_with('w') {
+                    Color::White
+                } else {
+                    Color::Black
+                } as usize;
+
+                if token.ends_with("time") {
+                    clock.time[color] = time;
+                } else {
+                    clock.increment[color] = time;
+                }
+            }
+            "movestogo" => {
+                let moves = value()?.clamp(0, u32::MAX as i64) as u32;
+                limits
+                    .clock
+                    .get_or_insert_with(GoClocks::default)
+                    .moves_to_go = Some(moves);
+            }
+            "infinite" => infinite = true,
+            _ => {}
+        }
+    }
+
+    if infinite {
+        // searches until `stop`, whatever the clocks say
+        limits.clock = None;
+        limits.move_time = None;
+    } else if limits == Limits::default() {
+        limits.depth = Some(DEFAULT_DEPTH);
+    }
+
+    Ok(limits)
+}
+
+/// Search on a separate thread, printing an `info` line per iteration and the `bestmove`.
+fn go(
+    position: Position,
+    mut limits: Limits,
+    options: Options,
+    table: Arc<TranspositionTable>,
+) -> Search {
+    limits.overhead = Some(Duration::from_millis(options.move_overhead as u64));
+    crash::set_state("search", format!("running {limits:?}"));
+    crash::set_state("hash", format!("{} MiB", table.megabytes()));
+
+    let mut searcher = Searcher::with_table(limits.clone(), table.clone());
+    let stop = searcher.stop_flag();
+    let searcher_stop = stop.clone();
+
+    let handle = std::thread::spawn(move || {
+        if let Some(termination) = position.termination() {
+            println!("info string game over: {termination}");
+        }
+
+        let start = Instant::now();
+        let result = searcher.search(&mut position.clone(), |result| {
+            let elapsed = start.elapsed();
+            println!(
+                "info depth {} {} nodes {} time {} nps {} hashfull {} pv {}",
+                result.depth,
+                uci_score(result.score, &options),
+                result.nodes,
+                elapsed.as_millis(),
+                (result.nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)) as u64,
+                table.hashfull(),
+                uci_line(&position, &result.pv, options.chess960)
+            );
+        });
+
+        // `go infinite` must not answer before `stop`, even with the search at its end
+        if limits.is_infinite() {
+            while !searcher_stop.load(Ordering::Relaxed) {
+                std::thread::park();
+            }
+        }
+
+        match result.best_move() {
+            Some(p_move) => println!("bestmove {}", uci_move(&position, p_move, options.chess960)),
+            None => println!("bestmove 0000"),
+        }
+        crash::set_state("search", format!("finished {limits:?}"));
+    });
+
+    Search { stop, handle }
+}
+
+/// Moves played one after another from `position`, as UCI writes them.
+fn uci_line(position: &Position, moves: &[MoveExt], chess960: bool) -> String {
+    let mut position = position.clone();
+
+    moves
+        .iter()
+        .map(|&p_move| {
+            let uci = uci_move(&position, p_move, chess960);
+            position.make_move_ext(p_move);
+            uci.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A move as UCI writes it, castling is king takes rook in Chess960 mode.
+fn uci_move(position: &Position, p_move: MoveExt, chess960: bool) -> Move {
+    let mut p_move = p_move.to_move();
+
+    if chess960 {
+        if let Some(castle) = position.castle_of(p_move) {
+            p_move.to = castle.rook_from;
+        }
+    }
+
+    p_move
+}
+
+/// The `score` part of an `info` line, rescaled for display.
+fn uci_score(score: i32, options: &Options) -> String {
+    if let Some(moves) = mate_in(score) {
+        return format!("score mate {moves}");
+    }
+
+    let calibration = Calibration::MATERIAL;
+    let mut out = format!("score cp {}", calibration.normalize(score));
+
+    if options.show_wdl {
+        let (win, draw, loss) = calibration.wdl(score);
+        out += &format!(" wdl {win} {draw} {loss}");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn go(command: &str) -> Result<Limits, String> {
+        parse_go(command.split_whitespace())
+    }
+
+    fn position(command: &str) -> Result<Position, String> {
+        parse_position(command.split_whitespace())
+    }
+
+    fn placement(position: &Position) -> String {
+        position.to_fen().split(' ').next().unwrap().to_owned()
+    }
+
+    #[test]
+    fn go_limits() {
+        assert_eq!(go("").unwrap().depth, Some(DEFAULT_DEPTH));
+        assert_eq!(go("depth 300 nodes -5").unwrap().depth, Some(u8::MAX));
+        assert_eq!(go("nodes -5").unwrap().nodes, Some(0));
+
+        let clock = go("wtime 1000 btime -20 winc 10 binc 20 movestogo 5")
+            .unwrap()
+            .clock
+            .unwrap();
+        assert_eq!(clock.time, [Duration::from_secs(1), Duration::ZERO]);
+        assert_eq!(
+            clock.increment,
+            [Duration::from_millis(10), Duration::from_millis(20)]
+        );
+        assert_eq!(clock.moves_to_go, Some(5));
+        assert_eq!(go("movetime -1").unwrap().move_time, Some(Duration::ZERO));
+
+        // searches until stopped, clocks or not
+        assert_eq!(go("infinite").unwrap(), Limits::default());
+        assert_eq!(
+            go("wtime 1000 btime 1000 movetime 50 infinite").unwrap(),
+            Limits::default()
+        );
+        assert_eq!(go("infinite depth 3").unwrap().depth, Some(3));
+
+        assert!(go("depth").is_err());
+        assert!(go("wtime soon").is_err());
+    }
+
+    #[test]
+    fn options() {
+        let option = |command: &str| parse_option(command.split_whitespace());
+
+        assert_eq!(
+            option("name Hash value 64"),
+            Some(("Hash".to_owned(), "64".to_owned()))
+        );
+        assert_eq!(
+            option("name Move Overhead value 100"),
+            Some(("Move Overhead".to_owned(), "100".to_owned()))
+        );
+        assert_eq!(
+            option("name Clear Hash"),
+            Some(("Clear Hash".to_owned(), String::new()))
+        );
+        assert_eq!(
+            option("name Book File value my book.bin"),
+            Some(("Book File".to_owned(), "my book.bin".to_owned()))
+        );
+        assert_eq!(option("Hash value 64"), None);
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(position("startpos").unwrap(), Position::starting());
+        let moved = position("startpos moves e2e4 e7e5 g1f3").unwrap();
+        assert_eq!(
+            placement(&moved),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R"
+        );
+        let fen =
+            position("fen rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2").unwrap();
+        assert_eq!(fen.to_fen(), moved.to_fen());
+
+        // castling as the king's move and as king takes rook
+        let fen = "fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves";
+        for castle in ["e1g1", "e1h1"] {
+            let castled = position(&format!("{fen} {castle}")).unwrap();
+            assert_eq!(placement(&castled), "r3k2r/8/8/8/8/8/8/R4RK1");
+        }
+
+        // Chess960, with Shredder-FEN castling rights
+        let castled =
+            position("fen 1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w GBgb - 0 1 moves e1b1 e8g8")
+                .unwrap();
+        assert_eq!(
+            placement(&castled),
+            "1r3rk1/pppppppp/8/8/8/8/PPPPPPPP/2KR2R1"
+        );
+
+        assert!(position("startpos moves e2e5").is_err());
+        assert!(position("startpos moves e2").is_err());
+        assert!(position("startpos e2e4").is_err());
+        assert!(position("fen 8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+        assert!(position("").is_err());
+    }
+}